@@ -1,22 +1,35 @@
 use crate::{
+    checkpoint::{Checkpoint, CheckpointStep, ColorState, InputHasher},
     color_map::{ColorConfig, ColorMap, LabColorMapSettings},
     darkness::Darkness,
     image::Image,
-    line_selector::{self, LineSelector},
-    nail_table::{self, BakedNailTable, BakedSegment},
+    instructions::{self, Instructions, PaletteColor},
+    length::ThreadLength,
+    line_selector::{self, LineItemSelector, LineSelector},
+    machine::{Machine, MachineError, Motion, Planner},
+    metrics::Quality,
+    nail_distancer::NailDistance,
+    nail_table::{self, BakedNailTable, BakedSegment, LineCoverage},
     nails,
     verboser::{Message, Verboser},
+    geometry::{circle, Point, Segment},
+    raster::{Canvas, RasterError, RasterSize, RasterStyle},
+    stop::{ErrorWindow, StopCriteria, StopReason},
     Float, Grid, NailTable,
 };
 use image::RgbImage;
-use num_traits::{AsPrimitive, ToPrimitive};
+use num_traits::{AsPrimitive, ConstZero, ToPrimitive};
 use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
-use std::ops::{Deref, Range};
+use std::{
+    hash::Hasher,
+    ops::{Deref, Range},
+};
 use thiserror::Error as ThisError;
 
 #[derive(Copy, Clone)]
 struct NextLine<S, L> {
     line: *mut BakedSegment<S>,
+    line_idx: usize,
     color_idx: usize,
 
     to_nail: usize,
@@ -29,16 +42,14 @@ unsafe impl<S: Send, L: Send> Send for NextLine<S, L> {}
 unsafe impl<S: Sync, L: Sync> Sync for NextLine<S, L> {}
 
 struct NextLineWeighted<S, L> {
-    next: NextLine<S, L>,
+    next: Option<NextLine<S, L>>,
     weight: S,
 }
 
 impl<S: Float, L> Default for NextLineWeighted<S, L> {
-    #[allow(invalid_value)]
     fn default() -> Self {
         Self {
-            //SAFETY: a -infinity weight will never be selected as best weight
-            next: unsafe { core::mem::MaybeUninit::uninit().assume_init() },
+            next: None,
             weight: -S::INFINITY,
         }
     }
@@ -49,31 +60,72 @@ struct BatchBuffer<S, L> {
     result: NextLineWeighted<S, L>,
 }
 
-impl<S, L> BatchBuffer<S, L> {
+impl<S: Float, L> BatchBuffer<S, L> {
     pub fn new(nail_count: usize) -> Vec<Self> {
         let buffer_count = num_cpus::get();
-        let chunk_size = (nail_count + buffer_count - 1) / buffer_count;
+        let chunk_size = nail_count.div_ceil(buffer_count);
         (0..buffer_count)
             .map(|index| {
                 let start = index * chunk_size;
                 BatchBuffer {
-                            range: start..nail_count.min(start + chunk_size),
-                            #[allow(invalid_value)]
-                            //Reason: will be initialized as default on get_best_line() before use
-                            result: unsafe { std::mem::MaybeUninit::uninit().assume_init() },
-                        }
+                    range: start..nail_count.min(start + chunk_size),
+                    result: Default::default(),
+                }
             })
             .collect()
     }
 }
 
+// Incremental version of `Algorithm::build_rgb` at the image resolution. Threads are laid in the
+// reverse order they are computed, so a new thread only paints the pixels no thread covers yet.
+struct Preview {
+    buffer: Vec<u8>,
+    painted: Vec<bool>,
+    grid: Grid,
+}
+
+impl Preview {
+    fn new(grid: Grid) -> Self {
+        Self {
+            buffer: vec![255; grid.height * grid.width * 3],
+            painted: vec![false; grid.height * grid.width],
+            grid,
+        }
+    }
+
+    fn draw<S: Float>(&mut self, grid: &Grid, segment: &Segment<S>, color: (u8, u8, u8)) {
+        for idx in grid.get_pixel_indexes_in_segment(segment) {
+            if !std::mem::replace(&mut self.painted[idx], true) {
+                self.buffer[3 * idx..3 * idx + 3].copy_from_slice(&[color.0, color.1, color.2]);
+            }
+        }
+    }
+
+    fn image(&self) -> RgbImage {
+        RgbImage::from_vec(
+            self.grid.width as u32,
+            self.grid.height as u32,
+            self.buffer.clone(),
+        )
+        .expect("the buffer matches the grid")
+    }
+}
+
+type ColorMaps<N> = Vec<ColorMap<<N as nails::Handle>::Scalar, <N as nails::Handle>::Link>>;
+
 pub struct Algorithm<N: nails::Handle> {
-    color_maps: Vec<ColorMap<N::Scalar, N::Link>>,
+    color_maps: ColorMaps<N>,
     table: BakedNailTable<N>,
     steps: Vec<NextLine<N::Scalar, N::Link>>,
     buffers: Vec<BatchBuffer<N::Scalar, N::Link>>,
     groups: LineSelector,
     grid: Grid,
+    thread_width: N::Scalar,
+    stop_reason: StopReason,
+    // Fingerprint of the inputs checkpoints are stamped with.
+    inputs: u64,
+    // Error estimate of the current computation, for the plateau criterion.
+    errors: ErrorWindow<N::Scalar>,
 }
 
 impl<N: nails::Handle> Deref for Algorithm<N> {
@@ -85,21 +137,402 @@ impl<N: nails::Handle> Deref for Algorithm<N> {
 }
 
 impl<N: nails::Handle<Scalar: Float>> Algorithm<N> {
-    #[must_use]
+    #[allow(clippy::too_many_arguments)]
     pub fn new<D: Darkness<N::Scalar>>(
         table: NailTable<N>,
-        min_nail_distance: usize,
+        min_nail_distance: impl Into<NailDistance<N::Scalar>>,
         image: &Image<N::Scalar>,
         palette: impl IntoIterator<Item = ColorConfig<N::Link>>,
         darkness: D,
+        coverage: LineCoverage<N::Scalar>,
+        line_config: &impl line_selector::Builder<N::Scalar>,
+        stop: StopCriteria<N::Scalar>,
+        verboser: &mut impl Verboser,
+    ) -> Result<Self, Error<N::Error>>
+    where
+        u8: AsPrimitive<N::Scalar>,
+        usize: AsPrimitive<N::Scalar>,
+    {
+        let mut algorithm = Self::prepare(
+            table,
+            min_nail_distance,
+            image,
+            palette,
+            coverage,
+            line_config,
+            verboser,
+        )?;
+        algorithm.compute(verboser, darkness, &stop);
+        Ok(algorithm)
+    }
+
+    /// Restarts the computation `checkpoint` was taken from, given the arguments of
+    /// [`Algorithm::new`] it was started with. Fails with [`Error::CheckpointMismatch`] unless the
+    /// image, the nails, the palette and the line selector hash as they did. `darkness` is not
+    /// part of the hash, and `stop` may differ to end the computation elsewhere. The plateau
+    /// criterion measures the error from the history the checkpoint kept.
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_checkpoint<D: Darkness<N::Scalar>>(
+        table: NailTable<N>,
+        min_nail_distance: impl Into<NailDistance<N::Scalar>>,
+        image: &Image<N::Scalar>,
+        palette: impl IntoIterator<Item = ColorConfig<N::Link>>,
+        darkness: D,
+        coverage: LineCoverage<N::Scalar>,
+        line_config: &impl line_selector::Builder<N::Scalar>,
+        stop: StopCriteria<N::Scalar>,
+        checkpoint: &Checkpoint,
+        verboser: &mut impl Verboser,
+    ) -> Result<Self, Error<N::Error>>
+    where
+        u8: AsPrimitive<N::Scalar>,
+        usize: AsPrimitive<N::Scalar>,
+    {
+        let mut algorithm = Self::prepare(
+            table,
+            min_nail_distance,
+            image,
+            palette,
+            coverage,
+            line_config,
+            verboser,
+        )?;
+        algorithm.restore(checkpoint)?;
+        algorithm.compute(verboser, darkness, &stop);
+        Ok(algorithm)
+    }
+
+    // Everything `Algorithm::new` sets up before computing.
+    fn prepare(
+        table: NailTable<N>,
+        min_nail_distance: impl Into<NailDistance<N::Scalar>>,
+        image: &Image<N::Scalar>,
+        palette: impl IntoIterator<Item = ColorConfig<N::Link>>,
+        coverage: LineCoverage<N::Scalar>,
+        line_config: &impl line_selector::Builder<N::Scalar>,
+        verboser: &mut impl Verboser,
+    ) -> Result<Self, Error<N::Error>>
+    where
+        u8: AsPrimitive<N::Scalar>,
+    {
+        let palette = Self::color_maps(&table, image, palette)?;
+        let table = Self::bake(table, min_nail_distance, image, &coverage, verboser)?;
+        let groups = line_config
+            .build_line_selector(image, &palette, verboser)
+            .map_err(Error::LineSelector)?;
+        if verboser.is_cancelled() {
+            return Err(Error::Cancelled);
+        }
+        Ok(Self::assemble(table, palette, groups, image, coverage.thread_width))
+    }
+
+    fn assemble(
+        table: BakedNailTable<N>,
+        color_maps: ColorMaps<N>,
+        groups: LineSelector,
+        image: &Image<N::Scalar>,
+        thread_width: N::Scalar,
+    ) -> Self {
+        let mut algorithm = Self {
+            grid: *image.grid(),
+            steps: Vec::new(),
+            buffers: BatchBuffer::new(table.nails().len()),
+            table,
+            color_maps,
+            groups,
+            thread_width,
+            stop_reason: StopReason::Exhausted,
+            inputs: 0,
+            errors: ErrorWindow::new(N::Scalar::ZERO),
+        };
+        algorithm.inputs = algorithm.fingerprint(image);
+        algorithm.errors = ErrorWindow::new(algorithm.error());
+        algorithm
+    }
+
+    // Sum of the weights left in every color map.
+    fn error(&mut self) -> N::Scalar {
+        self.color_maps
+            .iter_mut()
+            .flat_map(|color_map| color_map.weights().iter())
+            .fold(N::Scalar::ZERO, |sum, &weight| sum + weight)
+    }
+
+    // Hash of everything but the line selector a checkpoint must be restored on.
+    fn fingerprint(&self, image: &Image<N::Scalar>) -> u64 {
+        let mut hasher = InputHasher::new();
+        let mut scalar = |value: N::Scalar| {
+            hasher.write_u64(value.to_f64().unwrap_or_default().to_bits())
+        };
+        for pixel in image.pixels() {
+            scalar(pixel.l);
+            scalar(pixel.a);
+            scalar(pixel.b);
+        }
+        for line in self.table.segments() {
+            let segment = line.segment();
+            scalar(segment.start.x);
+            scalar(segment.start.y);
+            scalar(segment.end.x);
+            scalar(segment.end.y);
+        }
+        scalar(self.thread_width);
+        hasher.write_usize(std::mem::size_of::<N::Scalar>());
+        hasher.write_usize(self.grid.width);
+        hasher.write_usize(self.grid.height);
+        hasher.write_usize(self.table.nails().len());
+        hasher.write_usize(self.table.segments().len());
+        for color in &self.color_maps {
+            hasher.write(&[color.color.0, color.color.1, color.color.2]);
+            hasher.write_usize(color.nail);
+            hasher.write_usize(color.link.into());
+        }
+        hasher.finish()
+    }
+
+    // Fingerprint of the inputs along with the layout of the line selector.
+    fn checkpoint_inputs(&self) -> u64 {
+        let mut hasher = InputHasher::new();
+        hasher.write_u64(self.inputs);
+        self.groups.hash_layout(&mut hasher);
+        hasher.finish()
+    }
+
+    /// State of the computation, to restart it with [`Algorithm::from_checkpoint`].
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            inputs: self.checkpoint_inputs(),
+            steps: self
+                .steps
+                .iter()
+                .map(|step| CheckpointStep {
+                    line_idx: step.line_idx,
+                    color_idx: step.color_idx,
+                    from_nail: step.from_nail,
+                    from_link: step.from_link.into(),
+                    to_nail: step.to_nail,
+                    to_link: step.to_link.into(),
+                })
+                .collect(),
+            colors: self
+                .color_maps
+                .iter()
+                .map(|color_map| ColorState {
+                    weights: color_map
+                        .weights
+                        .iter()
+                        .map(|weight| weight.to_f64().unwrap_or_default())
+                        .collect(),
+                    nail: color_map.curr_nail,
+                    link: color_map.curr_link.into(),
+                })
+                .collect(),
+            selector: self.groups.counters(),
+            errors: self
+                .errors
+                .map(|error| error.to_f64())
+                .unwrap_or_else(|| ErrorWindow::new(0.0)),
+        }
+    }
+
+    // Takes the state of `checkpoint`, on an algorithm without steps yet.
+    fn restore(&mut self, checkpoint: &Checkpoint) -> Result<(), Error<N::Error>> {
+        let nail_count = self.table.nails().len();
+        let line_count = self.table.segments().len();
+        let matches = checkpoint.inputs == self.checkpoint_inputs()
+            && checkpoint.colors.len() == self.color_maps.len()
+            && checkpoint
+                .colors
+                .iter()
+                .zip(&self.color_maps)
+                .all(|(color, color_map)| {
+                    color.weights.len() == color_map.weights.len() && color.nail < nail_count
+                })
+            && checkpoint.steps.iter().all(|step| {
+                step.line_idx < line_count
+                    && step.color_idx < self.color_maps.len()
+                    && step.from_nail < nail_count
+                    && step.to_nail < nail_count
+            });
+        if !matches {
+            return Err(Error::CheckpointMismatch);
+        }
+        let mut steps = Vec::with_capacity(checkpoint.steps.len());
+        for step in &checkpoint.steps {
+            let line: *mut _ = &mut self.table.segments_mut()[step.line_idx];
+            steps.push(NextLine {
+                line,
+                line_idx: step.line_idx,
+                color_idx: step.color_idx,
+                to_nail: step.to_nail,
+                to_link: Self::link(step.to_link).ok_or(Error::CheckpointMismatch)?,
+                from_nail: step.from_nail,
+                from_link: Self::link(step.from_link).ok_or(Error::CheckpointMismatch)?,
+            });
+        }
+        for (color_map, color) in self.color_maps.iter_mut().zip(&checkpoint.colors) {
+            color_map.curr_link = Self::link(color.link).ok_or(Error::CheckpointMismatch)?;
+            color_map.curr_nail = color.nail;
+            for (weight, &saved) in color_map.weights.iter_mut().zip(&color.weights) {
+                *weight = num_traits::NumCast::from(saved).ok_or(Error::CheckpointMismatch)?;
+            }
+        }
+        if !self.groups.set_counters(&checkpoint.selector) {
+            return Err(Error::CheckpointMismatch);
+        }
+        self.errors = checkpoint
+            .errors
+            .map(num_traits::NumCast::from)
+            .ok_or(Error::CheckpointMismatch)?;
+        for step in &steps {
+            unsafe { (*step.line).mark_used() };
+        }
+        self.steps = steps;
+        Ok(())
+    }
+
+    /// Draws more threads on the result, each color going on from the nail and link it stopped at,
+    /// over the weights the threads drawn so far left on `image`, the image it was computed for.
+    /// `line_config` selects the new threads only and replaces the line selector. Stops under
+    /// `stop` like [`Algorithm::new`], the length budget covering every thread of the result.
+    pub fn resume<D: Darkness<N::Scalar>>(
+        &mut self,
+        image: &Image<N::Scalar>,
+        darkness: D,
         line_config: &impl line_selector::Builder<N::Scalar>,
+        stop: StopCriteria<N::Scalar>,
+        verboser: &mut impl Verboser,
+    ) -> Result<(), Error<N::Error>>
+    where
+        u8: AsPrimitive<N::Scalar>,
+        usize: AsPrimitive<N::Scalar>,
+    {
+        self.groups = line_config
+            .build_line_selector(image, &self.color_maps, verboser)
+            .map_err(Error::LineSelector)?;
+        if verboser.is_cancelled() {
+            return Err(Error::Cancelled);
+        }
+        self.errors = ErrorWindow::new(self.error());
+        self.compute(verboser, darkness, &stop);
+        Ok(())
+    }
+
+    /// Rebuilds the result `instructions` describe on `table`, the nail table they were computed
+    /// on, darkening the weights of `image` step after step as [`Algorithm::new`] does. Selections
+    /// of a line already drawn are not part of the instructions, so the weights may differ
+    /// slightly from the original run. The line selector is spent, with an item per color
+    /// capped at the lines of that color.
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_instructions<D: Darkness<N::Scalar>>(
+        table: NailTable<N>,
+        min_nail_distance: impl Into<NailDistance<N::Scalar>>,
+        image: &Image<N::Scalar>,
+        instructions: &Instructions,
+        darkness: D,
+        coverage: LineCoverage<N::Scalar>,
         verboser: &mut impl Verboser,
     ) -> Result<Self, Error<N::Error>>
     where
         u8: AsPrimitive<N::Scalar>,
         usize: AsPrimitive<N::Scalar>,
     {
-        let palette = palette
+        let header = &instructions.header;
+        let matches = header.width == image.grid().width
+            && header.height == image.grid().height
+            && header.links == <N::Links as nails::Links>::LEN
+            && header.nails.len() == table.nails().len()
+            && header.nails.iter().zip(table.nails()).all(|(&[x, y], &nail)| {
+                // Nails are expected at the same place within half a pixel.
+                let position = table.handle().position(nail);
+                let dx = position.x.to_f64().unwrap_or(f64::NAN) - x;
+                let dy = position.y.to_f64().unwrap_or(f64::NAN) - y;
+                dx * dx + dy * dy <= 0.25
+            });
+        if !matches {
+            return Err(Error::Mismatch);
+        }
+        let palette = header
+            .palette
+            .iter()
+            .map(|color| {
+                Ok(ColorConfig::new(
+                    color.name.clone(),
+                    (color.rgb[0], color.rgb[1], color.rgb[2]),
+                    color.nail,
+                    Self::link(color.link).ok_or(Error::Mismatch)?,
+                ))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let palette = Self::color_maps(&table, image, palette)?;
+        let table = Self::bake(table, min_nail_distance, image, &coverage, verboser)?;
+        let mut algorithm = Self::assemble(
+            table,
+            palette,
+            LineSelector::from_iter([]),
+            image,
+            coverage.thread_width,
+        );
+        let mut spent = vec![(0, 0.0); algorithm.color_maps.len()];
+        let mut last = vec![None; algorithm.color_maps.len()];
+        // Instructions run backwards through the steps, see `Algorithm::instructions`.
+        for (idx, step) in instructions.steps.iter().enumerate().rev() {
+            let invalid = || Error::InvalidStep(idx);
+            if step.color >= algorithm.color_maps.len()
+                || step.from >= algorithm.table.nails().len()
+                || step.to >= algorithm.table.nails().len()
+            {
+                return Err(invalid());
+            }
+            let from_link = Self::link(step.to_link).ok_or_else(invalid)?;
+            let to_link = Self::link(step.from_link).ok_or_else(invalid)?;
+            let line_idx = algorithm
+                .table
+                .distancer()
+                .index_of::<N::Links>(step.to, from_link, step.from, to_link)
+                .ok_or_else(invalid)?;
+            let line: *mut _ = &mut algorithm.table.segments_mut()[line_idx];
+            if unsafe { (*line).is_used() } {
+                return Err(invalid());
+            }
+            let next = NextLine {
+                line,
+                line_idx,
+                color_idx: step.color,
+                to_nail: step.from,
+                to_link,
+                from_nail: step.to,
+                from_link,
+            };
+            algorithm.darken(&next, &darkness);
+            let previous = last[step.color].map(|idx| &algorithm.steps[idx]);
+            let length = algorithm.step_length(previous, &next);
+            let color_map = &mut algorithm.color_maps[step.color];
+            color_map.curr_link = algorithm.table.handle().get_next_link(to_link);
+            color_map.curr_nail = step.from;
+            unsafe { (*line).mark_used() };
+            spent[step.color].0 += 1;
+            spent[step.color].1 += length.to_f32().unwrap_or_default();
+            last[step.color] = Some(algorithm.steps.len());
+            algorithm.steps.push(next);
+        }
+        algorithm.groups = LineSelector::from_iter([spent
+            .into_iter()
+            .enumerate()
+            .map(|(color_idx, (count, length))| LineItemSelector::spent(color_idx, count, length))
+            .collect()]);
+        Ok(algorithm)
+    }
+
+    fn color_maps(
+        table: &NailTable<N>,
+        image: &Image<N::Scalar>,
+        palette: impl IntoIterator<Item = ColorConfig<N::Link>>,
+    ) -> Result<ColorMaps<N>, Error<N::Error>>
+    where
+        u8: AsPrimitive<N::Scalar>,
+    {
+        palette
             .into_iter()
             .map(|color| {
                 if color.nail >= table.nails().len() {
@@ -111,23 +544,38 @@ impl<N: nails::Handle<Scalar: Float>> Algorithm<N> {
                     ))
                 }
             })
-            .collect::<Result<Vec<_>, _>>()?;
+            .collect()
+    }
+
+    fn bake(
+        table: NailTable<N>,
+        min_nail_distance: impl Into<NailDistance<N::Scalar>>,
+        image: &Image<N::Scalar>,
+        coverage: &LineCoverage<N::Scalar>,
+        verboser: &mut impl Verboser,
+    ) -> Result<BakedNailTable<N>, Error<N::Error>> {
         verboser.verbose(Message::Baking);
-        let table = BakedNailTable::new(table, min_nail_distance)
-            .map_err(Error::Baking)?;
-        let groups = line_config
-            .build_line_selector(image, &palette, verboser)
-            .map_err(|err| Error::LineSelector(err))?;
-        let mut algorithm = Self {
-            grid: *image.grid(),
-            steps: Vec::new(),
-            buffers: BatchBuffer::new(table.nails().len()),
-            table,
-            color_maps: palette,
-            groups,
-        };
-        algorithm.compute(verboser, darkness);
-        Ok(algorithm)
+        let mut table =
+            BakedNailTable::new(table, min_nail_distance, verboser).map_err(|err| match err {
+                nail_table::Error::Cancelled => Error::Cancelled,
+                err => Error::Baking(err),
+            })?;
+        if let Some(budget) = coverage.pixel_budget {
+            let bytes = table.pixel_memory_estimate(coverage.thread_width);
+            verboser.verbose(Message::BakingPixels { bytes, budget });
+            if bytes <= budget {
+                table.bake_pixels(image.grid(), coverage.thread_width, verboser);
+            }
+        }
+        if verboser.is_cancelled() {
+            return Err(Error::Cancelled);
+        }
+        Ok(table)
+    }
+
+    // Link of the nails whose index is `idx`.
+    fn link(idx: usize) -> Option<N::Link> {
+        N::LINKS.into_iter().find(|&link| link.into() == idx)
     }
 
     pub fn build_svg(&self, line_tickness: f32) -> svg::Document {
@@ -194,6 +642,42 @@ impl<N: nails::Handle<Scalar: Float>> Algorithm<N> {
         unsafe { RgbImage::from_vec(grid.width, grid.height, buffer).unwrap_unchecked() }
     }
 
+    /// Renders the result on the grid of the computed image, so it can be compared pixel by pixel
+    /// with the image the threads were computed for. The size of `style` is ignored.
+    pub fn render_source(&self, style: &RasterStyle) -> Result<RgbImage, RasterError> {
+        self.build_raster(&RasterStyle {
+            size: RasterSize::Pixels(self.grid.width.max(self.grid.height) as u32),
+            ..*style
+        })
+    }
+
+    /// Measures the result rendered with `style` against `image`, the image it was computed for.
+    /// `None` if `image` has a different size or is too big to render with `style`.
+    pub fn quality(&self, image: &Image<N::Scalar>, style: &RasterStyle) -> Option<Quality> {
+        Quality::measure(image, &self.render_source(style).ok()?)
+    }
+
+    /// Rasterizes the result like [`Algorithm::build_svg`] does, at the size and with the style
+    /// given. Fails if it would take more than [`crate::MAX_RASTER_SAMPLES`] samples.
+    pub fn build_raster(&self, style: &RasterStyle) -> Result<RgbImage, RasterError> {
+        let mut canvas = Canvas::new(self.grid, style)?;
+        if let Some(nails) = &style.nails {
+            for &nail in self.table.nails().iter() {
+                if let Some(center) = self.handle().position(nail).cast() {
+                    canvas.nail(center, nails);
+                }
+            }
+        }
+        for step in self.steps.iter().rev() {
+            let segment = unsafe { (*step.line).segment() };
+            let color = unsafe { self.color_maps.get_unchecked(step.color_idx) }.color;
+            if let Some(segment) = segment.cast() {
+                canvas.thread(segment, color);
+            }
+        }
+        Ok(canvas.into())
+    }
+
     pub fn build_instructions(&self) -> String
     where
         N: nails::Handle<Link: ToString>,
@@ -221,61 +705,349 @@ impl<N: nails::Handle<Scalar: Float>> Algorithm<N> {
             }
         }
 
+        // Nails with a single link have no direction worth writing.
+        let line = |name: &str, nail: usize, link: N::Link| {
+            if <N::Links as nails::Links>::LEN == 1 {
+                format!("{} {} \n", name, nail)
+            } else {
+                format!("{} {} {} \n", name, nail, link.to_string())
+            }
+        };
+
         instructions.extend(init_nails.into_iter().filter_map(|step| {
-            step.map(|init_nail| {
-                format!(
-                    "{} {} {} \n",
-                    init_nail.0,
-                    init_nail.1,
-                    init_nail.2.to_string()
-                )
-            })
+            step.map(|init_nail| line(init_nail.0, init_nail.1, init_nail.2))
         }));
 
         instructions.extend(iter.map(|step| {
-            format!(
-                "{} {} {} \n",
+            line(
                 unsafe { self.color_maps.get_unchecked(step.color_idx).name.as_str() },
                 step.from_nail,
-                step.from_link.to_string()
+                step.from_link,
             )
         }));
         instructions
     }
 
+    /// Structured counterpart of [`Algorithm::build_instructions`], with the ends and the thread
+    /// length of every step.
+    pub fn instructions(&self) -> Instructions {
+        let to_array = |point: Point<N::Scalar>| {
+            [
+                point.x.to_f64().unwrap_or_default(),
+                point.y.to_f64().unwrap_or_default(),
+            ]
+        };
+        let nails: Vec<_> = self
+            .table
+            .nails()
+            .iter()
+            .map(|&nail| to_array(self.handle().position(nail)))
+            .collect();
+        let mut lengths = vec![0.0; self.color_maps.len()];
+        let mut last: Vec<Option<&Segment<N::Scalar>>> = vec![None; self.color_maps.len()];
+        let steps = self
+            .steps
+            .iter()
+            .rev()
+            .map(|step| {
+                // Instructions run backwards through the steps, so each one is wound from the nail
+                // it was computed to.
+                let segment = unsafe { (*step.line).segment() };
+                let from = self.handle().position(self.table.nails()[step.to_nail]);
+                let (start, end) =
+                    if segment.start.sq_distance(&from) <= segment.end.sq_distance(&from) {
+                        (segment.start, segment.end)
+                    } else {
+                        (segment.end, segment.start)
+                    };
+                let wrap = last[step.color_idx].map_or(N::Scalar::ZERO, |previous| {
+                    self.handle()
+                        .wrap_length(self.table.nails()[step.to_nail], previous, segment)
+                });
+                lengths[step.color_idx] +=
+                    (start.distance(&end) + wrap).to_f64().unwrap_or_default();
+                last[step.color_idx] = Some(segment);
+                let color = &self.color_maps[step.color_idx];
+                instructions::Step {
+                    color: step.color_idx,
+                    name: color.name.clone(),
+                    rgb: [color.color.0, color.color.1, color.color.2],
+                    from: step.to_nail,
+                    from_link: step.to_link.into(),
+                    to: step.from_nail,
+                    to_link: step.from_link.into(),
+                    start: to_array(start),
+                    end: to_array(end),
+                    length: lengths[step.color_idx],
+                }
+            })
+            .collect();
+        Instructions {
+            header: instructions::Header {
+                version: instructions::VERSION,
+                width: self.grid.width,
+                height: self.grid.height,
+                nails,
+                frame_len: self.table.frame_len(),
+                links: <N::Links as nails::Links>::LEN,
+                board: None,
+                palette: self
+                    .color_maps
+                    .iter()
+                    .map(|color| PaletteColor {
+                        name: color.name.clone(),
+                        rgb: [color.color.0, color.color.1, color.color.2],
+                        nail: color.nail,
+                        link: color.link.into(),
+                    })
+                    .collect(),
+                parameters: serde_json::Value::Null,
+            },
+            steps,
+        }
+    }
+
+    /// Moves winding the result on `machine`, in the order of [`Algorithm::build_instructions`].
+    /// The ring stops at the first nail of each color for the thread to be tied, and at every
+    /// change of color for the thread to be swapped.
+    pub fn motions(&self, machine: &Machine) -> Result<Vec<Motion>, MachineError> {
+        let to_f64 = |point: Point<N::Scalar>| Point {
+            x: point.x.to_f64().unwrap_or_default(),
+            y: point.y.to_f64().unwrap_or_default(),
+        };
+        let handle = self.handle();
+        let nails = self.table.nails();
+        let positions: Vec<_> = nails
+            .iter()
+            .map(|&nail| to_f64(handle.position(nail)))
+            .collect();
+        let center = Point {
+            x: self.grid.width as f64 * 0.5,
+            y: self.grid.height as f64 * 0.5,
+        };
+        let mut planner = Planner::new(machine, machine.ring_angles(&positions, center)?);
+
+        // Instructions run backwards through the steps, each one from its last nail to its first.
+        let order: Vec<_> = self.steps.iter().rev().collect();
+        let mut following = vec![None; order.len()];
+        let mut next_of_color = vec![None; self.color_maps.len()];
+        for (idx, step) in order.iter().enumerate().rev() {
+            following[idx] = next_of_color[step.color_idx];
+            next_of_color[step.color_idx] = Some(idx);
+        }
+        // Ends of a segment as the one on `nail` and the other one.
+        let ends = |segment: &Segment<N::Scalar>, nail: usize| {
+            let (start, end) = (to_f64(segment.start), to_f64(segment.end));
+            let at = positions[nail];
+            if start.sq_distance(&at) <= end.sq_distance(&at) {
+                (start, end)
+            } else {
+                (end, start)
+            }
+        };
+        let cross = |a: Point<f64>, b: Point<f64>| a.x * b.y - a.y * b.x;
+
+        let mut current = vec![None; self.color_maps.len()];
+        let mut active = None;
+        for (idx, step) in order.iter().enumerate() {
+            let color = &self.color_maps[step.color_idx];
+            if active != Some(step.color_idx) {
+                active = Some(step.color_idx);
+                let (r, g, b) = color.color;
+                match current[step.color_idx] {
+                    None => planner.pause_at(
+                        step.to_nail,
+                        format!(
+                            "Tie {} (#{:02X}{:02X}{:02X}) at nail {}",
+                            color.name, r, g, b, step.to_nail
+                        ),
+                    ),
+                    Some(nail) => planner.pause_at(
+                        nail,
+                        format!("Change to {} at nail {}", color.name, nail),
+                    ),
+                }
+            }
+
+            let nail = step.from_nail;
+            let clockwise = match handle.wrap_direction(nails[nail], step.from_link) {
+                // Lines are built from their bigger nail, the thread arriving there turns with
+                // the link and the one arriving at the smaller nail against it.
+                Some(direction) => {
+                    (direction == circle::Direction::ClockWise) == (nail > step.to_nail)
+                }
+                // Nails without a sense turn with the thread, arriving along this step and
+                // leaving along the next one of its color.
+                None => following[idx].is_none_or(|next: usize| {
+                    let (touch, from) = ends(unsafe { (*step.line).segment() }, nail);
+                    let (_, to) = ends(unsafe { (*order[next].line).segment() }, nail);
+                    cross(touch - from, to - touch) >= 0.0
+                }),
+            };
+            planner.wrap(nail, clockwise);
+            current[step.color_idx] = Some(nail);
+        }
+        Ok(planner.finish(String::from("Tie off every thread")))
+    }
+
+    /// Threads drawn so far.
+    pub fn threads(&self) -> usize {
+        self.steps.len()
+    }
+
     pub fn line_selector(&self) -> &LineSelector {
         &self.groups
     }
 
-    fn compute<D: Darkness<N::Scalar>>(&mut self, verboser: &mut impl Verboser, darkness: D)
-    where
+    /// Whether the computation was cancelled before drawing every thread, the steps computed so
+    /// far are kept.
+    pub fn is_cancelled(&self) -> bool {
+        self.stop_reason == StopReason::Cancelled
+    }
+
+    /// Why the computation ended.
+    pub fn stop_reason(&self) -> StopReason {
+        self.stop_reason
+    }
+
+    fn compute<D: Darkness<N::Scalar>>(
+        &mut self,
+        verboser: &mut impl Verboser,
+        darkness: D,
+        stop: &StopCriteria<N::Scalar>,
+    ) where
         usize: AsPrimitive<N::Scalar>,
     {
-        loop {
+        let interval = verboser.preview_interval().filter(|&interval| interval > 0);
+        let checkpoints = verboser.checkpoint_interval().filter(|&interval| interval > 0);
+        let mut preview = interval.map(|_| Preview::new(self.grid));
+        let mut length = N::Scalar::ZERO;
+        // Last step of each color, which the next one wraps a nail after.
+        let mut last = vec![None; self.color_maps.len()];
+        // Threads already drawn by a resumed result count towards the budget and the preview.
+        for (idx, step) in self.steps.iter().enumerate() {
+            length += self.step_length(last[step.color_idx].map(|idx| &self.steps[idx]), step);
+            last[step.color_idx] = Some(idx);
+            if let Some(preview) = &mut preview {
+                let color = self.color_maps[step.color_idx].color;
+                preview.draw(&self.grid, unsafe { (*step.line).segment() }, color);
+            }
+        }
+        self.stop_reason = loop {
             verboser.verbose(Message::Computing(self.steps.len()));
-            if let Some(next) = self.get_best_line() {
-                let color_map = unsafe { self.color_maps.get_unchecked_mut(next.color_idx) };
-                let line = unsafe { &mut *next.line };                
-
-                for point in self.grid.get_pixel_indexes_in_segment(line.segment()) {
-                    let weight = unsafe { color_map.weights().get_unchecked_mut(point) };
-                    *weight = darkness.compute(*weight);
-                }
-                if line.is_used() {
-                    continue;
-                }                
-                
+            if verboser.is_cancelled() {
+                break StopReason::Cancelled;
+            }
+            let Some(NextLineWeighted {
+                next,
+                weight: best_weight,
+            }) = self.get_best_line()
+            else {
+                break StopReason::Exhausted;
+            };
+            // A color standing on a nail without any partner can not go on, the others still can.
+            let Some(next) = next else {
+                self.groups.retire_last();
+                continue;
+            };
+            if stop.min_weight.is_some_and(|min_weight| best_weight < min_weight) {
+                break StopReason::MinWeight;
+            }
+            let segment = *unsafe { (*next.line).segment() };
+            let previous = unsafe { *last.get_unchecked(next.color_idx) };
+            let line_length = self.step_length(previous.map(|idx| &self.steps[idx]), &next);
+            if stop
+                .length_budget
+                .is_some_and(|budget| length + line_length > budget)
+            {
+                break StopReason::LengthBudget;
+            }
+            let improvement = self.darken(&next, &darkness);
+            let color_map = unsafe { self.color_maps.get_unchecked_mut(next.color_idx) };
+            let plateaued = self.errors.step(improvement, stop.plateau.as_ref());
+            self.groups.add_length(line_length.to_f32().unwrap_or_default());
+            let line = unsafe { &mut *next.line };
+            if !line.is_used() {
                 color_map.curr_link = self.table.handle().get_next_link(next.to_link);
                 color_map.curr_nail = next.to_nail;
                 line.mark_used();
+                length += line_length;
+                last[next.color_idx] = Some(self.steps.len());
                 self.steps.push(next);
-            } else {
-                return;
+                if let (Some(preview), Some(interval)) = (&mut preview, interval) {
+                    preview.draw(&self.grid, &segment, color_map.color);
+                    if self.steps.len().is_multiple_of(interval) {
+                        verboser.preview(preview.image());
+                    }
+                }
+                if checkpoints.is_some_and(|interval| self.steps.len().is_multiple_of(interval)) {
+                    verboser.checkpoint(self.checkpoint());
+                }
             }
+            if plateaued {
+                break StopReason::Plateau;
+            }
+        };
+        verboser.verbose(Message::Stopped(self.stop_reason));
+        if let Some(preview) = preview {
+            verboser.preview(preview.image());
         }
     }
 
-    fn get_best_line(&mut self) -> Option<NextLine<N::Scalar, N::Link>>
+    // Darkens the weights under the line of `next`, returning how much the error dropped.
+    fn darken<D: Darkness<N::Scalar>>(
+        &mut self,
+        next: &NextLine<N::Scalar, N::Link>,
+        darkness: &D,
+    ) -> N::Scalar {
+        let segment = unsafe { (*next.line).segment() };
+        let color_map = unsafe { self.color_maps.get_unchecked_mut(next.color_idx) };
+        let mut improvement = N::Scalar::ZERO;
+        for (point, coverage) in
+            self.table
+                .coverage(next.line_idx, segment, &self.grid, self.thread_width)
+        {
+            let weight = unsafe { color_map.weights().get_unchecked_mut(point) };
+            let delta = (darkness.compute(*weight) - *weight) * coverage;
+            *weight += delta;
+            improvement -= delta;
+        }
+        improvement
+    }
+
+    /// Length of thread used by every color, wraps around the nails included.
+    pub fn thread_length(&self) -> ThreadLength {
+        let mut per_color = vec![0.0; self.color_maps.len()];
+        let mut last = vec![None; self.color_maps.len()];
+        for step in self.steps.iter() {
+            let length = self.step_length(last[step.color_idx], step);
+            per_color[step.color_idx] += length.to_f64().unwrap_or_default();
+            last[step.color_idx] = Some(step);
+        }
+        ThreadLength::new(per_color, self.grid.width.max(self.grid.height) as f64)
+    }
+
+    // Length of the line of `next` plus the thread wrapped around its first nail since `previous`,
+    // the last step of the same color.
+    fn step_length(
+        &self,
+        previous: Option<&NextLine<N::Scalar, N::Link>>,
+        next: &NextLine<N::Scalar, N::Link>,
+    ) -> N::Scalar {
+        let segment = unsafe { (*next.line).segment() };
+        let wrap = previous.map_or(N::Scalar::ZERO, |previous| {
+            self.handle().wrap_length(
+                self.table.nails()[next.from_nail],
+                unsafe { (*previous.line).segment() },
+                segment,
+            )
+        });
+        segment.start.distance(&segment.end) + wrap
+    }
+
+    // `None` once the line selector is exhausted, without a line if the selected color has no
+    // valid partner from its nail.
+    fn get_best_line(&mut self) -> Option<NextLineWeighted<N::Scalar, N::Link>>
     where
         usize: AsPrimitive<N::Scalar>,
     {
@@ -283,14 +1055,14 @@ impl<N: nails::Handle<Scalar: Float>> Algorithm<N> {
 
         impl<S> SyncLineTable<S> {
             pub unsafe fn get_unchecked(&self, idx: usize) -> *mut BakedSegment<S> {
-                (*self.0).get_unchecked_mut(idx)
+                (&mut (*self.0)).get_unchecked_mut(idx)
             }
         }
 
         unsafe impl<S: Sync> Sync for SyncLineTable<S> {}
         unsafe impl<S: Send> Send for SyncLineTable<S> {}
 
-        self.groups.select_next().and_then(|color_idx| {
+        self.groups.select_next().map(|color_idx| {
             let color_map = unsafe { self.color_maps.get_unchecked(color_idx) };
             let mut best_weight = -N::Scalar::INFINITY;
             let mut best_line = None;
@@ -314,20 +1086,26 @@ impl<N: nails::Handle<Scalar: Float>> Algorithm<N> {
                                 )
                             };
                             let line: *mut _ = unsafe { line_table.get_unchecked(line_idx) };
-                            let segment = unsafe { &*line };
+                            let segment = unsafe { (*line).segment() };
 
-                            let weight = color_map.calculate_weight(&segment, &self.grid);
+                            let weight = color_map.calculate_weight(self.table.coverage(
+                                line_idx,
+                                segment,
+                                &self.grid,
+                                self.thread_width,
+                            ));
                             if weight > buffer.result.weight {
                                 buffer.result = NextLineWeighted {
                                     weight,
-                                    next: NextLine {
+                                    next: Some(NextLine {
                                         line,
+                                        line_idx,
                                         color_idx,
                                         to_nail,
                                         to_link,
                                         from_nail: color_map.curr_nail,
                                         from_link: color_map.curr_link,
-                                    },
+                                    }),
                                 };
                             }
                         }
@@ -337,11 +1115,14 @@ impl<N: nails::Handle<Scalar: Float>> Algorithm<N> {
             for BatchBuffer { range: _, result } in self.buffers.iter() {
                 if result.weight > best_weight {
                     best_weight = result.weight;
-                    best_line = Some(result.next);
+                    best_line = result.next;
                 }
             }
 
-            best_line
+            NextLineWeighted {
+                next: best_line,
+                weight: best_weight,
+            }
         })
     }
 }
@@ -354,4 +1135,270 @@ pub enum Error<N> {
     InvalidInitialNail,
     #[error(transparent)]
     LineSelector(line_selector::Error),
+    #[error("The computation was cancelled.")]
+    Cancelled,
+    #[error("The instructions were computed on another image or nail table.")]
+    Mismatch,
+    #[error("Step {0} of the instructions is not a line of the nail table.")]
+    InvalidStep(usize),
+    #[error("The checkpoint was taken on another image or configuration.")]
+    CheckpointMismatch,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        darkness::FlatDarkness,
+        line_config::{LineConfig, LineGroupConfig, LineItemConfig},
+        nails::{circular, point, Circular, PointNail},
+        verboser::Silent,
+    };
+
+    // Cancels once `.0` threads are drawn, or right away if `.1` starts as `true`.
+    struct CancelAt(usize, bool);
+
+    impl Verboser for CancelAt {
+        fn verbose(&mut self, message: Message) {
+            if let Message::Computing(threads) = message {
+                self.1 |= threads >= self.0;
+            }
+        }
+
+        fn is_cancelled(&self) -> bool {
+            self.1
+        }
+    }
+
+    // Keeps every preview, one each 10 threads.
+    #[derive(Default)]
+    struct Previews(Vec<RgbImage>);
+
+    impl Verboser for Previews {
+        fn verbose(&mut self, _: Message) {}
+
+        fn preview_interval(&self) -> Option<usize> {
+            Some(10)
+        }
+
+        fn preview(&mut self, image: RgbImage) {
+            self.0.push(image);
+        }
+    }
+
+    // Dark disc on a white background.
+    fn image() -> Image<f32> {
+        RgbImage::from_fn(48, 48, |x, y| {
+            let dark = (x as i32 - 24).pow(2) + (y as i32 - 24).pow(2) < 200;
+            image::Rgb(if dark { [0; 3] } else { [255; 3] })
+        })
+        .into()
+    }
+
+    fn algorithm(
+        threads: usize,
+        stop: StopCriteria<f32>,
+        verboser: &mut impl Verboser,
+    ) -> Result<Algorithm<PointNail>, Error<point::Error>> {
+        let image = image();
+        let table = NailTable::ellipse(*image.grid(), PointNail::new(), 60, &mut Silent);
+        Algorithm::new(
+            table,
+            5,
+            &image,
+            [ColorConfig::new("Black".to_string(), (0, 0, 0), 0, Default::default())],
+            FlatDarkness(0.2),
+            LineCoverage::new(1.0),
+            &LineConfig::new(vec![LineGroupConfig::new(vec![LineItemConfig::new(0, threads)])]),
+            stop,
+            verboser,
+        )
+    }
+
+    #[test]
+    fn cancelled_runs_keep_their_threads() {
+        let cancelled = algorithm(100, StopCriteria::new(), &mut CancelAt(10, false)).unwrap();
+        assert!(cancelled.is_cancelled());
+        assert_eq!(cancelled.stop_reason(), StopReason::Cancelled);
+        assert_eq!(cancelled.steps.len(), 10);
+        let finished = algorithm(100, StopCriteria::new(), &mut CancelAt(usize::MAX, false)).unwrap();
+        assert!(!finished.is_cancelled());
+        assert_eq!(finished.stop_reason(), StopReason::Exhausted);
+        assert!(finished.steps.len() > 10);
+    }
+
+    #[test]
+    fn cancelling_before_threading_fails() {
+        assert!(matches!(
+            algorithm(100, StopCriteria::new(), &mut CancelAt(0, true)),
+            Err(Error::Cancelled)
+        ));
+    }
+
+    #[test]
+    fn previews_end_on_the_result() {
+        let mut previews = Previews::default();
+        let algorithm = algorithm(300, StopCriteria::new(), &mut previews).unwrap();
+        let threads = algorithm.steps.len();
+        assert!(threads > 20);
+        // One every 10 threads, plus the finished result.
+        assert_eq!(previews.0.len(), threads / 10 + 1);
+        assert!(previews.0.last().unwrap() == &algorithm.build_rgb(algorithm.grid));
+        assert!(previews.0[0] != previews.0[1]);
+    }
+
+    #[test]
+    fn stops_below_the_min_weight() {
+        let stop = StopCriteria::new().with_min_weight(f32::INFINITY);
+        let algorithm = algorithm(100, stop, &mut Silent).unwrap();
+        assert_eq!(algorithm.stop_reason(), StopReason::MinWeight);
+        assert!(algorithm.steps.is_empty());
+    }
+
+    #[test]
+    fn stops_before_exceeding_the_length_budget() {
+        let length = |algorithm: &Algorithm<PointNail>| -> f32 {
+            algorithm
+                .steps
+                .iter()
+                .map(|step| {
+                    let segment = unsafe { (*step.line).segment() };
+                    segment.start.distance(&segment.end)
+                })
+                .sum()
+        };
+        let unbounded = algorithm(100, StopCriteria::new(), &mut Silent).unwrap();
+        let budget = length(&unbounded) / 2.0;
+        let bounded =
+            algorithm(100, StopCriteria::new().with_length_budget(budget), &mut Silent).unwrap();
+        assert_eq!(bounded.stop_reason(), StopReason::LengthBudget);
+        assert!(length(&bounded) <= budget);
+        assert!(bounded.steps.len() < unbounded.steps.len());
+    }
+
+    #[test]
+    fn stops_once_the_error_plateaus() {
+        let stop = StopCriteria::new().with_plateau(10, 0.5);
+        let algorithm = algorithm(300, stop, &mut Silent).unwrap();
+        assert_eq!(algorithm.stop_reason(), StopReason::Plateau);
+        assert!(algorithm.steps.len() < 300);
+    }
+
+    // Sense of every wrap of `motions`, `true` for clockwise.
+    fn wraps(motions: &[Motion]) -> Vec<bool> {
+        motions
+            .iter()
+            .filter_map(|motion| match motion {
+                Motion::Comment(comment) => Some(!comment.ends_with("counterclockwise")),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn wraps_follow_the_links() {
+        let image = image();
+        let table = NailTable::ellipse(*image.grid(), Circular::new(0.5), 60, &mut Silent);
+        let mut algorithm = Algorithm::new(
+            table,
+            5,
+            &image,
+            [ColorConfig::new("Black".to_string(), (0, 0, 0), 0, Default::default())],
+            FlatDarkness(0.2),
+            LineCoverage::new(1.0),
+            &LineConfig::new(vec![LineGroupConfig::new(vec![LineItemConfig::new(0, 40)])]),
+            StopCriteria::new(),
+            &mut Silent,
+        )
+        .unwrap();
+        let machine = Machine::new();
+        let clockwise = |algorithm: &Algorithm<Circular>| {
+            algorithm
+                .steps
+                .iter()
+                .rev()
+                .map(|step| {
+                    (step.from_link.0 == circle::Direction::ClockWise)
+                        == (step.from_nail > step.to_nail)
+                })
+                .collect::<Vec<_>>()
+        };
+        let links = clockwise(&algorithm);
+        assert!(links.contains(&true) && links.contains(&false));
+        assert_eq!(wraps(&algorithm.motions(&machine).unwrap()), links);
+
+        // Linking every nail the other way round wraps every nail the other way round.
+        for step in algorithm.steps.iter_mut() {
+            step.from_link = circular::Direction(match step.from_link.0 {
+                circle::Direction::ClockWise => circle::Direction::CounterClockWise,
+                circle::Direction::CounterClockWise => circle::Direction::ClockWise,
+            });
+        }
+        let flipped = wraps(&algorithm.motions(&machine).unwrap());
+        assert_eq!(flipped, links.iter().map(|clockwise| !clockwise).collect::<Vec<_>>());
+    }
+
+    fn replay(
+        instructions: &Instructions,
+        nails: usize,
+    ) -> Result<Algorithm<PointNail>, Error<point::Error>> {
+        let image = image();
+        let table = NailTable::ellipse(*image.grid(), PointNail::new(), nails, &mut Silent);
+        Algorithm::from_instructions(
+            table,
+            5,
+            &image,
+            instructions,
+            FlatDarkness(0.2),
+            LineCoverage::new(1.0),
+            &mut Silent,
+        )
+    }
+
+    #[test]
+    fn replays_its_instructions() {
+        let original = algorithm(50, StopCriteria::new(), &mut Silent).unwrap();
+        let instructions = original.instructions();
+        let replayed = replay(&instructions, 60).unwrap();
+        assert_eq!(replayed.instructions().steps, instructions.steps);
+        assert!(replayed.build_rgb(replayed.grid) == original.build_rgb(original.grid));
+        assert_eq!(replayed.build_instructions(), original.build_instructions());
+    }
+
+    #[test]
+    fn refuses_instructions_of_other_nails() {
+        let mut instructions = algorithm(20, StopCriteria::new(), &mut Silent)
+            .unwrap()
+            .instructions();
+        assert!(matches!(replay(&instructions, 61), Err(Error::Mismatch)));
+
+        // A step between nails too close to be linked.
+        let last = instructions.steps.len() - 1;
+        instructions.steps[last].to = (instructions.steps[last].from + 1) % 60;
+        assert!(matches!(replay(&instructions, 60), Err(Error::InvalidStep(idx)) if idx == last));
+    }
+
+    #[test]
+    fn resumes_where_it_stopped() {
+        let config = |threads| {
+            LineConfig::new(vec![LineGroupConfig::new(vec![LineItemConfig::new(0, threads)])])
+        };
+        let mut resumed = algorithm(50, StopCriteria::new(), &mut Silent).unwrap();
+        let first: Vec<_> = resumed.steps.iter().map(|step| step.line_idx).collect();
+        resumed
+            .resume(&image(), FlatDarkness(0.2), &config(50), StopCriteria::new(), &mut Silent)
+            .unwrap();
+        let threads = resumed.steps.len();
+        assert!(threads > first.len());
+        assert!(resumed.steps[..first.len()].iter().map(|step| step.line_idx).eq(first));
+        // Each thread goes on from the nail the previous one of its color ended at.
+        assert!(resumed.steps.windows(2).all(|pair| pair[1].from_nail == pair[0].to_nail));
+
+        // The length budget covers the threads drawn before resuming.
+        let spent = resumed.thread_length().total() as f32;
+        let stop = StopCriteria::new().with_length_budget(spent);
+        resumed.resume(&image(), FlatDarkness(0.2), &config(50), stop, &mut Silent).unwrap();
+        assert_eq!(resumed.steps.len(), threads);
+        assert_eq!(resumed.stop_reason(), StopReason::LengthBudget);
+    }
 }
@@ -15,6 +15,7 @@ pub struct AutoLineConfig<
 > {
     pub groups: G,
     pub threads: usize,
+    #[serde(skip)]
     _panthom: std::marker::PhantomData<(S, C)>,
 }
 
@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    raster::ThreadWidth,
+    Grid,
+};
+
+/// Physical dimensions of a board in millimeters, converted into pixels of the image grid the
+/// threads are computed on. The longest side of the image spans the diameter of the board, so the
+/// same board gives the same physical result at any resolution.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Board {
+    /// Diameter of round boards, the longest side of other ones.
+    pub diameter: f32,
+    pub nail_diameter: f32,
+    pub thread_diameter: f32,
+}
+
+impl Board {
+    pub fn new(diameter: f32, nail_diameter: f32, thread_diameter: f32) -> Self {
+        Self {
+            diameter,
+            nail_diameter,
+            thread_diameter,
+        }
+    }
+
+    /// Pixels of `grid` per millimeter of the board.
+    pub fn pixels_per_millimeter(&self, grid: &Grid) -> f32 {
+        grid.width.max(grid.height) as f32 / self.diameter
+    }
+
+    /// `millimeters` in pixels of `grid`.
+    pub fn pixels(&self, grid: &Grid, millimeters: f32) -> f32 {
+        millimeters * self.pixels_per_millimeter(grid)
+    }
+
+    /// Radius of the nails in pixels of `grid`, as taken by [`crate::nails::Circular::new`].
+    pub fn nail_radius(&self, grid: &Grid) -> f32 {
+        self.pixels(grid, self.nail_diameter * 0.5)
+    }
+
+    /// Width of the thread in pixels of `grid`, as taken by [`crate::LineCoverage::new`].
+    pub fn thread_width(&self, grid: &Grid) -> f32 {
+        self.pixels(grid, self.thread_diameter)
+    }
+
+    /// Width of the thread when rasterizing the result.
+    pub fn raster_thread_width(&self) -> ThreadWidth {
+        ThreadWidth::Millimeters {
+            width: self.thread_diameter,
+            board_diameter: self.diameter,
+        }
+    }
+
+    /// Width and height in millimeters of an image laid out on `grid`.
+    pub fn size(&self, grid: &Grid) -> (f32, f32) {
+        let scale = self.pixels_per_millimeter(grid);
+        (grid.width as f32 / scale, grid.height as f32 / scale)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_its_size_at_any_resolution() {
+        let board = Board::new(500.0, 2.0, 0.5);
+        let (low, high) = (Grid::new(400, 500), Grid::new(800, 1000));
+        assert_eq!(board.nail_radius(&low), 1.0);
+        assert_eq!(board.nail_radius(&high), 2.0);
+        assert_eq!(board.thread_width(&low), 0.5);
+        assert_eq!(board.thread_width(&high), 1.0);
+        assert_eq!(board.size(&low), (500.0, 400.0));
+        assert_eq!(board.size(&high), (500.0, 400.0));
+        // A 1000 pixel raster prints the board at 2 pixels per millimeter.
+        assert_eq!(board.raster_thread_width().pixels(1000.0), 1.0);
+    }
+}
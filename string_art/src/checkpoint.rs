@@ -0,0 +1,89 @@
+//! Snapshots of a running computation, to restart it after a crash.
+//!
+//! A [`Checkpoint`] holds the steps drawn so far, the weights of every color, the nail and link
+//! each color stands on, the counters of the line selector and the error history the plateau
+//! criterion measures. Lines used are the ones of the steps. It is stamped with a hash of the
+//! inputs, so it is only restored on the same image, nails, palette and line selector.
+
+use std::hash::Hasher;
+
+use serde::{Deserialize, Serialize};
+
+use crate::stop::ErrorWindow;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub(crate) inputs: u64,
+    pub(crate) steps: Vec<CheckpointStep>,
+    pub(crate) colors: Vec<ColorState>,
+    pub(crate) selector: SelectorCounters,
+    pub(crate) errors: ErrorWindow<f64>,
+}
+
+impl Checkpoint {
+    /// Hash of the image, the nails, the palette and the line selector the state belongs to.
+    pub fn inputs(&self) -> u64 {
+        self.inputs
+    }
+
+    /// Threads drawn when the checkpoint was taken.
+    pub fn threads(&self) -> usize {
+        self.steps.len()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct CheckpointStep {
+    pub(crate) line_idx: usize,
+    pub(crate) color_idx: usize,
+    pub(crate) from_nail: usize,
+    pub(crate) from_link: usize,
+    pub(crate) to_nail: usize,
+    pub(crate) to_link: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct ColorState {
+    pub(crate) weights: Vec<f64>,
+    pub(crate) nail: usize,
+    pub(crate) link: usize,
+}
+
+/// Lines drawn, thread used and whether it was retired for every item of the line selector, with
+/// the group in use.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct SelectorCounters {
+    pub(crate) curr: usize,
+    pub(crate) items: Vec<Vec<(usize, f32, bool)>>,
+}
+
+// FNV-1a over words rather than bytes, stable between builds unlike the std hashers.
+pub(crate) struct InputHasher(u64);
+
+impl InputHasher {
+    pub(crate) fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for InputHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for chunk in bytes.chunks(8) {
+            let mut word = [0; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            self.write_u64(u64::from_le_bytes(word));
+        }
+    }
+
+    fn write_u64(&mut self, word: u64) {
+        self.0 = (self.0 ^ word).wrapping_mul(0x0000_0100_0000_01b3);
+    }
+
+    fn write_usize(&mut self, word: usize) {
+        self.write_u64(word as u64);
+    }
+}
@@ -1,7 +1,7 @@
 use std::ops::Deref;
 use num_traits::AsPrimitive;
 use palette::{color_difference::EuclideanDistance, FromColor, Srgb};
-use crate::{geometry::Segment, grid::Grid, AsLab, Float, Image, Lab};
+use crate::{AsLab, Float, Image, Lab};
 
 
 #[derive(Clone)]
@@ -61,7 +61,7 @@ impl<S, L> Deref for LabColorMapSettings<S, L> {
 
 pub struct ColorMap<S, L> {
     settings: ColorConfig<L>,
-    weights: Vec<S>,
+    pub(crate) weights: Vec<S>,
     pub(crate) curr_nail: usize,
     pub(crate) curr_link: L,
 }
@@ -95,13 +95,13 @@ impl<S: Float, L: Copy> ColorMap<S, L> {
         }
     }
 
-    pub (crate) fn calculate_weight(&self, segment: &Segment<S>, grid: &Grid) -> S {
+    pub (crate) fn calculate_weight(&self, pixels: impl Iterator<Item = (usize, S)>) -> S {
         let mut weight = S::ZERO;
         let mut count = S::ZERO;
-        for idx in grid.get_pixel_indexes_in_segment(segment) {
+        for (idx, coverage) in pixels {
             let delta = unsafe { *self.weights.get_unchecked(idx) };
-            weight += delta;
-            count += S::ONE;
+            weight += delta * coverage;
+            count += coverage;
         }
         if count > S::ZERO {
             weight / count as S
@@ -69,6 +69,10 @@ impl<'a, P, W> Ditherer<'a, P, W> {
         let x = image_dithered.width;
 
         for y in 0..y {
+            // The rows left are not dithered, the caller discards the result once cancelled.
+            if verboser.is_cancelled() {
+                return Ok(());
+            }
             verboser.verbose(Message::Dithering(y, image_dithered.height));
             for x in 0..x {
                 let old_color = unsafe { image_dithered.get_unchecked_mut(Point { x, y }) };
@@ -99,7 +103,7 @@ impl<'a, P, W> Ditherer<'a, P, W> {
         if let Some(weighted_color) = iter.next() {
             let mut min = color.distance_squared(weighted_color.color());
             let mut best = weighted_color;
-            while let Some(weighted_color) = iter.next() {
+            for weighted_color in iter {
                 let distance = color.distance_squared(weighted_color.color());
                 if distance < min {
                     min = distance;
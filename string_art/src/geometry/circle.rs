@@ -80,6 +80,41 @@ impl<T: Float> Circle<T> {
         })
     }
 
+    /// Length of the arc a thread wraps around the circle, arriving along `arrival` and leaving
+    /// along `departure`, both tangent to it. The thread keeps the sense it arrives with.
+    pub fn wrap_length(&self, arrival: &Segment<T>, departure: &Segment<T>) -> T {
+        // Ends of a segment as the one touching the circle and the other one.
+        let ends = |segment: &Segment<T>| {
+            if segment.start.sq_distance(&self.center) <= segment.end.sq_distance(&self.center) {
+                (segment.start, segment.end)
+            } else {
+                (segment.end, segment.start)
+            }
+        };
+        let cross = |a: Point<T>, b: Point<T>| a.x * b.y - a.y * b.x;
+        let (arrival_point, arrival_from) = ends(arrival);
+        let (departure_point, _) = ends(departure);
+        let arrival_radius = arrival_point - self.center;
+        let departure_radius = departure_point - self.center;
+        let angle = cross(arrival_radius, departure_radius).atan2(
+            arrival_radius.x * departure_radius.x + arrival_radius.y * departure_radius.y,
+        );
+        // Positive angles follow the sense of the thread around the center.
+        let angle = if cross(arrival_radius, arrival_point - arrival_from) < T::ZERO {
+            -angle
+        } else {
+            angle
+        };
+        // Threads leaving straight on may land slightly behind their arrival.
+        let tolerance = T::from(1e-4).unwrap_or(T::ZERO);
+        let angle = if angle < -tolerance {
+            angle + T::PI + T::PI
+        } else {
+            angle.max(T::ZERO)
+        };
+        self.radius * angle
+    }
+
     pub fn draw<I: GenericImage>(self, image: &mut I, pixel: I::Pixel) {
         let mut y = -self.radius;
         while y <= self.radius {
@@ -89,7 +124,7 @@ impl<T: Float> Circle<T> {
             let y0 = self.center.y + y;
             image.put_pixel(x0.to_u32().unwrap(), y0.to_u32().unwrap(), pixel);
             image.put_pixel(x1.to_u32().unwrap(), y0.to_u32().unwrap(), pixel);
-            y = y + T::ONE;
+            y += T::ONE;
         }
     }
 }
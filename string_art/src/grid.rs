@@ -30,6 +30,56 @@ impl<T: NumCast + Unsigned + PartialOrd + Copy> Grid<T> {
             .filter_map(|point| point.cast::<T>())
     }
 
+    /// Pixels covered by a thread of `width` pixels along `seg`, paired with the covered fraction
+    /// of each pixel. Coverage is box filtered across the thread, one column (or row, for steep
+    /// threads) at a time.
+    pub fn get_pixel_coverage_in_segment<F: Float>(
+        &self,
+        seg: &Segment<F>,
+        width: F,
+    ) -> impl Iterator<Item = (T, F)> + '_ {
+        let (dx, dy) = (seg.end.x - seg.start.x, seg.end.y - seg.start.y);
+        let steep = dy.abs() > dx.abs();
+        // Major axis u, minor axis v.
+        let (mut start, mut end) = if steep {
+            (
+                Point { x: seg.start.y, y: seg.start.x },
+                Point { x: seg.end.y, y: seg.end.x },
+            )
+        } else {
+            (seg.start, seg.end)
+        };
+        if start.x > end.x {
+            std::mem::swap(&mut start, &mut end);
+        }
+        let du = end.x - start.x;
+        let slope = if du > F::ZERO { (end.y - start.y) / du } else { F::ZERO };
+        let half = width * F::HALF * num_traits::Float::sqrt(F::ONE + slope * slope);
+        let first = start.x.floor().to_isize().unwrap_or(0);
+        let last = end.x.floor().to_isize().unwrap_or(-1);
+        (first..=last).flat_map(move |u| {
+            let center = Float::min(
+                (num_traits::cast::<_, F>(u).unwrap_or(F::ZERO) + F::HALF).max(start.x),
+                end.x,
+            );
+            let v = start.y + slope * (center - start.x);
+            let (low, high) = (v - half, v + half);
+            let rows = low.floor().to_isize().unwrap_or(0)..=high.floor().to_isize().unwrap_or(-1);
+            rows.filter_map(move |row| {
+                let top: F = num_traits::cast(row).unwrap_or(F::ZERO);
+                let coverage = Float::min(top + F::ONE, high) - low.max(top);
+                if coverage <= F::ZERO {
+                    return None;
+                }
+                let (x, y) = if steep { (row, u) } else { (u, row) };
+                Point { x, y }
+                    .cast::<T>()
+                    .and_then(|point| self.index_of(point))
+                    .map(|idx| (idx, coverage))
+            })
+        })
+    }
+
     pub fn index_of(&self, point: Point<T>) -> Option<T> {
         if point.x < self.width && point.y < self.height {
             Some(point.y * self.width + point.x)
@@ -38,7 +88,69 @@ impl<T: NumCast + Unsigned + PartialOrd + Copy> Grid<T> {
         }
     }
 
+    /// # Safety
+    /// `point` must lie inside the grid, otherwise the returned index is meaningless.
     pub unsafe fn index_of_unchecked(&self, point: Point<T>) -> T {
         point.y * self.width + point.x
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coverage(seg: Segment<f32>, width: f32) -> Vec<(usize, f32)> {
+        Grid::new(20, 20).get_pixel_coverage_in_segment(&seg, width).collect()
+    }
+
+    fn segment(start: (f32, f32), end: (f32, f32)) -> Segment<f32> {
+        Segment {
+            start: Point { x: start.0, y: start.1 },
+            end: Point { x: end.0, y: end.1 },
+        }
+    }
+
+    #[test]
+    fn centered_threads_cover_whole_pixels() {
+        let horizontal = coverage(segment((0.5, 2.5), (9.5, 2.5)), 1.0);
+        assert_eq!(horizontal.len(), 10);
+        for (x, &(idx, coverage)) in horizontal.iter().enumerate() {
+            assert_eq!(idx, 2 * 20 + x);
+            assert!((coverage - 1.0).abs() < 1e-5);
+        }
+        // Steep threads walk rows instead of columns.
+        let vertical = coverage(segment((2.5, 9.5), (2.5, 0.5)), 1.0);
+        assert_eq!(vertical.len(), 10);
+        for (y, &(idx, coverage)) in vertical.iter().enumerate() {
+            assert_eq!(idx, y * 20 + 2);
+            assert!((coverage - 1.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn coverage_follows_the_width() {
+        for &(_, coverage) in &coverage(segment((0.5, 2.5), (9.5, 2.5)), 0.5) {
+            assert!((coverage - 0.5).abs() < 1e-5);
+        }
+        // On the border between two rows, each one gets half of the thread.
+        let border = coverage(segment((0.5, 3.0), (4.5, 3.0)), 1.0);
+        assert_eq!(border.len(), 10);
+        for (idx, coverage) in border {
+            assert!(idx / 20 == 2 || idx / 20 == 3);
+            assert!((coverage - 0.5).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn diagonal_threads_spread_over_each_column() {
+        let diagonal = coverage(segment((2.5, 2.5), (12.5, 12.5)), 1.0);
+        for column in 2..=12 {
+            let total: f32 = diagonal
+                .iter()
+                .filter(|(idx, _)| idx % 20 == column)
+                .map(|(_, coverage)| coverage)
+                .sum();
+            assert!((total - f32::sqrt(2.0)).abs() < 1e-4);
+        }
+    }
+}
@@ -147,10 +147,14 @@ impl<T> Image<T> {
             .map(|index| unsafe { self.pixels.get_unchecked_mut(index) })
     }
 
+    /// # Safety
+    /// `point` must lie inside the image grid.
     pub unsafe fn get_unchecked(&self, point: Point<usize>) -> &Lab<T> {
         self.pixels.get_unchecked(self.index_of_unchecked(point))
     }
 
+    /// # Safety
+    /// `point` must lie inside the image grid.
     pub unsafe fn get_unchecked_mut(&mut self, point: Point<usize>) -> &mut Lab<T> {
         self.pixels
             .get_unchecked_mut(self.grid.index_of_unchecked(point))
@@ -0,0 +1,405 @@
+//! Structured threading instructions, saved as JSON or CSV and read back.
+//!
+//! JSON files hold an [`Instructions`] object. CSV files start with `#` comment lines, the second
+//! one holding the [`Header`] as JSON, followed by a row of column names and one row per [`Step`].
+
+use std::{fmt::Write as _, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Board;
+
+/// Version of the layout written by [`Instructions::to_json`] and [`Instructions::to_csv`].
+pub const VERSION: u32 = 1;
+
+const CSV_MARKER: &str = "# string_art instructions";
+const CSV_COLUMNS: [&str; 15] = [
+    "step", "color", "name", "r", "g", "b", "from", "from_link", "to", "to_link", "x1", "y1", "x2",
+    "y2", "length",
+];
+
+/// Threads of a result in the order they are wound, as [`crate::Algorithm::build_instructions`]
+/// lists them.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Instructions {
+    pub header: Header,
+    pub steps: Vec<Step>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Header {
+    /// Version of the layout.
+    pub version: u32,
+    /// Size in pixels of the image the nails are laid out on.
+    pub width: usize,
+    pub height: usize,
+    /// Position of every nail in pixels of the image.
+    pub nails: Vec<[f64; 2]>,
+    /// Nails placed on the frame, the interior ones follow them.
+    pub frame_len: usize,
+    /// Links of every nail, steps refer to them by index.
+    pub links: usize,
+    /// Physical size of the board, if known.
+    pub board: Option<Board>,
+    pub palette: Vec<PaletteColor>,
+    /// Settings the result was generated with, in the layout of the application that saved it.
+    #[serde(default)]
+    pub parameters: serde_json::Value,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PaletteColor {
+    pub name: String,
+    pub rgb: [u8; 3],
+    /// Nail and link the computation of this color started from.
+    pub nail: usize,
+    pub link: usize,
+}
+
+/// Thread stretched from a nail to the next one.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Step {
+    /// Index of the color in the palette.
+    pub color: usize,
+    pub name: String,
+    pub rgb: [u8; 3],
+    pub from: usize,
+    pub from_link: usize,
+    pub to: usize,
+    pub to_link: usize,
+    /// Ends of the thread in pixels of the image, from the `from` nail to the `to` one.
+    pub start: [f64; 2],
+    pub end: [f64; 2],
+    /// Thread of this color used so far in pixels of the image, wraps around the nails included.
+    pub length: f64,
+}
+
+impl Instructions {
+    /// Sets the physical size of the board.
+    pub fn with_board(self, board: Board) -> Self {
+        Self {
+            header: Header {
+                board: Some(board),
+                ..self.header
+            },
+            ..self
+        }
+    }
+
+    /// Sets the settings the result was generated with.
+    pub fn with_parameters(self, parameters: serde_json::Value) -> Self {
+        Self {
+            header: Header {
+                parameters,
+                ..self.header
+            },
+            ..self
+        }
+    }
+
+    /// Reads a `.csv` file as [`Instructions::from_csv`] and anything else as
+    /// [`Instructions::from_json`].
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, InstructionsError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(InstructionsError::Io)?;
+        if is_csv(path) {
+            Self::from_csv(&text)
+        } else {
+            Self::from_json(&text)
+        }
+    }
+
+    /// Writes a `.csv` file as [`Instructions::to_csv`] and anything else as
+    /// [`Instructions::to_json`].
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), InstructionsError> {
+        let path = path.as_ref();
+        let text = if is_csv(path) {
+            self.to_csv()?
+        } else {
+            self.to_json()?
+        };
+        std::fs::write(path, text).map_err(InstructionsError::Io)
+    }
+
+    pub fn to_json(&self) -> Result<String, InstructionsError> {
+        serde_json::to_string_pretty(self).map_err(InstructionsError::Json)
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, InstructionsError> {
+        let instructions: Self = serde_json::from_str(text).map_err(InstructionsError::Json)?;
+        instructions.check_version()
+    }
+
+    pub fn to_csv(&self) -> Result<String, InstructionsError> {
+        let mut csv = format!(
+            "{}\n# {}\n{}\n",
+            CSV_MARKER,
+            serde_json::to_string(&self.header).map_err(InstructionsError::Json)?,
+            CSV_COLUMNS.join(",")
+        );
+        for (idx, step) in self.steps.iter().enumerate() {
+            let [r, g, b] = step.rgb;
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                idx,
+                step.color,
+                csv_field(&step.name),
+                r,
+                g,
+                b,
+                step.from,
+                step.from_link,
+                step.to,
+                step.to_link,
+                step.start[0],
+                step.start[1],
+                step.end[0],
+                step.end[1],
+                step.length
+            );
+        }
+        Ok(csv)
+    }
+
+    /// Parses the layout of [`Instructions::to_csv`]. Columns may come in any order.
+    pub fn from_csv(text: &str) -> Result<Self, InstructionsError> {
+        let mut header = None;
+        let mut columns: Option<Vec<String>> = None;
+        let mut steps = Vec::new();
+        let mut record = String::new();
+        let mut number = 0;
+        for (idx, line) in text.split('\n').enumerate() {
+            if record.is_empty() {
+                let trimmed = line.trim();
+                if trimmed.is_empty() {
+                    continue;
+                }
+                if let Some(comment) = trimmed.strip_prefix('#') {
+                    let comment = comment.trim();
+                    if header.is_none() && comment.starts_with('{') {
+                        header = Some(
+                            serde_json::from_str::<Header>(comment)
+                                .map_err(InstructionsError::Json)?,
+                        );
+                    }
+                    continue;
+                }
+                number = idx + 1;
+            } else {
+                record.push('\n');
+            }
+            record.push_str(line);
+            // Quoted fields may hold line breaks, the record goes on until its quotes are closed.
+            let Some(fields) = split_csv(&record) else {
+                continue;
+            };
+            record.clear();
+            let Some(columns) = &columns else {
+                columns = Some(fields);
+                continue;
+            };
+            let field = |name: &str| {
+                columns
+                    .iter()
+                    .position(|column| column == name)
+                    .and_then(|column| fields.get(column))
+                    .ok_or(InstructionsError::Csv(number))
+            };
+            let parse = |name: &str| -> Result<f64, InstructionsError> {
+                field(name)?
+                    .parse()
+                    .map_err(|_| InstructionsError::Csv(number))
+            };
+            let index = |name: &str| -> Result<usize, InstructionsError> {
+                field(name)?
+                    .parse()
+                    .map_err(|_| InstructionsError::Csv(number))
+            };
+            let channel = |name: &str| {
+                index(name).and_then(|value| {
+                    u8::try_from(value).map_err(|_| InstructionsError::Csv(number))
+                })
+            };
+            steps.push(Step {
+                color: index("color")?,
+                name: field("name")?.clone(),
+                rgb: [channel("r")?, channel("g")?, channel("b")?],
+                from: index("from")?,
+                from_link: index("from_link")?,
+                to: index("to")?,
+                to_link: index("to_link")?,
+                start: [parse("x1")?, parse("y1")?],
+                end: [parse("x2")?, parse("y2")?],
+                length: parse("length")?,
+            });
+        }
+        if !record.is_empty() {
+            return Err(InstructionsError::Csv(number));
+        }
+        Self {
+            header: header.ok_or(InstructionsError::MissingHeader)?,
+            steps,
+        }
+        .check_version()
+    }
+
+    fn check_version(self) -> Result<Self, InstructionsError> {
+        if self.header.version > VERSION {
+            Err(InstructionsError::UnsupportedVersion(self.header.version))
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+fn is_csv(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"))
+}
+
+/// Quotes `field` if it holds separators, quotes or line breaks.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) || field.trim() != field {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        String::from(field)
+    }
+}
+
+/// Splits a record on commas, unquoting quoted fields. `None` if a quote is left open.
+fn split_csv(line: &str) -> Option<Vec<String>> {
+    let mut fields = Vec::new();
+    let mut rest = line;
+    loop {
+        let trimmed = rest.trim_start();
+        let (field, next) = match trimmed.strip_prefix('"') {
+            Some(quoted) => {
+                let mut field = String::new();
+                let mut chars = quoted.char_indices();
+                let end = loop {
+                    match chars.next()? {
+                        (idx, '"') if quoted[idx + 1..].starts_with('"') => {
+                            chars.next();
+                            field.push('"');
+                        }
+                        (idx, '"') => break idx + 1,
+                        (_, char) => field.push(char),
+                    }
+                };
+                let after = &quoted[end..];
+                (field, after.find(',').map(|idx| &after[idx + 1..]))
+            }
+            None => match trimmed.find(',') {
+                Some(idx) => (trimmed[..idx].trim().to_string(), Some(&trimmed[idx + 1..])),
+                None => (trimmed.trim().to_string(), None),
+            },
+        };
+        fields.push(field);
+        match next {
+            Some(next) => rest = next,
+            None => return Some(fields),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum InstructionsError {
+    #[error(transparent)]
+    Io(io::Error),
+    #[error(transparent)]
+    Json(serde_json::Error),
+    #[error("Invalid instruction at line {0}.")]
+    Csv(usize),
+    #[error("The instructions have no header.")]
+    MissingHeader,
+    #[error("The instructions were saved by a newer version (format {0}).")]
+    UnsupportedVersion(u32),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Instructions {
+        let names = ["Black", " spaced, \"quoted\"", "two\nlines", "crlf\r\nend"];
+        let palette: Vec<_> = names
+            .iter()
+            .enumerate()
+            .map(|(idx, name)| PaletteColor {
+                name: name.to_string(),
+                rgb: [idx as u8, 40, 200],
+                nail: idx,
+                link: idx % 2,
+            })
+            .collect();
+        let steps = palette
+            .iter()
+            .enumerate()
+            .map(|(idx, color)| Step {
+                color: idx,
+                name: color.name.clone(),
+                rgb: color.rgb,
+                from: idx,
+                from_link: 1,
+                to: idx + 2,
+                to_link: 0,
+                start: [0.1 * idx as f64, 12.5],
+                end: [300.0 / 7.0, 1e-3],
+                length: 100.0 / 3.0 * (idx + 1) as f64,
+            })
+            .collect();
+        Instructions {
+            header: Header {
+                version: VERSION,
+                width: 400,
+                height: 300,
+                nails: vec![
+                    [0.0, 0.0],
+                    [399.5, 0.25],
+                    [200.0, 299.0],
+                    [1.0 / 3.0, 150.0],
+                    [7.0, 7.0],
+                    [8.0, 9.0],
+                ],
+                frame_len: 4,
+                links: 2,
+                board: None,
+                palette,
+                parameters: serde_json::json!({ "threads": 4 }),
+            },
+            steps,
+        }
+        .with_board(Board::new(500.0, 1.5, 0.3))
+    }
+
+    #[test]
+    fn round_trips_csv() {
+        let instructions = sample();
+        let csv = instructions.to_csv().unwrap();
+        assert_eq!(Instructions::from_csv(&csv).unwrap(), instructions);
+        let crlf = csv.replace('\n', "\r\n");
+        assert_eq!(
+            Instructions::from_csv(&crlf).unwrap().steps.len(),
+            instructions.steps.len()
+        );
+    }
+
+    #[test]
+    fn round_trips_json() {
+        let instructions = sample();
+        let json = instructions.to_json().unwrap();
+        assert_eq!(Instructions::from_json(&json).unwrap(), instructions);
+    }
+
+    #[test]
+    fn rejects_unclosed_quotes() {
+        let mut csv = sample().to_csv().unwrap();
+        csv.push_str("0,\"open,0,0,0");
+        let lines = csv.lines().count();
+        assert!(matches!(
+            Instructions::from_csv(&csv),
+            Err(InstructionsError::Csv(line)) if line == lines
+        ));
+    }
+}
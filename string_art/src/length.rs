@@ -0,0 +1,44 @@
+/// Thread used by a result, wraps around the nails included.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ThreadLength {
+    per_color: Vec<f64>,
+    longest_side: f64,
+}
+
+impl ThreadLength {
+    /// `per_color` holds the length of each color of the palette in pixels of an image whose
+    /// longest side is `longest_side`.
+    pub fn new(per_color: Vec<f64>, longest_side: f64) -> Self {
+        Self {
+            per_color,
+            longest_side,
+        }
+    }
+
+    /// Length of each color of the palette in pixels of the image.
+    pub fn per_color(&self) -> &[f64] {
+        &self.per_color
+    }
+
+    /// Length of every color in pixels of the image.
+    pub fn total(&self) -> f64 {
+        self.per_color.iter().sum()
+    }
+
+    /// Millimeters per pixel of the image on a board whose diameter in millimeters spans its
+    /// longest side, like [`crate::ThreadWidth::Millimeters`].
+    pub fn millimeters_per_pixel(&self, board_diameter: f64) -> f64 {
+        board_diameter / self.longest_side.max(1.0)
+    }
+
+    /// Length of each color of the palette in millimeters on a board of `board_diameter`.
+    pub fn per_color_millimeters(&self, board_diameter: f64) -> Vec<f64> {
+        let scale = self.millimeters_per_pixel(board_diameter);
+        self.per_color.iter().map(|length| length * scale).collect()
+    }
+
+    /// Length of every color in millimeters on a board of `board_diameter`.
+    pub fn total_millimeters(&self, board_diameter: f64) -> f64 {
+        self.total() * self.millimeters_per_pixel(board_diameter)
+    }
+}
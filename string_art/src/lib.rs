@@ -26,10 +26,19 @@ pub mod color;
 mod color_map;
 pub mod line_selector;
 mod nail_distancer;
+mod board;
+mod length;
+mod metrics;
+mod raster;
+mod stop;
+mod template;
 
 pub mod auto_line_config;
+pub mod checkpoint;
 pub mod darkness;
+pub mod instructions;
 pub mod line_config;
+pub mod machine;
 
 pub use algorithm::*;
 pub use auto_line_config::AutoLineConfig;
@@ -37,6 +46,21 @@ pub use color::*;
 pub use color_map::ColorConfig;
 pub use image::*;
 pub use line_config::LineConfig;
+pub use nail_distancer::{GeometricRule, NailDistance};
+pub use board::Board;
+pub use checkpoint::Checkpoint;
+pub use instructions::{Instructions, InstructionsError};
+pub use length::ThreadLength;
+pub use machine::{Machine, MachineError, Motion};
+pub use metrics::{Quality, HEATMAP_MAX_DELTA_E};
+pub use stop::{Plateau, StopCriteria, StopReason};
+pub use template::{Paper, Template};
+pub use raster::{
+    NailStyle, RasterError, RasterSize, RasterStyle, ThreadWidth, MAX_RASTER_SAMPLES, MM_PER_INCH,
+};
 //pub use line_selector::{Builder as LineSelectorBuilder, Error as LineSelectorError, LineSelector};
-pub use nail_table::NailTable;
+pub use nail_table::{
+    Corners, ImportError, Interior, InteriorError, LineCoverage, NailPosition, NailTable,
+    Normalization, ParseError, PixelArena, PolygonError,
+};
 pub mod verboser;
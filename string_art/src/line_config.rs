@@ -43,6 +43,7 @@ impl<C> DerefMut for LineGroupConfig<C>{
 #[derive(Copy, Clone, Serialize, Deserialize)]
 pub struct LineConfig<G = Vec<LineGroupConfig>, C = Vec<LineItemConfig>> {
     groups: G,
+    #[serde(skip)]
     _panthom: std::marker::PhantomData<C>,
 }
 
@@ -116,3 +117,85 @@ impl From<LineItemConfig> for LineItemSelector {
         LineItemSelector::new(value.color_idx, 0, value.cap)
     }
 }
+
+/// Length of thread allowed for a color, in pixels of the image.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct LengthItemConfig {
+    pub color_idx: usize,
+    pub length: f32,
+}
+
+impl LengthItemConfig {
+    pub fn new(color_idx: usize, length: f32) -> Self {
+        LengthItemConfig { color_idx, length }
+    }
+}
+
+/// Caps each color by the length of its thread, wraps around the nails included, rather than by
+/// its line count. Colors take turns by the fraction of their length spent.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LengthConfig<C = Vec<LengthItemConfig>>(C);
+
+impl<C: AsRef<[LengthItemConfig]>> LengthConfig<C> {
+    /// Fails unless every length is positive and finite.
+    pub fn new(items: C) -> Result<Self, LengthError> {
+        if items
+            .as_ref()
+            .iter()
+            .all(|item| item.length > 0.0 && item.length.is_finite())
+        {
+            Ok(LengthConfig(items))
+        } else {
+            Err(LengthError)
+        }
+    }
+}
+
+impl<C> Deref for LengthConfig<C> {
+    type Target = C;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+unsafe impl<S, C: AsRef<[LengthItemConfig]>> line_selector::Builder<S> for LengthConfig<C> {
+    fn build_line_selector(
+        &self,
+        _: &Image<S>,
+        palette: &[impl AsLab<S>],
+        _: &mut impl Verboser,
+    ) -> Result<LineSelector, line_selector::Error> {
+        let group = self
+            .0
+            .as_ref()
+            .iter()
+            .map(|item| {
+                if item.color_idx >= palette.len() {
+                    Err(line_selector::Error)
+                } else {
+                    Ok(LineItemSelector::with_length_cap(item.color_idx, item.length))
+                }
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(LineSelector::from_iter([group]))
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("The thread length of every color must be positive.")]
+pub struct LengthError;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lengths_must_be_positive() {
+        assert!(LengthConfig::new(vec![LengthItemConfig::new(0, 100.0)]).is_ok());
+        for length in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+            let items = vec![LengthItemConfig::new(0, 100.0), LengthItemConfig::new(1, length)];
+            assert!(LengthConfig::new(items).is_err());
+        }
+    }
+}
@@ -1,9 +1,11 @@
-use std::ops::Deref;
+use std::{hash::Hasher, ops::Deref};
 
 use crate::{
-    verboser::Verboser, AsLab, Image
+    checkpoint::SelectorCounters, verboser::Verboser, AsLab, Image
 };
 
+/// # Safety
+/// Every color index stored in the returned selector must be a valid index of `palette`.
 pub unsafe trait Builder<S> {
     fn build_line_selector(
         &self,
@@ -18,6 +20,10 @@ pub struct LineItemSelector {
     color_idx: usize,
     count: usize,
     cap: usize,
+    length: f32,
+    length_cap: Option<f32>,
+    // Its color stands on a nail without any line left to draw.
+    retired: bool,
 }
 
 impl LineItemSelector {
@@ -26,6 +32,31 @@ impl LineItemSelector {
             color_idx,
             count,
             cap,
+            length: 0.0,
+            length_cap: None,
+            retired: false,
+        }
+    }
+
+    /// Item of `color_idx` drawn until its thread reaches `length_cap`, in pixels of the image.
+    /// The line reaching the cap is still drawn. Lines picked again count their length towards
+    /// the cap without adding thread, like they count towards a line cap, so the item always ends.
+    pub fn with_length_cap(color_idx: usize, length_cap: f32) -> Self {
+        LineItemSelector {
+            length_cap: Some(length_cap),
+            ..LineItemSelector::new(color_idx, 0, usize::MAX)
+        }
+    }
+
+    /// Item of `color_idx` that already drew its `count` lines, using `length` pixels of thread.
+    pub(crate) fn spent(color_idx: usize, count: usize, length: f32) -> Self {
+        LineItemSelector {
+            color_idx,
+            count,
+            cap: count,
+            length,
+            length_cap: None,
+            retired: false,
         }
     }
 
@@ -36,13 +67,37 @@ impl LineItemSelector {
     pub fn cap(&self) -> usize {
         self.cap
     }
+
+    pub fn length_cap(&self) -> Option<f32> {
+        self.length_cap
+    }
+
+    // Fraction of the cap spent, by count or by length.
+    fn ratio(&self) -> f32 {
+        if self.retired {
+            return 1.0;
+        }
+        let ratio = self.count as f32 / self.cap as f32;
+        match self.length_cap {
+            Some(cap) if cap > 0.0 && cap.is_finite() => ratio.max(self.length / cap),
+            // A cap that is not positive and finite is spent from the start.
+            Some(_) => 1.0,
+            None => ratio,
+        }
+    }
 }
 
-pub struct LineGroupSelector(Vec<LineItemSelector>);
+pub struct LineGroupSelector {
+    items: Vec<LineItemSelector>,
+    last: Option<usize>,
+}
 
 impl FromIterator<LineItemSelector> for LineGroupSelector {
     fn from_iter<T: IntoIterator<Item = LineItemSelector>>(iter: T) -> Self {
-        LineGroupSelector(iter.into_iter().collect())
+        LineGroupSelector {
+            items: iter.into_iter().collect(),
+            last: None,
+        }
     }
 }
 
@@ -50,26 +105,28 @@ impl LineGroupSelector {
     fn select_next(&mut self) -> Option<usize> {
         let mut choice = None;
         let mut best_ratio = 1.0;
-        for item in self.0.iter_mut() {
-            let ratio = item.count as f32 / item.cap as f32;
+        for (idx, item) in self.items.iter().enumerate() {
+            let ratio = item.ratio();
             if ratio < best_ratio {
                 best_ratio = ratio;
-                choice = Some(item);
+                choice = Some(idx);
             }
         }
 
-        choice.map(|item| {
+        self.last = choice;
+        choice.map(|idx| {
+            let item = unsafe { self.items.get_unchecked_mut(idx) };
             item.count += 1;
             item.color_idx
         })
-    }    
+    }
 }
 
 impl Deref for LineGroupSelector{
     type Target = [LineItemSelector];
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.items
     }
 }
 
@@ -93,7 +150,78 @@ impl LineSelector {
         }
 
         None
-    }    
+    }
+
+    /// Takes back the line counted for the last item selected and never selects it again.
+    pub(crate) fn retire_last(&mut self) {
+        if let Some(group) = self.lines.get_mut(self.curr) {
+            if let Some(item) = group.last.and_then(|idx| group.items.get_mut(idx)) {
+                item.count = item.count.saturating_sub(1);
+                item.retired = true;
+            }
+        }
+    }
+
+    /// Lines drawn, thread used and retirement of every item.
+    pub(crate) fn counters(&self) -> SelectorCounters {
+        SelectorCounters {
+            curr: self.curr,
+            items: self
+                .lines
+                .iter()
+                .map(|group| {
+                    group
+                        .items
+                        .iter()
+                        .map(|item| (item.count, item.length, item.retired))
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+
+    /// Restores the counters of a selector with the same items, `false` if they differ.
+    pub(crate) fn set_counters(&mut self, counters: &SelectorCounters) -> bool {
+        let matches = counters.curr <= self.lines.len()
+            && counters.items.len() == self.lines.len()
+            && counters
+                .items
+                .iter()
+                .zip(&self.lines)
+                .all(|(items, group)| items.len() == group.items.len());
+        if matches {
+            self.curr = counters.curr;
+            for (items, group) in counters.items.iter().zip(&mut self.lines) {
+                for (&(count, length, retired), item) in items.iter().zip(&mut group.items) {
+                    item.count = count;
+                    item.length = length;
+                    item.retired = retired;
+                }
+            }
+        }
+        matches
+    }
+
+    /// Hashes the color and the caps of every item.
+    pub(crate) fn hash_layout(&self, hasher: &mut impl Hasher) {
+        for group in &self.lines {
+            hasher.write_usize(group.items.len());
+            for item in &group.items {
+                hasher.write_usize(item.color_idx);
+                hasher.write_usize(item.cap);
+                hasher.write_u32(item.length_cap.map_or(u32::MAX, f32::to_bits));
+            }
+        }
+    }
+
+    /// Adds the length of the line picked for the last item selected, drawn or picked again.
+    pub(crate) fn add_length(&mut self, length: f32) {
+        if let Some(group) = self.lines.get_mut(self.curr) {
+            if let Some(item) = group.last.and_then(|idx| group.items.get_mut(idx)) {
+                item.length += length;
+            }
+        }
+    }
 }
 
 impl Deref for LineSelector {
@@ -117,4 +245,47 @@ impl FromIterator<LineGroupSelector> for LineSelector {
 
 #[derive(Debug, thiserror::Error)]
 #[error("Invalid group index")]
-pub struct Error;
\ No newline at end of file
+pub struct Error;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn length_caps_end_on_lines_picked_again() {
+        let mut selector = LineSelector::from_iter([LineGroupSelector::from_iter([
+            LineItemSelector::with_length_cap(0, 10.0),
+            LineItemSelector::with_length_cap(1, 0.0),
+        ])]);
+        let mut picks = 0;
+        while let Some(color_idx) = selector.select_next() {
+            assert_eq!(color_idx, 0);
+            // Every pick finds a used line, which still counts its length.
+            selector.add_length(1.0);
+            picks += 1;
+            assert!(picks <= 10);
+        }
+        assert_eq!(picks, 10);
+    }
+
+    #[test]
+    fn counters_keep_retired_items() {
+        let selector = || {
+            LineSelector::from_iter([LineGroupSelector::from_iter([
+                LineItemSelector::new(0, 0, 5),
+                LineItemSelector::new(1, 0, 5),
+            ])])
+        };
+        let mut retiring = selector();
+        let retired = retiring.select_next().unwrap();
+        retiring.retire_last();
+        let mut restored = selector();
+        assert!(restored.set_counters(&retiring.counters()));
+        let mut picks = 0;
+        while let Some(color_idx) = restored.select_next() {
+            assert_ne!(color_idx, retired);
+            picks += 1;
+        }
+        assert_eq!(picks, 5);
+    }
+}
@@ -0,0 +1,254 @@
+use std::fmt::{self, Write as _};
+
+use crate::geometry::Point;
+
+/// Winding machine with a rotating ring carrying the nails and an arm feeding the thread, moving
+/// across the ring. The ring angle of a nail is the angle it has to turn to bring that nail to the
+/// arm, so turning the ring towards greater angles moves the arm along the nails the same way.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Machine {
+    /// Ring angle in degrees of every nail, the angle of the nails around the center of the image
+    /// when `None`. Angles must grow clockwise on the image, or counterclockwise with
+    /// [`Machine::counterclockwise`].
+    pub nail_angles: Option<Vec<f64>>,
+    /// Degrees added to the ring angle of every nail.
+    pub angle_offset: f64,
+    /// Ring angles grow counterclockwise on the image.
+    pub counterclockwise: bool,
+    /// Degrees the ring turns past each side of a nail while wrapping it.
+    pub wrap_angle: f64,
+    /// Position of the arm in millimeters inside the ring of nails, where it crosses the board.
+    pub arm_inside: f64,
+    /// Position of the arm in millimeters outside the ring of nails, where it wraps them.
+    pub arm_outside: f64,
+    /// Feed rate of the ring between nails, in degrees per minute.
+    pub travel_feed: f64,
+    /// Feed rate of the ring while wrapping a nail, in degrees per minute.
+    pub wrap_feed: f64,
+    /// Feed rate of the arm, in millimeters per minute.
+    pub arm_feed: f64,
+}
+
+impl Default for Machine {
+    fn default() -> Self {
+        Self {
+            nail_angles: None,
+            angle_offset: 0.0,
+            counterclockwise: false,
+            wrap_angle: 2.0,
+            arm_inside: 0.0,
+            arm_outside: 10.0,
+            travel_feed: 3600.0,
+            wrap_feed: 1200.0,
+            arm_feed: 600.0,
+        }
+    }
+}
+
+impl Machine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_nail_angles(self, nail_angles: Vec<f64>) -> Self {
+        Self {
+            nail_angles: Some(nail_angles),
+            ..self
+        }
+    }
+
+    pub fn with_ring(self, angle_offset: f64, counterclockwise: bool) -> Self {
+        Self {
+            angle_offset,
+            counterclockwise,
+            ..self
+        }
+    }
+
+    pub fn with_wrap_angle(self, wrap_angle: f64) -> Self {
+        Self { wrap_angle, ..self }
+    }
+
+    pub fn with_arm(self, inside: f64, outside: f64) -> Self {
+        Self {
+            arm_inside: inside,
+            arm_outside: outside,
+            ..self
+        }
+    }
+
+    pub fn with_feeds(self, travel: f64, wrap: f64, arm: f64) -> Self {
+        Self {
+            travel_feed: travel,
+            wrap_feed: wrap,
+            arm_feed: arm,
+            ..self
+        }
+    }
+
+    /// Ring angle of every nail at `positions`, laid out around `center`.
+    pub(crate) fn ring_angles(
+        &self,
+        positions: &[Point<f64>],
+        center: Point<f64>,
+    ) -> Result<Vec<f64>, MachineError> {
+        let sign = if self.counterclockwise { -1.0 } else { 1.0 };
+        match &self.nail_angles {
+            Some(angles) if angles.len() != positions.len() => Err(MachineError::NailAngles {
+                expected: positions.len(),
+                found: angles.len(),
+            }),
+            Some(angles) => Ok(angles
+                .iter()
+                .map(|angle| angle + self.angle_offset)
+                .collect()),
+            // The y axis of the image points down, so these angles grow clockwise.
+            None => Ok(positions
+                .iter()
+                .map(|position| {
+                    let radius = *position - center;
+                    sign * radius.y.atan2(radius.x).to_degrees() + self.angle_offset
+                })
+                .collect()),
+        }
+    }
+
+    /// Direction the ring turns to wrap a nail clockwise on the image.
+    pub(crate) fn clockwise_sign(&self) -> f64 {
+        if self.counterclockwise {
+            -1.0
+        } else {
+            1.0
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum MachineError {
+    #[error("Expected a ring angle for each of the {expected} nails, found {found}.")]
+    NailAngles { expected: usize, found: usize },
+}
+
+/// Single move of a [`Machine`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum Motion {
+    /// Turns the ring to `angle` degrees at `feed` degrees per minute. Angles are not wrapped
+    /// around, so the ring takes the shortest way to every nail.
+    Ring { angle: f64, feed: f64 },
+    /// Moves the arm to `position` millimeters at `feed` millimeters per minute.
+    Arm { position: f64, feed: f64 },
+    /// Waits for the operator, to tie or change the thread.
+    Pause(String),
+    /// Describes the moves that follow.
+    Comment(String),
+}
+
+impl fmt::Display for Motion {
+    /// Line of the motion command stream: `RING <angle> <feed>`, `ARM <position> <feed>`,
+    /// `PAUSE <message>` or `# <comment>`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Motion::Ring { angle, feed } => write!(f, "RING {:.3} {:.0}", angle, feed),
+            Motion::Arm { position, feed } => write!(f, "ARM {:.3} {:.0}", position, feed),
+            Motion::Pause(message) => write!(f, "PAUSE {}", message),
+            Motion::Comment(comment) => write!(f, "# {}", comment),
+        }
+    }
+}
+
+/// `motions` as G-code, with the ring on the X axis in degrees and the arm on the Y axis. Pauses
+/// are `M0` stops preceded by their message.
+pub fn gcode(motions: &[Motion]) -> String {
+    let mut gcode = String::from("G21 ; millimeters\nG90 ; absolute positions\n");
+    for motion in motions {
+        let _ = match motion {
+            Motion::Ring { angle, feed } => writeln!(gcode, "G1 X{:.3} F{:.0}", angle, feed),
+            Motion::Arm { position, feed } => writeln!(gcode, "G1 Y{:.3} F{:.0}", position, feed),
+            Motion::Pause(message) => writeln!(gcode, "M117 {}\nM0 ; {}", message, message),
+            Motion::Comment(comment) => writeln!(gcode, "; {}", comment),
+        };
+    }
+    gcode.push_str("M2\n");
+    gcode
+}
+
+/// `motions` as a motion command stream, one [`Motion`] per line.
+pub fn commands(motions: &[Motion]) -> String {
+    motions.iter().fold(String::new(), |mut commands, motion| {
+        let _ = writeln!(commands, "{}", motion);
+        commands
+    })
+}
+
+// Builds the motions of a winding, nail after nail.
+pub(crate) struct Planner<'a> {
+    machine: &'a Machine,
+    angles: Vec<f64>,
+    moves: Vec<Motion>,
+    // Unwrapped angle of the ring.
+    ring: f64,
+}
+
+impl<'a> Planner<'a> {
+    pub(crate) fn new(machine: &'a Machine, angles: Vec<f64>) -> Self {
+        Self {
+            machine,
+            angles,
+            moves: vec![Motion::Arm {
+                position: machine.arm_inside,
+                feed: machine.arm_feed,
+            }],
+            ring: 0.0,
+        }
+    }
+
+    // Angle of `nail` the least turn away from the ring.
+    fn nearest(&self, nail: usize) -> f64 {
+        let angle = self.angles[nail];
+        angle + ((self.ring - angle) / 360.0).round() * 360.0
+    }
+
+    fn turn(&mut self, angle: f64, feed: f64) {
+        self.ring = angle;
+        self.moves.push(Motion::Ring { angle, feed });
+    }
+
+    fn arm(&mut self, position: f64) {
+        self.moves.push(Motion::Arm {
+            position,
+            feed: self.machine.arm_feed,
+        });
+    }
+
+    /// Brings `nail` to the arm and waits for the operator.
+    pub(crate) fn pause_at(&mut self, nail: usize, message: String) {
+        let angle = self.nearest(nail);
+        self.turn(angle, self.machine.travel_feed);
+        self.moves.push(Motion::Pause(message));
+    }
+
+    /// Crosses the board to `nail` and wraps it clockwise on the image if `clockwise`.
+    pub(crate) fn wrap(&mut self, nail: usize, clockwise: bool) {
+        let angle = self.nearest(nail);
+        let sense = if clockwise { 1.0 } else { -1.0 } * self.machine.clockwise_sign();
+        let wrap = self.machine.wrap_angle * sense;
+        self.moves.push(Motion::Comment(format!(
+            "nail {} {}",
+            nail,
+            if clockwise {
+                "clockwise"
+            } else {
+                "counterclockwise"
+            }
+        )));
+        self.turn(angle - wrap, self.machine.travel_feed);
+        self.arm(self.machine.arm_outside);
+        self.turn(angle + wrap, self.machine.wrap_feed);
+        self.arm(self.machine.arm_inside);
+    }
+
+    pub(crate) fn finish(mut self, message: String) -> Vec<Motion> {
+        self.moves.push(Motion::Pause(message));
+        self.moves
+    }
+}
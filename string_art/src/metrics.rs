@@ -0,0 +1,215 @@
+use image::RgbImage;
+use palette::{
+    color_difference::{Ciede2000, EuclideanDistance},
+    white_point::D65,
+    FromColor, Srgb,
+};
+
+use crate::{image::Image, Float, Grid};
+
+type Lab64 = palette::Lab<D65, f64>;
+
+/// Color difference mapped to the hottest color of the heatmap.
+pub const HEATMAP_MAX_DELTA_E: f64 = 50.0;
+
+/// Range of the lightness channel of Lab colors.
+const LIGHTNESS_RANGE: f64 = 100.0;
+
+// Gaussian window of the SSIM, as proposed by its authors.
+const SSIM_RADIUS: usize = 5;
+const SSIM_SIGMA: f64 = 1.5;
+
+// Stops of the heatmap, from no difference to `HEATMAP_MAX_DELTA_E` and beyond.
+const HEATMAP_STOPS: [[f64; 3]; 5] = [
+    [0.0, 0.0, 4.0],
+    [87.0, 16.0, 110.0],
+    [188.0, 55.0, 84.0],
+    [249.0, 142.0, 9.0],
+    [252.0, 255.0, 164.0],
+];
+
+/// Error of a rendered result against the image it was computed for, measured in Lab space.
+#[derive(Clone, Debug)]
+pub struct Quality {
+    /// Mean CIE76 color difference, the euclidean distance between Lab colors.
+    pub mean_delta_e: f64,
+    /// Mean CIEDE2000 color difference.
+    pub mean_ciede2000: f64,
+    /// Structural similarity of the lightness, 1 for identical images.
+    pub ssim: f64,
+    /// Peak signal to noise ratio of the lightness in decibels, infinite for identical images.
+    pub psnr: f64,
+    /// CIEDE2000 difference of every pixel, from black through purple, red and orange up to pale
+    /// yellow at [`HEATMAP_MAX_DELTA_E`], like the inferno color map.
+    pub heatmap: RgbImage,
+}
+
+impl Quality {
+    /// Compares `render` with `source` pixel by pixel, `None` if their sizes differ.
+    pub fn measure<S: Float>(source: &Image<S>, render: &RgbImage) -> Option<Self> {
+        let grid = *source.grid();
+        if render.width() as usize != grid.width || render.height() as usize != grid.height {
+            return None;
+        }
+        let source: Vec<Lab64> = source
+            .pixels()
+            .iter()
+            .map(|lab| {
+                Lab64::new(
+                    lab.l.to_f64().unwrap_or_default(),
+                    lab.a.to_f64().unwrap_or_default(),
+                    lab.b.to_f64().unwrap_or_default(),
+                )
+            })
+            .collect();
+        let render: Vec<Lab64> = render
+            .pixels()
+            .map(|pixel| Lab64::from_color(Srgb::from(pixel.0).into_format::<f64>()))
+            .collect();
+
+        let count = source.len().max(1) as f64;
+        let mut delta_e = 0.0;
+        let mut ciede2000 = 0.0;
+        let mut squared_error = 0.0;
+        let mut heatmap = Vec::with_capacity(source.len() * 3);
+        for (&expected, &actual) in source.iter().zip(&render) {
+            delta_e += expected.distance(actual);
+            let difference = expected.difference(actual);
+            ciede2000 += difference;
+            squared_error += (expected.l - actual.l).powi(2);
+            heatmap.extend(heat(difference / HEATMAP_MAX_DELTA_E));
+        }
+        let mse = squared_error / count;
+        Some(Self {
+            mean_delta_e: delta_e / count,
+            mean_ciede2000: ciede2000 / count,
+            ssim: ssim(grid, &source, &render),
+            psnr: 10.0 * (LIGHTNESS_RANGE * LIGHTNESS_RANGE / mse).log10(),
+            heatmap: RgbImage::from_vec(grid.width as u32, grid.height as u32, heatmap)
+                .expect("the buffer matches the grid"),
+        })
+    }
+}
+
+fn heat(value: f64) -> [u8; 3] {
+    let position = value.clamp(0.0, 1.0) * (HEATMAP_STOPS.len() - 1) as f64;
+    let idx = (position as usize).min(HEATMAP_STOPS.len() - 2);
+    let t = position - idx as f64;
+    let (from, to) = (HEATMAP_STOPS[idx], HEATMAP_STOPS[idx + 1]);
+    [0, 1, 2].map(|channel| (from[channel] + (to[channel] - from[channel]) * t).round() as u8)
+}
+
+/// Mean structural similarity of the lightness over a gaussian window, clamped at the borders.
+fn ssim(grid: Grid, source: &[Lab64], render: &[Lab64]) -> f64 {
+    let c1 = (0.01 * LIGHTNESS_RANGE).powi(2);
+    let c2 = (0.03 * LIGHTNESS_RANGE).powi(2);
+    let x: Vec<f64> = source.iter().map(|lab| lab.l).collect();
+    let y: Vec<f64> = render.iter().map(|lab| lab.l).collect();
+    let products = |a: &[f64], b: &[f64]| a.iter().zip(b).map(|(a, b)| a * b).collect::<Vec<_>>();
+    let mu_x = blur(grid, &x);
+    let mu_y = blur(grid, &y);
+    let xx = blur(grid, &products(&x, &x));
+    let yy = blur(grid, &products(&y, &y));
+    let xy = blur(grid, &products(&x, &y));
+    let sum: f64 = (0..x.len())
+        .map(|idx| {
+            let (mx, my) = (mu_x[idx], mu_y[idx]);
+            let var_x = xx[idx] - mx * mx;
+            let var_y = yy[idx] - my * my;
+            let cov = xy[idx] - mx * my;
+            ((2.0 * mx * my + c1) * (2.0 * cov + c2))
+                / ((mx * mx + my * my + c1) * (var_x + var_y + c2))
+        })
+        .sum();
+    sum / x.len().max(1) as f64
+}
+
+/// Separable gaussian blur with the SSIM window.
+fn blur(grid: Grid, values: &[f64]) -> Vec<f64> {
+    let kernel: Vec<f64> = (0..=2 * SSIM_RADIUS)
+        .map(|idx| {
+            let offset = idx as f64 - SSIM_RADIUS as f64;
+            (-offset * offset / (2.0 * SSIM_SIGMA * SSIM_SIGMA)).exp()
+        })
+        .collect();
+    let total: f64 = kernel.iter().sum();
+    let kernel: Vec<f64> = kernel.iter().map(|weight| weight / total).collect();
+    let sample = |len: usize, at: usize, idx: usize| {
+        (at + idx).saturating_sub(SSIM_RADIUS).min(len - 1)
+    };
+
+    let mut horizontal = vec![0.0; values.len()];
+    for y in 0..grid.height {
+        let row = &values[y * grid.width..(y + 1) * grid.width];
+        for x in 0..grid.width {
+            horizontal[y * grid.width + x] = kernel
+                .iter()
+                .enumerate()
+                .map(|(idx, weight)| weight * row[sample(grid.width, x, idx)])
+                .sum();
+        }
+    }
+    let mut blurred = vec![0.0; values.len()];
+    for y in 0..grid.height {
+        for x in 0..grid.width {
+            blurred[y * grid.width + x] = kernel
+                .iter()
+                .enumerate()
+                .map(|(idx, weight)| {
+                    weight * horizontal[sample(grid.height, y, idx) * grid.width + x]
+                })
+                .sum();
+        }
+    }
+    blurred
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gradient() -> RgbImage {
+        RgbImage::from_fn(24, 16, |x, y| image::Rgb([(x * 10) as u8, (y * 15) as u8, 128]))
+    }
+
+    // Loaded like the application does, through a dynamic image.
+    fn source() -> Image<f64> {
+        image::DynamicImage::from(gradient()).into()
+    }
+
+    #[test]
+    fn identical_images_score_perfectly() {
+        let render = gradient();
+        let quality = Quality::measure(&source(), &render).unwrap();
+        assert!(quality.mean_delta_e < 1e-3);
+        assert!(quality.mean_ciede2000 < 1e-3);
+        assert!(quality.ssim > 0.9999);
+        assert!(quality.psnr > 60.0);
+        assert!(quality.heatmap.pixels().all(|pixel| pixel.0 == [0, 0, 4]));
+    }
+
+    #[test]
+    fn differences_lower_the_score() {
+                let mut render = gradient();
+        image::imageops::invert(&mut render);
+        let quality = Quality::measure(&source(), &render).unwrap();
+        assert!(quality.mean_delta_e > 10.0);
+        assert!(quality.mean_ciede2000 > 10.0);
+        assert!(quality.ssim < 0.5);
+        assert!(quality.psnr < 20.0);
+        assert!(quality.heatmap.pixels().any(|pixel| pixel.0 != [0, 0, 4]));
+    }
+
+    #[test]
+    fn mismatched_sizes_are_not_measured() {
+                assert!(Quality::measure(&source(), &RgbImage::new(16, 24)).is_none());
+    }
+
+    #[test]
+    fn heatmap_saturates_past_the_maximum() {
+        assert_eq!(heat(0.0), [0, 0, 4]);
+        assert_eq!(heat(0.5), [188, 55, 84]);
+        assert_eq!(heat(1.0), [252, 255, 164]);
+        assert_eq!(heat(3.0), heat(1.0));
+    }
+}
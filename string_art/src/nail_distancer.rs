@@ -1,18 +1,67 @@
-use crate::nails;
+use crate::{geometry::Point, nails, Float};
 use thiserror::Error;
 
-#[derive(Clone, Copy)]
-pub struct NailDistancer {
-    min: usize,
-    max: usize,
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NailDistance<S> {
+    /// Number of consecutive nails around the frame that can not be linked.
+    Ring(usize),
+    /// Pairs are validated from the nail positions.
+    Geometric(GeometricRule<S>),
+}
+
+/// Geometric conditions every pair of linked nails must meet.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GeometricRule<S> {
+    /// Minimum euclidean distance between two linked nails.
+    pub min_distance: S,
+    /// Minimum angle in radians between a link and the frame at each of its frame nails.
+    pub min_edge_angle: S,
+    /// Rejects links between nails lying on the same straight side of the frame.
+    pub distinct_sides: bool,
+}
+
+impl<S: Float> GeometricRule<S> {
+    pub fn euclidean(min_distance: S) -> Self {
+        Self {
+            min_distance,
+            min_edge_angle: S::ZERO,
+            distinct_sides: false,
+        }
+    }
+}
+
+impl<S> From<GeometricRule<S>> for NailDistance<S> {
+    fn from(value: GeometricRule<S>) -> Self {
+        NailDistance::Geometric(value)
+    }
+}
+
+impl<S> From<usize> for NailDistance<S> {
+    fn from(value: usize) -> Self {
+        NailDistance::Ring(value)
+    }
+}
+
+#[derive(Clone)]
+pub enum NailDistancer {
+    Ring {
+        min: usize,
+        max: usize,
+    },
+    // Lower triangular bit table of the valid pairs, `ranks` holds the valid pairs preceding each
+    // word so only the valid pairs get a slot in the baked table.
+    Geometric {
+        valid: Vec<u64>,
+        ranks: Vec<usize>,
+    },
 }
 
 impl NailDistancer {
     pub fn new(count: usize, distance: usize) -> Result<Self, Error> {
         if count < 2 * distance {
-            Err(Error((1 + count) / 2))
+            Err(Error(count.div_ceil(2)))
         } else {
-            Ok(Self {
+            Ok(Self::Ring {
                 min: distance,
                 //SAFETY: checked bounds in if
                 max: unsafe { count.unchecked_sub(distance) },
@@ -20,9 +69,67 @@ impl NailDistancer {
         }
     }
 
+    /// Validates the pairs of `positions` against `rule`. The first `frame_len` positions are
+    /// the frame nails in perimeter order, the edge and side rules are only applied to them.
+    pub fn geometric<S: Float>(
+        positions: &[Point<S>],
+        frame_len: usize,
+        rule: &GeometricRule<S>,
+    ) -> Self {
+        let frame = Frame::new(&positions[..frame_len.min(positions.len())]);
+        let sq_distance = rule.min_distance * rule.min_distance;
+        let max_cos = rule.min_edge_angle.cos();
+        let mut valid = vec![0u64; pair_count(positions.len()).div_ceil(64)];
+        for (big_idx, &big) in positions.iter().enumerate() {
+            for (small_idx, &small) in positions.iter().enumerate().take(big_idx) {
+                if big.sq_distance(&small) < sq_distance {
+                    continue;
+                }
+                if rule.distinct_sides && frame.share_side(big_idx, small_idx) {
+                    continue;
+                }
+                if rule.min_edge_angle > S::ZERO {
+                    let link = normalize(big - small);
+                    let steep = |idx: usize| {
+                        frame
+                            .tangent(idx)
+                            .is_none_or(|tangent| dot(link, tangent).abs() <= max_cos)
+                    };
+                    if !steep(big_idx) || !steep(small_idx) {
+                        continue;
+                    }
+                }
+                let pair = pair_index(big_idx, small_idx);
+                valid[pair / 64] |= 1 << (pair % 64);
+            }
+        }
+        let ranks = valid
+            .iter()
+            .scan(0, |acc, word| {
+                let rank = *acc;
+                *acc += word.count_ones() as usize;
+                Some(rank)
+            })
+            .collect();
+        Self::Geometric { valid, ranks }
+    }
+
     pub fn is_valid(&self, a_idx: usize, b_idx: usize) -> bool {
-        let diff = a_idx.abs_diff(b_idx);
-        diff > self.min && diff < self.max
+        match self {
+            NailDistancer::Ring { min, max } => {
+                let diff = a_idx.abs_diff(b_idx);
+                diff > *min && diff < *max
+            }
+            NailDistancer::Geometric { valid, .. } => {
+                if a_idx == b_idx {
+                    return false;
+                }
+                let pair = pair_index(a_idx.max(b_idx), a_idx.min(b_idx));
+                valid
+                    .get(pair / 64)
+                    .is_some_and(|word| word & (1 << (pair % 64)) != 0)
+            }
+        }
     }
 
     pub fn index_of<L: nails::Links>(
@@ -39,7 +146,8 @@ impl NailDistancer {
         }
     }
 
-    //SAFETY: caller must ensure that the indices are valid via NailDistancer::is_valid
+    /// # Safety
+    /// Caller must ensure that the indices are valid via [`NailDistancer::is_valid`].
     pub unsafe fn index_of_unchecked<L: nails::Links>(
         &self,
         a_idx: usize,
@@ -52,23 +160,139 @@ impl NailDistancer {
         } else {
             (b_idx, b_link, a_idx, a_link)
         };
-        let cap = self.max.unchecked_sub(1);
-        let first = if big_idx > cap {
-            let diff = big_idx.unchecked_sub(cap);
-            big_idx = cap;
-            small_idx = small_idx.unchecked_sub(diff);
-            diff.unchecked_mul(cap.unchecked_sub(self.min)).unchecked_mul(L::SQ_LEN)
-        } else {
-            0
+        match *self {
+            NailDistancer::Ring { min, max } => {
+                let cap = max.unchecked_sub(1);
+                let first = if big_idx > cap {
+                    let diff = big_idx.unchecked_sub(cap);
+                    big_idx = cap;
+                    small_idx = small_idx.unchecked_sub(diff);
+                    diff.unchecked_mul(cap.unchecked_sub(min))
+                        .unchecked_mul(L::SQ_LEN)
+                } else {
+                    0
+                };
+
+                let diff = big_idx.unchecked_sub(min);
+
+                first
+                    .unchecked_add((diff * diff.unchecked_sub(1) / 2).unchecked_mul(L::SQ_LEN))
+                    .unchecked_add(L::LEN.unchecked_mul(diff).unchecked_mul(big_link.into()))
+                    .unchecked_add(L::LEN.unchecked_mul(small_idx))
+                    .unchecked_add(small_link.into())
+            }
+            NailDistancer::Geometric {
+                ref valid,
+                ref ranks,
+            } => {
+                let rank = |pair: usize| {
+                    let word = pair / 64;
+                    match valid.get(word) {
+                        Some(bits) => {
+                            ranks.get_unchecked(word)
+                                + (bits & ((1 << (pair % 64)) - 1)).count_ones() as usize
+                        }
+                        None => ranks.last().map_or(0, |rank| {
+                            rank + valid.last().map_or(0, |bits| bits.count_ones() as usize)
+                        }),
+                    }
+                };
+                let row = rank(pair_count(big_idx));
+                let row_len = rank(pair_count(big_idx.unchecked_add(1))).unchecked_sub(row);
+                row.unchecked_mul(L::SQ_LEN)
+                    .unchecked_add(L::LEN.unchecked_mul(row_len).unchecked_mul(big_link.into()))
+                    .unchecked_add(
+                        L::LEN.unchecked_mul(rank(pair_index(big_idx, small_idx)).unchecked_sub(row)),
+                    )
+                    .unchecked_add(small_link.into())
+            }
+        }
+    }
+}
+
+fn pair_count(count: usize) -> usize {
+    count * count.saturating_sub(1) / 2
+}
+
+fn pair_index(big_idx: usize, small_idx: usize) -> usize {
+    pair_count(big_idx) + small_idx
+}
+
+// Tangents and straight sides of the frame nails, taken in perimeter order.
+struct Frame<S> {
+    tangents: Vec<Option<Point<S>>>,
+    // Sides before and after each nail.
+    sides: Vec<(usize, usize)>,
+}
+
+impl<S: Float> Frame<S> {
+    // Maximum cross product between the directions of two segments on the same straight side.
+    const COLLINEAR: f64 = 1e-4;
+
+    fn new(frame: &[Point<S>]) -> Self {
+        let len = frame.len();
+        if len < 3 {
+            return Self {
+                tangents: Vec::new(),
+                sides: Vec::new(),
+            };
+        }
+        let tangents = (0..len)
+            .map(|idx| {
+                let tangent = frame[(idx + 1) % len] - frame[(idx + len - 1) % len];
+                (dot(tangent, tangent) > S::EPSILON).then(|| normalize(tangent))
+            })
+            .collect();
+        let tolerance = num_traits::cast(Self::COLLINEAR).unwrap_or(S::EPSILON);
+        let directions: Vec<_> = (0..len)
+            .map(|idx| normalize(frame[(idx + 1) % len] - frame[idx]))
+            .collect();
+        let collinear = |a: Point<S>, b: Point<S>| {
+            (a.x * b.y - a.y * b.x).abs() < tolerance && dot(a, b) > S::ZERO
         };
+        let mut segment_sides = vec![0; len];
+        for idx in 1..len {
+            segment_sides[idx] = segment_sides[idx - 1]
+                + usize::from(!collinear(directions[idx - 1], directions[idx]));
+        }
+        let last = segment_sides[len - 1];
+        if last != 0 && collinear(directions[len - 1], directions[0]) {
+            for side in segment_sides.iter_mut().filter(|side| **side == last) {
+                *side = 0;
+            }
+        }
+        Self {
+            tangents,
+            sides: (0..len)
+                .map(|idx| (segment_sides[(idx + len - 1) % len], segment_sides[idx]))
+                .collect(),
+        }
+    }
+
+    fn tangent(&self, idx: usize) -> Option<Point<S>> {
+        self.tangents.get(idx).copied().flatten()
+    }
 
-        let diff = big_idx.unchecked_sub(self.min);
+    fn share_side(&self, a_idx: usize, b_idx: usize) -> bool {
+        match (self.sides.get(a_idx), self.sides.get(b_idx)) {
+            (Some(&(a_in, a_out)), Some(&(b_in, b_out))) => {
+                a_in == b_in || a_in == b_out || a_out == b_in || a_out == b_out
+            }
+            _ => false,
+        }
+    }
+}
+
+fn dot<S: Float>(a: Point<S>, b: Point<S>) -> S {
+    a.x * b.x + a.y * b.y
+}
 
-        first
-            .unchecked_add((diff * diff.unchecked_sub(1) / 2).unchecked_mul(L::SQ_LEN))
-            .unchecked_add(L::LEN.unchecked_mul(diff).unchecked_mul(big_link.into()))
-            .unchecked_add(L::LEN.unchecked_mul(small_idx))
-            .unchecked_add(small_link.into())
+fn normalize<S: Float>(point: Point<S>) -> Point<S> {
+    let len = num_traits::Float::sqrt(dot(point, point));
+    if len > S::ZERO {
+        point / len
+    } else {
+        point
     }
 }
 
@@ -5,16 +5,28 @@ use num_traits::{AsPrimitive, ConstOne, Float as _};
 use crate::{
     geometry::{Point, Segment},
     grid::Grid,
-    nail_distancer::{self, NailDistancer},
+    nail_distancer::{self, NailDistance, NailDistancer},
     nails,
     verboser::{self, Message},
     Float,
 };
 
+mod import;
+mod interior;
+mod pixels;
+mod polygon;
+
+pub use import::{ImportError, NailPosition, Normalization, ParseError};
+pub use interior::{Interior, InteriorError};
+pub(crate) use pixels::Coverage;
+pub use pixels::{LineCoverage, PixelArena};
+pub use polygon::{Corners, PolygonError};
+
 #[derive(Clone)]
 pub struct NailTable<N: nails::Handle> {
     nails: Vec<N::Nail>,
     handle: N,
+    frame_len: usize,
 }
 
 impl<N: nails::Handle> NailTable<N> {
@@ -28,7 +40,6 @@ impl<N: nails::Handle> NailTable<N> {
         usize: AsPrimitive<B::Scalar>,
     {
         let nails = (0..nail_count)
-            .into_iter()
             .map(|i| {
                 verboser.verbose(Message::CreatingNail(i));
                 let theta: B::Scalar =
@@ -46,6 +57,7 @@ impl<N: nails::Handle> NailTable<N> {
         Self {
             nails,
             handle: nail_builder.build_handle(),
+            frame_len: nail_count,
         }
     }
 
@@ -56,60 +68,80 @@ impl<N: nails::Handle> NailTable<N> {
     pub fn nails(&self) -> &[N::Nail] {
         &self.nails
     }
+
+    /// Number of nails placed on the frame, the interior ones are stored after them.
+    pub fn frame_len(&self) -> usize {
+        self.frame_len
+    }
 }
 
 pub struct BakedNailTable<N: nails::Handle> {
     table: NailTable<N>,
     segments: Vec<BakedSegment<N::Scalar>>,
     distancer: NailDistancer,
+    pixels: Option<PixelArena<N::Scalar>>,
 }
 
 impl<N: nails::Handle<Error: std::error::Error>> BakedNailTable<N> {
-    pub fn new(table: NailTable<N>, min_nail_distance: usize) -> Result<Self, Error<N::Error>> {
+    /// Fails with [`Error::Cancelled`] as soon as `verboser` is cancelled while baking.
+    pub fn new(
+        table: NailTable<N>,
+        min_nail_distance: impl Into<NailDistance<N::Scalar>>,
+        verboser: &mut impl verboser::Verboser,
+    ) -> Result<Self, Error<N::Error>> {
         let nail_count = table.nails.len();
-        let distancer =
-            NailDistancer::new(nail_count, min_nail_distance).map_err(Error::Distancer)?;
+        let distancer = match min_nail_distance.into() {
+            NailDistance::Ring(distance) => {
+                NailDistancer::new(nail_count, distance).map_err(Error::Distancer)?
+            }
+            NailDistance::Geometric(rule) => NailDistancer::geometric(
+                &table
+                    .nails
+                    .iter()
+                    .map(|&nail| table.handle.position(nail))
+                    .collect::<Vec<_>>(),
+                table.frame_len,
+                &rule,
+            ),
+        };
         let nails = &table.nails;
-
-        Ok(Self {
-            segments: (0..nail_count)
-                .into_iter()
-                .map(move |big_idx| {
-                    N::LINKS
-                        .into_iter()
-                        .map(move |big_link| {
-                            (0..big_idx)
-                                .filter_map(move |small_idx| {
-                                    if distancer.is_valid(big_idx, small_idx) {
-                                        Some(N::LINKS.into_iter().map(move |small_link| {
-                                            //((big_idx, big_link), (small_idx, small_link))
-                                            Ok(BakedSegment {
-                                                segment: table.handle.get_segment(
-                                                    (
-                                                        unsafe { nails.get_unchecked(big_idx) },
-                                                        big_link,
-                                                    ),
-                                                    (
-                                                        unsafe { nails.get_unchecked(small_idx) },
-                                                        small_link,
-                                                    ),
-                                                )?,
-                                                used: false,
-                                            })
-                                        }))
-                                    } else {
-                                        None
-                                    }
+        let handle = table.handle;
+        let valid = &distancer;
+        let verboser = &*verboser;
+
+        let segments = (0..nail_count)
+            .take_while(|_| !verboser.is_cancelled())
+            .flat_map(move |big_idx| {
+                N::LINKS.into_iter().flat_map(move |big_link| {
+                    (0..big_idx)
+                        .filter(move |&small_idx| valid.is_valid(big_idx, small_idx))
+                        .flat_map(move |small_idx| {
+                            N::LINKS.into_iter().map(move |small_link| {
+                                //((big_idx, big_link), (small_idx, small_link))
+                                Ok(BakedSegment {
+                                    segment: handle.get_segment(
+                                        (unsafe { nails.get_unchecked(big_idx) }, big_link),
+                                        (
+                                            unsafe { nails.get_unchecked(small_idx) },
+                                            small_link,
+                                        ),
+                                    )?,
+                                    used: false,
                                 })
-                                .flatten()
+                            })
                         })
-                        .flatten()
                 })
-                .flatten()
-                .collect::<Result<_, _>>()
-                .map_err(Error::Nail)?,
+            })
+            .collect::<Result<_, _>>()
+            .map_err(Error::Nail)?;
+        if verboser.is_cancelled() {
+            return Err(Error::Cancelled);
+        }
+        Ok(Self {
+            segments,
             table,
             distancer,
+            pixels: None,
         })
     }
 
@@ -117,11 +149,58 @@ impl<N: nails::Handle<Error: std::error::Error>> BakedNailTable<N> {
         &self.distancer
     }
 
+    pub(crate) fn segments(&self) -> &[BakedSegment<N::Scalar>] {
+        &self.segments
+    }
+
     pub(crate) fn segments_mut(&mut self) -> &mut [BakedSegment<N::Scalar>] {
         &mut self.segments
     }
 }
 
+impl<N: nails::Handle> BakedNailTable<N> {
+    /// Bytes [`BakedNailTable::bake_pixels`] would take with the given thread width.
+    pub fn pixel_memory_estimate(&self, thread_width: N::Scalar) -> usize {
+        PixelArena::estimate(self.segments.iter().map(|baked| &baked.segment), thread_width)
+    }
+
+    /// Stores the pixels covered by every segment so scoring does not rasterize them again. Lines
+    /// keep being rasterized on the fly if the grid is too big for the arena or `verboser` is
+    /// cancelled while baking, returning `false`.
+    pub fn bake_pixels(
+        &mut self,
+        grid: &Grid,
+        thread_width: N::Scalar,
+        verboser: &mut impl verboser::Verboser,
+    ) -> bool {
+        self.pixels = PixelArena::bake(
+            self.segments.iter().map(|baked| &baked.segment),
+            grid,
+            thread_width,
+            verboser,
+        );
+        self.pixels.is_some()
+    }
+
+    pub fn pixel_arena(&self) -> Option<&PixelArena<N::Scalar>> {
+        self.pixels.as_ref()
+    }
+
+    // Pixels under `segment`, which must be the baked segment at `idx`.
+    pub(crate) fn coverage<'a>(
+        &'a self,
+        idx: usize,
+        segment: &'a Segment<N::Scalar>,
+        grid: &'a Grid,
+        thread_width: N::Scalar,
+    ) -> impl Iterator<Item = (usize, N::Scalar)> + 'a {
+        match &self.pixels {
+            Some(arena) => Coverage::Baked(arena.pixels(idx)),
+            None => Coverage::Lazy(grid.get_pixel_coverage_in_segment(segment, thread_width)),
+        }
+    }
+}
+
 impl<N: nails::Handle> Deref for BakedNailTable<N> {
     type Target = NailTable<N>;
 
@@ -164,4 +243,6 @@ pub enum Error<N> {
     Nail(N),
     #[error(transparent)]
     Distancer(nail_distancer::Error),
+    #[error("The baking was cancelled.")]
+    Cancelled,
 }
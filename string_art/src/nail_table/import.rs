@@ -0,0 +1,366 @@
+use std::{io, path::Path};
+
+use num_traits::{AsPrimitive, ConstZero};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    geometry::Point,
+    grid::Grid,
+    nails,
+    verboser::{self, Message},
+    Float,
+};
+
+use super::NailTable;
+
+/// A nail position as stored in an imported file. `rotation` is in radians.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(from = "RawNailPosition<S>")]
+pub struct NailPosition<S> {
+    pub x: S,
+    pub y: S,
+    pub rotation: Option<S>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawNailPosition<S> {
+    Point(S, S),
+    Rotated(S, S, S),
+    Object {
+        x: S,
+        y: S,
+        rotation: Option<S>,
+    },
+}
+
+impl<S> From<RawNailPosition<S>> for NailPosition<S> {
+    fn from(value: RawNailPosition<S>) -> Self {
+        match value {
+            RawNailPosition::Point(x, y) => Self { x, y, rotation: None },
+            RawNailPosition::Rotated(x, y, rotation) => Self {
+                x,
+                y,
+                rotation: Some(rotation),
+            },
+            RawNailPosition::Object { x, y, rotation } => Self { x, y, rotation },
+        }
+    }
+}
+
+impl<S: Float> NailPosition<S> {
+    /// Reads a `.json` file as [`NailPosition::parse_json`] and anything else as [`NailPosition::parse_csv`].
+    pub fn read(path: impl AsRef<Path>) -> Result<Vec<Self>, ParseError>
+    where
+        S: for<'de> Deserialize<'de>,
+    {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(ParseError::Io)?;
+        if path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+        {
+            Self::parse_json(&text)
+        } else {
+            Self::parse_csv(&text)
+        }
+    }
+
+    /// Parses an array whose items are `[x, y]`, `[x, y, rotation]` or `{"x", "y", "rotation"}`.
+    pub fn parse_json(text: &str) -> Result<Vec<Self>, ParseError>
+    where
+        S: for<'de> Deserialize<'de>,
+    {
+        serde_json::from_str(text).map_err(ParseError::Json)
+    }
+
+    /// Parses one `x, y[, rotation]` row per line. Fields may be separated by commas, semicolons or
+    /// tabs, lines starting with `#` are ignored and a non numeric first row is taken as a header.
+    pub fn parse_csv(text: &str) -> Result<Vec<Self>, ParseError> {
+        let mut positions = Vec::new();
+        let mut header_allowed = true;
+        for (idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields = line
+                .split([',', ';', '\t'])
+                .map(|field| S::from_str_radix(field.trim(), 10))
+                .collect::<Result<Vec<_>, _>>();
+            match fields.as_deref() {
+                Ok(&[x, y]) => positions.push(Self { x, y, rotation: None }),
+                Ok(&[x, y, rotation]) => positions.push(Self {
+                    x,
+                    y,
+                    rotation: Some(rotation),
+                }),
+                Err(_) if header_allowed => {}
+                _ => return Err(ParseError::Csv(idx + 1)),
+            }
+            header_allowed = false;
+        }
+        Ok(positions)
+    }
+}
+
+/// How imported positions are mapped into the image grid.
+#[derive(Clone, Copy, Debug)]
+pub enum Normalization<S> {
+    /// Uniformly scales the positions to fit inside the grid, keeping them centered.
+    Fit,
+    /// Stretches each axis independently so the positions span the whole grid.
+    Fill,
+    /// Applies `position * scale + offset`, every position must fall inside the grid.
+    Explicit { scale: S, offset: Point<S> },
+}
+
+impl<S: Float> Normalization<S> {
+    fn apply(self, positions: &[NailPosition<S>], grid: Grid) -> Vec<(Point<S>, S)>
+    where
+        usize: AsPrimitive<S>,
+    {
+        let size = Point {
+            x: grid.width.as_(),
+            y: grid.height.as_(),
+        };
+        let (scale, offset) = match self {
+            Normalization::Explicit { scale, offset } => (Point { x: scale, y: scale }, offset),
+            Normalization::Fit | Normalization::Fill => {
+                let (min, max) = positions.iter().fold(
+                    (
+                        Point {
+                            x: S::INFINITY,
+                            y: S::INFINITY,
+                        },
+                        Point {
+                            x: -S::INFINITY,
+                            y: -S::INFINITY,
+                        },
+                    ),
+                    |(min, max), pos| {
+                        (
+                            Point {
+                                x: Float::min(min.x, pos.x),
+                                y: Float::min(min.y, pos.y),
+                            },
+                            Point {
+                                x: max.x.max(pos.x),
+                                y: max.y.max(pos.y),
+                            },
+                        )
+                    },
+                );
+                let extent = max - min;
+                let ratio = |size: S, extent: S| {
+                    if extent > S::EPSILON {
+                        Some(size / extent)
+                    } else {
+                        None
+                    }
+                };
+                let (x, y) = (ratio(size.x, extent.x), ratio(size.y, extent.y));
+                let scale = match self {
+                    Normalization::Fill => Point {
+                        x: x.or(y).unwrap_or(S::ONE),
+                        y: y.or(x).unwrap_or(S::ONE),
+                    },
+                    _ => {
+                        let uniform = match (x, y) {
+                            (Some(x), Some(y)) => Float::min(x, y),
+                            (x, y) => x.or(y).unwrap_or(S::ONE),
+                        };
+                        Point {
+                            x: uniform,
+                            y: uniform,
+                        }
+                    }
+                };
+                (scale, (size - extent * scale) * S::HALF - min * scale)
+            }
+        };
+        let center = size * S::HALF;
+        positions
+            .iter()
+            .map(|pos| {
+                let point = Point { x: pos.x, y: pos.y } * scale + offset;
+                let rotation = match pos.rotation {
+                    Some(rotation) => (rotation.sin() * scale.y).atan2(rotation.cos() * scale.x),
+                    None => (point.y - center.y).atan2(point.x - center.x),
+                };
+                (point, rotation)
+            })
+            .collect()
+    }
+}
+
+impl<N: nails::Handle> NailTable<N> {
+    pub fn from_positions<B: nails::Builder<Scalar: Float, Handle = N, Nail = N::Nail>>(
+        grid: Grid,
+        nail_builder: B,
+        positions: &[NailPosition<B::Scalar>],
+        normalization: Normalization<B::Scalar>,
+        verboser: &mut impl verboser::Verboser,
+    ) -> Result<Self, ImportError<N::Error>>
+    where
+        usize: AsPrimitive<B::Scalar>,
+    {
+        if positions.is_empty() {
+            return Err(ImportError::Empty);
+        }
+        let placed = normalization.apply(positions, grid);
+        // Fitted positions span the grid by construction, explicit ones may fall anywhere.
+        if let Normalization::Explicit { .. } = normalization {
+            let (width, height): (B::Scalar, B::Scalar) = (grid.width.as_(), grid.height.as_());
+            let outside = placed.iter().position(|(point, _)| {
+                !(point.x >= B::Scalar::ZERO
+                    && point.x <= width
+                    && point.y >= B::Scalar::ZERO
+                    && point.y <= height)
+            });
+            if let Some(idx) = outside {
+                return Err(ImportError::OutsideGrid(idx));
+            }
+        }
+        for (a_idx, (a, _)) in placed.iter().enumerate() {
+            for (b_idx, (b, _)) in placed.iter().enumerate().take(a_idx) {
+                if a.aprox_eq(b) {
+                    return Err(ImportError::Duplicate(b_idx, a_idx));
+                }
+            }
+        }
+
+        let nails: Vec<_> = placed
+            .into_iter()
+            .enumerate()
+            .map(|(i, (position, rotation))| {
+                verboser.verbose(Message::CreatingNail(i));
+                nail_builder.build_nail(position, rotation)
+            })
+            .collect();
+        verboser.verbose(Message::CreatingNail(nails.len()));
+        let handle = nail_builder.build_handle();
+        for (a_idx, a) in nails.iter().enumerate() {
+            for (b_idx, b) in nails.iter().enumerate().take(a_idx) {
+                for a_link in N::LINKS {
+                    for b_link in N::LINKS {
+                        if let Err(err) = handle.get_segment((b, b_link), (a, a_link)) {
+                            return Err(ImportError::Overlap(b_idx, a_idx, err));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(Self {
+            frame_len: nails.len(),
+            nails,
+            handle,
+        })
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ParseError {
+    #[error(transparent)]
+    Io(io::Error),
+    #[error(transparent)]
+    Json(serde_json::Error),
+    #[error("Invalid nail position at line {0}.")]
+    Csv(usize),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ImportError<N> {
+    #[error("The nail file does not contain any position.")]
+    Empty,
+    #[error("Nail {0} falls outside the image once scaled.")]
+    OutsideGrid(usize),
+    #[error("Nails {0} and {1} share the same position.")]
+    Duplicate(usize, usize),
+    #[error("Nails {0} and {1} are overlapping: {2}")]
+    Overlap(usize, usize, N),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{nails::Circular, verboser::Silent};
+
+    #[test]
+    fn parses_csv_rows() {
+        let text = "x;y;rotation\n# comment\n1, 2\n\n3\t4\t0.5\n";
+        let positions = NailPosition::<f32>::parse_csv(text).unwrap();
+        assert_eq!(positions.len(), 2);
+        assert_eq!((positions[0].x, positions[0].y, positions[0].rotation), (1.0, 2.0, None));
+        assert_eq!((positions[1].x, positions[1].y, positions[1].rotation), (3.0, 4.0, Some(0.5)));
+        assert!(matches!(
+            NailPosition::<f32>::parse_csv("1,2\nx,y\n"),
+            Err(ParseError::Csv(2))
+        ));
+        assert!(matches!(
+            NailPosition::<f32>::parse_csv("1,2,3,4\n"),
+            Err(ParseError::Csv(1))
+        ));
+    }
+
+    #[test]
+    fn parses_json_items() {
+        let text = r#"[[1, 2], [3, 4, 0.5], {"x": 5, "y": 6}, {"x": 7, "y": 8, "rotation": 1}]"#;
+        let positions = NailPosition::<f32>::parse_json(text).unwrap();
+        let rotations: Vec<_> = positions.iter().map(|pos| pos.rotation).collect();
+        assert_eq!(rotations, [None, Some(0.5), None, Some(1.0)]);
+        assert_eq!((positions[2].x, positions[2].y), (5.0, 6.0));
+    }
+
+    fn square() -> Vec<NailPosition<f32>> {
+        [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)]
+            .map(|(x, y)| NailPosition { x, y, rotation: None })
+            .to_vec()
+    }
+
+    #[test]
+    fn fits_positions_centered() {
+        let grid = Grid::new(100, 200);
+        let table = NailTable::from_positions(
+            grid,
+            Circular::new(1.0),
+            &square(),
+            Normalization::Fit,
+            &mut Silent,
+        )
+        .unwrap();
+        let xs: Vec<_> = table.nails().iter().map(|nail| nail.x).collect();
+        let ys: Vec<_> = table.nails().iter().map(|nail| nail.y).collect();
+        assert_eq!(xs, [50.0, 150.0, 150.0, 50.0]);
+        assert_eq!(ys, [0.0, 0.0, 100.0, 100.0]);
+    }
+
+    #[test]
+    fn rejects_invalid_layouts() {
+        let grid = Grid::new(100, 100);
+        let explicit = Normalization::Explicit {
+            scale: 20.0,
+            offset: Point { x: 0.0, y: 0.0 },
+        };
+        assert!(matches!(
+            NailTable::from_positions(grid, Circular::new(1.0), &square(), explicit, &mut Silent),
+            Err(ImportError::OutsideGrid(1))
+        ));
+        let mut duplicated = square();
+        duplicated.push(duplicated[1]);
+        assert!(matches!(
+            NailTable::from_positions(
+                grid,
+                Circular::new(1.0),
+                &duplicated,
+                Normalization::Fit,
+                &mut Silent
+            ),
+            Err(ImportError::Duplicate(1, 4))
+        ));
+        assert!(matches!(
+            NailTable::from_positions(grid, Circular::new(1.0), &[], Normalization::Fit, &mut Silent),
+            Err(ImportError::Empty)
+        ));
+    }
+}
@@ -0,0 +1,377 @@
+use num_traits::{AsPrimitive, ConstOne, ConstZero, Float as _, ToPrimitive};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use crate::{
+    geometry::Point,
+    nails,
+    verboser::{self, Message},
+    Float, Image,
+};
+
+use super::NailTable;
+
+/// Distribution of the nails placed inside the frame.
+#[derive(Clone, Copy, Debug)]
+pub enum Interior<S> {
+    /// Square lattice with the given spacing.
+    Grid { spacing: S },
+    /// Poisson-disk sampling, no two nails are closer than `min_distance`.
+    Poisson { min_distance: S, seed: u64 },
+    /// `count` nails sampled with a density proportional to the edges of the image.
+    Detail {
+        count: usize,
+        min_distance: S,
+        seed: u64,
+    },
+}
+
+const ATTEMPTS: usize = 30;
+
+impl<N: nails::Handle> NailTable<N> {
+    /// Appends nails inside the region enclosed by the current ones, keeping them apart from every
+    /// existing nail. The whole image is used as region when there are less than three nails.
+    /// Fails if the spacing or the minimum distance is not a positive finite number of pixels.
+    pub fn add_interior<B: nails::Builder<Scalar = N::Scalar, Handle = N, Nail = N::Nail>>(
+        &mut self,
+        nail_builder: &B,
+        image: &Image<N::Scalar>,
+        interior: Interior<N::Scalar>,
+        verboser: &mut impl verboser::Verboser,
+    ) -> Result<(), InteriorError>
+    where
+        usize: AsPrimitive<N::Scalar>,
+    {
+        let frame: Vec<_> = self
+            .nails
+            .iter()
+            .map(|&nail| self.handle.position(nail))
+            .collect();
+        let size = Point {
+            x: image.width.as_(),
+            y: image.height.as_(),
+        };
+        let min_distance = match interior {
+            Interior::Grid { spacing } => spacing,
+            Interior::Poisson { min_distance, .. } | Interior::Detail { min_distance, .. } => {
+                min_distance
+            }
+        };
+        if !(min_distance > N::Scalar::ZERO && min_distance.is_finite()) {
+            return Err(InteriorError::Distance);
+        }
+        let mut region = Region::new(frame, size, min_distance);
+        let first = region.points.len();
+
+        match interior {
+            Interior::Grid { spacing } => {
+                let cols = (size.x / spacing).floor().to_usize().unwrap_or(0);
+                let rows = (size.y / spacing).floor().to_usize().unwrap_or(0);
+                let offset = Point {
+                    x: size.x - spacing * cols.as_(),
+                    y: size.y - spacing * rows.as_(),
+                } * N::Scalar::HALF;
+                for row in 0..=rows {
+                    for col in 0..=cols {
+                        region.try_insert(
+                            offset
+                                + Point {
+                                    x: col.as_(),
+                                    y: row.as_(),
+                                } * spacing,
+                        );
+                    }
+                }
+            }
+            Interior::Poisson { min_distance, seed } => {
+                let mut rng = SmallRng::seed_from_u64(seed);
+                let mut active: Vec<Point<N::Scalar>> = (0..ATTEMPTS)
+                    .map(|_| random_point(&mut rng, size))
+                    .find(|&point| region.try_insert(point))
+                    .into_iter()
+                    .collect();
+                while !active.is_empty() {
+                    let idx = rng.gen_range(0..active.len());
+                    let center = active[idx];
+                    let candidate = (0..ATTEMPTS)
+                        .map(|_| {
+                            let angle = N::Scalar::TWO * N::Scalar::PI * random::<N::Scalar>(&mut rng);
+                            let radius = min_distance * (N::Scalar::ONE + random::<N::Scalar>(&mut rng));
+                            center
+                                + Point {
+                                    x: angle.cos(),
+                                    y: angle.sin(),
+                                } * radius
+                        })
+                        .find(|&point| region.try_insert(point));
+                    match candidate {
+                        Some(point) => active.push(point),
+                        None => {
+                            active.swap_remove(idx);
+                        }
+                    }
+                }
+            }
+            Interior::Detail { count, seed, .. } => {
+                let mut rng = SmallRng::seed_from_u64(seed);
+                let density = edge_density(image);
+                let total = density.last().copied().unwrap_or(N::Scalar::ZERO);
+                let mut placed = 0;
+                for _ in 0..count * ATTEMPTS {
+                    if placed == count {
+                        break;
+                    }
+                    let point = if total > N::Scalar::ZERO {
+                        let target = total * random::<N::Scalar>(&mut rng);
+                        let idx = density
+                            .partition_point(|&acc| acc <= target)
+                            .min(density.len() - 1);
+                        Point {
+                            x: (idx % image.width).as_() + random::<N::Scalar>(&mut rng),
+                            y: (idx / image.width).as_() + random::<N::Scalar>(&mut rng),
+                        }
+                    } else {
+                        random_point(&mut rng, size)
+                    };
+                    if region.try_insert(point) {
+                        placed += 1;
+                    }
+                }
+            }
+        }
+
+        let center = size * N::Scalar::HALF;
+        for (idx, &point) in region.points.iter().enumerate().skip(first) {
+            verboser.verbose(Message::CreatingNail(idx));
+            self.nails.push(
+                nail_builder.build_nail(point, (point.y - center.y).atan2(point.x - center.x)),
+            );
+        }
+        verboser.verbose(Message::CreatingNail(self.nails.len()));
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum InteriorError {
+    #[error("The spacing and the minimum distance of interior nails must be positive.")]
+    Distance,
+}
+
+// Area enclosed by the frame, with a bucket grid to look up the points close to a candidate.
+struct Region<S> {
+    frame: Vec<Point<S>>,
+    size: Point<S>,
+    min_distance: S,
+    points: Vec<Point<S>>,
+    cols: usize,
+    rows: usize,
+    buckets: Vec<Vec<usize>>,
+}
+
+impl<S: Float> Region<S>
+where
+    usize: AsPrimitive<S>,
+{
+    fn new(frame: Vec<Point<S>>, size: Point<S>, min_distance: S) -> Self {
+        let cols = (size.x / min_distance).floor().to_usize().unwrap_or(0) + 1;
+        let rows = (size.y / min_distance).floor().to_usize().unwrap_or(0) + 1;
+        let mut region = Self {
+            points: Vec::with_capacity(frame.len()),
+            size,
+            min_distance,
+            cols,
+            rows,
+            buckets: vec![Vec::new(); cols * rows],
+            frame: if frame.len() < 3 { Vec::new() } else { frame.clone() },
+        };
+        for point in frame {
+            region.insert(point);
+        }
+        region
+    }
+
+    fn bucket(&self, point: Point<S>) -> (usize, usize) {
+        let cell = |value: S, len: usize| {
+            (value / self.min_distance)
+                .floor()
+                .max(S::ZERO)
+                .to_usize()
+                .unwrap_or(0)
+                .min(len - 1)
+        };
+        (cell(point.x, self.cols), cell(point.y, self.rows))
+    }
+
+    fn insert(&mut self, point: Point<S>) {
+        let (col, row) = self.bucket(point);
+        self.buckets[row * self.cols + col].push(self.points.len());
+        self.points.push(point);
+    }
+
+    fn try_insert(&mut self, point: Point<S>) -> bool {
+        if point.x < S::ZERO || point.y < S::ZERO || point.x > self.size.x || point.y > self.size.y
+        {
+            return false;
+        }
+        if !self.frame.is_empty() && !contains(&self.frame, point) {
+            return false;
+        }
+        let (col, row) = self.bucket(point);
+        let sq_distance = self.min_distance * self.min_distance;
+        for r in row.saturating_sub(1)..(row + 2).min(self.rows) {
+            for c in col.saturating_sub(1)..(col + 2).min(self.cols) {
+                for &idx in &self.buckets[r * self.cols + c] {
+                    if self.points[idx].sq_distance(&point) < sq_distance {
+                        return false;
+                    }
+                }
+            }
+        }
+        self.insert(point);
+        true
+    }
+}
+
+// Even-odd rule, so it also gives a sensible answer for self intersecting frames.
+fn contains<S: Float>(polygon: &[Point<S>], point: Point<S>) -> bool {
+    let mut inside = false;
+    let mut prev = polygon[polygon.len() - 1];
+    for &curr in polygon {
+        if (curr.y > point.y) != (prev.y > point.y)
+            && point.x < (prev.x - curr.x) * (point.y - curr.y) / (prev.y - curr.y) + curr.x
+        {
+            inside = !inside;
+        }
+        prev = curr;
+    }
+    inside
+}
+
+// Cumulative Sobel gradient magnitude of the lightness channel.
+fn edge_density<S: Float>(image: &Image<S>) -> Vec<S> {
+    let (width, height) = (image.width, image.height);
+    let pixels = image.pixels();
+    let l = |x: usize, y: usize| pixels[y * width + x].l;
+    let mut acc = S::ZERO;
+    (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            if x > 0 && y > 0 && x + 1 < width && y + 1 < height {
+                let gx = l(x + 1, y - 1) + S::TWO * l(x + 1, y) + l(x + 1, y + 1)
+                    - l(x - 1, y - 1)
+                    - S::TWO * l(x - 1, y)
+                    - l(x - 1, y + 1);
+                let gy = l(x - 1, y + 1) + S::TWO * l(x, y + 1) + l(x + 1, y + 1)
+                    - l(x - 1, y - 1)
+                    - S::TWO * l(x, y - 1)
+                    - l(x + 1, y - 1);
+                acc += num_traits::Float::sqrt(gx * gx + gy * gy);
+            }
+            acc
+        })
+        .collect()
+}
+
+fn random<S: Float>(rng: &mut SmallRng) -> S {
+    num_traits::cast(rng.gen::<f64>()).unwrap_or(S::ZERO)
+}
+
+fn random_point<S: Float>(rng: &mut SmallRng, size: Point<S>) -> Point<S> {
+    Point {
+        x: size.x * random::<S>(rng),
+        y: size.y * random::<S>(rng),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::RgbImage;
+
+    use super::*;
+    use crate::{grid::Grid, nails::Circular, verboser::Silent};
+
+    fn layout(interior: Interior<f32>) -> Result<Vec<Point<f32>>, InteriorError> {
+        // A dark disc, its outline holds every edge.
+        let image = Image::from(RgbImage::from_fn(100, 100, |x, y| {
+            let inside = (x as f32 - 50.0).hypot(y as f32 - 50.0) < 25.0;
+            image::Rgb([if inside { 0 } else { 255 }; 3])
+        }));
+        let mut table = NailTable::ellipse(Grid::new(100, 100), Circular::new(0.5), 64, &mut Silent);
+        table.add_interior(&Circular::new(0.5), &image, interior, &mut Silent)?;
+        Ok(table.nails()[64..].to_vec())
+    }
+
+    fn min_distance(points: &[Point<f32>]) -> f32 {
+        let mut min = f32::INFINITY;
+        for (idx, a) in points.iter().enumerate() {
+            for b in &points[..idx] {
+                min = min.min(a.distance(b));
+            }
+        }
+        min
+    }
+
+    #[test]
+    fn places_nails_inside_the_frame_apart() {
+        for interior in [
+            Interior::Grid { spacing: 10.0 },
+            Interior::Poisson {
+                min_distance: 8.0,
+                seed: 1,
+            },
+            Interior::Detail {
+                count: 40,
+                min_distance: 6.0,
+                seed: 1,
+            },
+        ] {
+            let points = layout(interior).unwrap();
+            assert!(!points.is_empty());
+            assert!(points.iter().all(|point| {
+                point.distance(&Point { x: 50.0, y: 50.0 }) < 50.0
+            }));
+            assert!(min_distance(&points) >= 6.0 - 1e-3);
+        }
+        let grid = layout(Interior::Grid { spacing: 10.0 }).unwrap();
+        assert!(grid.iter().all(|point| point.x % 10.0 == 0.0 && point.y % 10.0 == 0.0));
+    }
+
+    #[test]
+    fn seeds_repeat_the_layout() {
+        let poisson = Interior::Poisson {
+            min_distance: 8.0,
+            seed: 7,
+        };
+        let first = layout(poisson).unwrap();
+        let second = layout(poisson).unwrap();
+        assert!(first.iter().zip(&second).all(|(a, b)| a.aprox_eq(b)));
+        let detail = Interior::Detail {
+            count: 25,
+            min_distance: 4.0,
+            seed: 3,
+        };
+        let detail = layout(detail).unwrap();
+        assert!(detail.len() <= 25);
+        // Detail follows the outline of the disc.
+        let center = Point { x: 50.0, y: 50.0 };
+        assert!(detail.iter().all(|point| (point.distance(&center) - 25.0).abs() < 4.0));
+    }
+
+    #[test]
+    fn rejects_non_positive_distances() {
+        for interior in [
+            Interior::Grid { spacing: 0.0 },
+            Interior::Poisson {
+                min_distance: -1.0,
+                seed: 0,
+            },
+            Interior::Detail {
+                count: 10,
+                min_distance: f32::NAN,
+                seed: 0,
+            },
+        ] {
+            assert!(matches!(layout(interior), Err(InteriorError::Distance)));
+        }
+    }
+}
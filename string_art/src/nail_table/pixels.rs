@@ -0,0 +1,203 @@
+use crate::{geometry::Segment, grid::Grid, verboser::Verboser, Float};
+
+/// How the pixels under each line are found while scoring.
+#[derive(Clone, Copy, Debug)]
+pub struct LineCoverage<S> {
+    /// Width of the thread in pixels.
+    pub thread_width: S,
+    /// Memory in bytes allowed to keep the pixels of every segment baked. Lines are rasterized on
+    /// every evaluation when it is `None` or too small for the estimate.
+    pub pixel_budget: Option<usize>,
+}
+
+impl<S> LineCoverage<S> {
+    pub fn new(thread_width: S) -> Self {
+        Self {
+            thread_width,
+            pixel_budget: None,
+        }
+    }
+
+    pub fn with_pixel_budget(self, budget: usize) -> Self {
+        Self {
+            pixel_budget: Some(budget),
+            ..self
+        }
+    }
+}
+
+/// Pixels covered by every baked segment, stored as spans of consecutive pixel indices with the
+/// coverage of each pixel quantized to a byte.
+pub struct PixelArena<S> {
+    // First span and first pixel of each segment, closed by the totals.
+    offsets: Vec<(usize, usize)>,
+    spans: Vec<Span>,
+    coverage: Vec<u8>,
+    levels: [S; 256],
+}
+
+#[derive(Clone, Copy)]
+struct Span {
+    start: u32,
+    len: u32,
+}
+
+const SPAN_BYTES: usize = size_of::<Span>();
+const OFFSET_BYTES: usize = size_of::<(usize, usize)>();
+
+impl<S: Float> PixelArena<S> {
+    /// Rasterizes every segment, `None` if the grid has too many pixels for `u32` indices or
+    /// `verboser` is cancelled first.
+    pub(crate) fn bake<'a>(
+        segments: impl ExactSizeIterator<Item = &'a Segment<S>>,
+        grid: &Grid,
+        thread_width: S,
+        verboser: &impl Verboser,
+    ) -> Option<Self> {
+        u32::try_from(grid.width * grid.height).ok()?;
+        let mut arena = Self {
+            offsets: Vec::with_capacity(segments.len() + 1),
+            spans: Vec::new(),
+            coverage: Vec::new(),
+            levels: core::array::from_fn(|level| {
+                num_traits::cast::<_, S>(level).unwrap_or(S::ZERO) / S::TWO_FIVE_FIVE
+            }),
+        };
+        let mut pixels = Vec::new();
+        for segment in segments {
+            if verboser.is_cancelled() {
+                return None;
+            }
+            let first_span = arena.spans.len();
+            arena.offsets.push((first_span, arena.coverage.len()));
+            pixels.clear();
+            pixels.extend(grid.get_pixel_coverage_in_segment(segment, thread_width));
+            pixels.sort_unstable_by_key(|&(idx, _)| idx);
+            for &(idx, coverage) in &pixels {
+                let idx = idx as u32;
+                match arena.spans[first_span..].last_mut() {
+                    Some(span) if span.start + span.len == idx => span.len += 1,
+                    _ => arena.spans.push(Span { start: idx, len: 1 }),
+                }
+                arena.coverage.push(
+                    (Float::min(coverage, S::ONE) * S::TWO_FIVE_FIVE)
+                        .round()
+                        .to_u8()
+                        .unwrap_or(u8::MAX),
+                );
+            }
+        }
+        arena.offsets.push((arena.spans.len(), arena.coverage.len()));
+        Some(arena)
+    }
+
+    /// Bytes an arena would take for `segments`, estimated from their geometry without rasterizing
+    /// them.
+    pub fn estimate<'a>(segments: impl Iterator<Item = &'a Segment<S>>, thread_width: S) -> usize {
+        let mut bytes = OFFSET_BYTES + size_of::<[S; 256]>();
+        for segment in segments {
+            let (dx, dy) = (segment.end.x - segment.start.x, segment.end.y - segment.start.y);
+            let (major, minor) = if dy.abs() > dx.abs() { (dy, dx) } else { (dx, dy) };
+            let slope = if major != S::ZERO { minor / major } else { S::ZERO };
+            let thickness = thread_width * num_traits::Float::sqrt(S::ONE + slope * slope);
+            let columns = major.abs().to_usize().unwrap_or(0) + 2;
+            let rows = thickness.ceil().to_usize().unwrap_or(0) + 1;
+            // Steep lines get one span per image row, shallow ones one span per row they cross.
+            let spans = if dy.abs() > dx.abs() {
+                columns
+            } else {
+                (minor.abs() + thickness).to_usize().unwrap_or(0) + 2
+            };
+            bytes += OFFSET_BYTES + columns * rows + spans * SPAN_BYTES;
+        }
+        bytes
+    }
+
+    /// Bytes taken by the arena.
+    pub fn memory(&self) -> usize {
+        self.offsets.len() * OFFSET_BYTES
+            + self.spans.len() * SPAN_BYTES
+            + self.coverage.len()
+            + size_of::<[S; 256]>()
+    }
+
+    /// Pixel indices covered by the baked segment at `idx`, with their coverage.
+    pub fn pixels(&self, idx: usize) -> impl Iterator<Item = (usize, S)> + '_ {
+        let (first_span, mut pixel) = self.offsets[idx];
+        let (last_span, _) = self.offsets[idx + 1];
+        self.spans[first_span..last_span].iter().flat_map(move |span| {
+            let first = pixel;
+            pixel += span.len as usize;
+            self.coverage[first..pixel]
+                .iter()
+                .zip(span.start as usize..)
+                .map(|(&coverage, idx)| (idx, self.levels[coverage as usize]))
+        })
+    }
+}
+
+/// Pixels of a line either read from a [`PixelArena`] or rasterized on the fly.
+pub(crate) enum Coverage<B, L> {
+    Baked(B),
+    Lazy(L),
+}
+
+impl<S, B: Iterator<Item = (usize, S)>, L: Iterator<Item = (usize, S)>> Iterator
+    for Coverage<B, L>
+{
+    type Item = (usize, S);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Coverage::Baked(iter) => iter.next(),
+            Coverage::Lazy(iter) => iter.next(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{geometry::Point, verboser::Silent};
+
+    fn segments() -> Vec<Segment<f32>> {
+        [((1.5, 2.0), (30.0, 7.5)), ((4.0, 1.0), (9.0, 38.0)), ((35.0, 35.0), (2.0, 20.5))]
+            .into_iter()
+            .map(|(start, end)| Segment {
+                start: Point { x: start.0, y: start.1 },
+                end: Point { x: end.0, y: end.1 },
+            })
+            .collect()
+    }
+
+    #[test]
+    fn baked_pixels_match_the_rasterized_ones() {
+        let grid = Grid::new(40, 40);
+        let segments = segments();
+        let arena = PixelArena::bake(segments.iter(), &grid, 1.5, &Silent).unwrap();
+        for (idx, segment) in segments.iter().enumerate() {
+            let mut expected: Vec<_> = grid.get_pixel_coverage_in_segment(segment, 1.5).collect();
+            expected.sort_unstable_by_key(|&(idx, _)| idx);
+            let baked: Vec<_> = arena.pixels(idx).collect();
+            assert_eq!(baked.len(), expected.len());
+            for ((idx, coverage), (expected_idx, expected_coverage)) in baked.into_iter().zip(expected) {
+                assert_eq!(idx, expected_idx);
+                assert!((coverage - expected_coverage.min(1.0)).abs() <= 0.5 / 255.0 + 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn estimate_bounds_the_memory() {
+        let grid = Grid::new(40, 40);
+        let segments = segments();
+        let arena = PixelArena::bake(segments.iter(), &grid, 1.5, &Silent).unwrap();
+        assert!(PixelArena::estimate(segments.iter(), 1.5) >= arena.memory());
+    }
+
+    #[test]
+    fn rejects_grids_past_u32_indices() {
+        let grid = Grid::new(1 << 16, 1 << 16);
+        assert!(PixelArena::<f32>::bake(segments().iter(), &grid, 1.0, &Silent).is_none());
+    }
+}
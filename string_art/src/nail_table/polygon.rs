@@ -0,0 +1,259 @@
+use num_traits::{AsPrimitive, ConstOne, ConstZero, Float as _};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    geometry::Point,
+    grid::Grid,
+    nails,
+    verboser::{self, Message},
+    Float,
+};
+
+use super::NailTable;
+
+/// Nail placement around the corners of a polygonal frame.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Corners {
+    /// Nails are evenly spaced along the whole perimeter, ignoring where the corners are.
+    #[default]
+    Continuous,
+    /// Every corner gets a nail and the remaining ones are spread along the sides.
+    /// Falls back to `Continuous` when there are fewer nails than corners.
+    Nailed,
+    /// Corners are left empty and the nails of each side are centered on it.
+    Skipped,
+}
+
+struct Edge<S> {
+    start: Point<S>,
+    direction: Point<S>,
+    length: S,
+    normal: S,
+}
+
+impl<N: nails::Handle> NailTable<N> {
+    pub fn rectangle<B: nails::Builder<Scalar: Float, Handle = N, Nail = N::Nail>>(
+        grid: Grid,
+        nail_builder: B,
+        nail_count: usize,
+        corners: Corners,
+        verboser: &mut impl verboser::Verboser,
+    ) -> Result<Self, PolygonError>
+    where
+        usize: AsPrimitive<B::Scalar>,
+    {
+        let width = grid.width.as_();
+        let height = grid.height.as_();
+        let zero = B::Scalar::ZERO;
+        Self::polygon(
+            nail_builder,
+            [
+                Point { x: zero, y: zero },
+                Point { x: width, y: zero },
+                Point {
+                    x: width,
+                    y: height,
+                },
+                Point { x: zero, y: height },
+            ],
+            nail_count,
+            corners,
+            verboser,
+        )
+    }
+
+    pub fn regular_polygon<B: nails::Builder<Scalar: Float, Handle = N, Nail = N::Nail>>(
+        grid: Grid,
+        nail_builder: B,
+        sides: usize,
+        rotation: B::Scalar,
+        nail_count: usize,
+        corners: Corners,
+        verboser: &mut impl verboser::Verboser,
+    ) -> Result<Self, PolygonError>
+    where
+        usize: AsPrimitive<B::Scalar>,
+    {
+        if sides < 3 {
+            return Err(PolygonError);
+        }
+        let vertices: Vec<_> = (0..sides)
+            .map(|i| {
+                let theta: B::Scalar =
+                    rotation + B::Scalar::TWO * B::Scalar::PI * i.as_() / sides.as_();
+                Point {
+                    x: grid.width.as_() * (B::Scalar::ONE + theta.cos()),
+                    y: grid.height.as_() * (B::Scalar::ONE + theta.sin()),
+                } * B::Scalar::HALF
+            })
+            .collect();
+        Self::polygon(nail_builder, vertices, nail_count, corners, verboser)
+    }
+
+    pub fn polygon<B: nails::Builder<Scalar: Float, Handle = N, Nail = N::Nail>>(
+        nail_builder: B,
+        vertices: impl IntoIterator<Item = Point<B::Scalar>>,
+        nail_count: usize,
+        corners: Corners,
+        verboser: &mut impl verboser::Verboser,
+    ) -> Result<Self, PolygonError>
+    where
+        usize: AsPrimitive<B::Scalar>,
+    {
+        let mut vertices: Vec<Point<B::Scalar>> = vertices.into_iter().collect();
+        vertices.dedup_by(|a, b| a.aprox_eq(b));
+        while vertices.len() > 1 && vertices[0].aprox_eq(&vertices[vertices.len() - 1]) {
+            vertices.pop();
+        }
+        if vertices.len() < 3 {
+            return Err(PolygonError);
+        }
+
+        let edges = edges_of(&vertices);
+        let positions = match corners {
+            Corners::Nailed if nail_count >= edges.len() => nailed(&edges, nail_count),
+            Corners::Skipped => skipped(&edges, nail_count),
+            _ => continuous(&edges, nail_count),
+        };
+
+        let nails = positions
+            .into_iter()
+            .enumerate()
+            .map(|(i, (position, rotation))| {
+                verboser.verbose(Message::CreatingNail(i));
+                nail_builder.build_nail(position, rotation)
+            })
+            .collect();
+        verboser.verbose(Message::CreatingNail(nail_count));
+        Ok(Self {
+            nails,
+            handle: nail_builder.build_handle(),
+            frame_len: nail_count,
+        })
+    }
+}
+
+fn edges_of<S: Float>(vertices: &[Point<S>]) -> Vec<Edge<S>> {
+    let next = |i: usize| vertices[(i + 1) % vertices.len()];
+    // Normals point outwards regardless of the winding, matching the `theta` of `NailTable::ellipse`.
+    let area = (0..vertices.len()).fold(S::ZERO, |acc, i| {
+        let (a, b) = (vertices[i], next(i));
+        acc + a.x * b.y - b.x * a.y
+    });
+    let offset = if area >= S::ZERO {
+        -S::PI * S::HALF
+    } else {
+        S::PI * S::HALF
+    };
+    vertices
+        .iter()
+        .enumerate()
+        .map(|(i, &start)| {
+            let delta = next(i) - start;
+            let length = start.distance(&next(i));
+            Edge {
+                start,
+                direction: delta / length,
+                length,
+                normal: delta.y.atan2(delta.x) + offset,
+            }
+        })
+        .collect()
+}
+
+fn continuous<S: Float>(edges: &[Edge<S>], nail_count: usize) -> Vec<(Point<S>, S)>
+where
+    usize: AsPrimitive<S>,
+{
+    let perimeter = edges.iter().fold(S::ZERO, |acc, edge| acc + edge.length);
+    let step = perimeter / nail_count.as_();
+    let mut edge_idx = 0;
+    let mut offset = S::ZERO;
+    (0..nail_count)
+        .map(|i| {
+            let distance = step * i.as_();
+            while edge_idx + 1 < edges.len() && distance >= offset + edges[edge_idx].length {
+                offset += edges[edge_idx].length;
+                edge_idx += 1;
+            }
+            let edge = &edges[edge_idx];
+            (
+                edge.start + edge.direction * (distance - offset),
+                edge.normal,
+            )
+        })
+        .collect()
+}
+
+fn nailed<S: Float>(edges: &[Edge<S>], nail_count: usize) -> Vec<(Point<S>, S)>
+where
+    usize: AsPrimitive<S>,
+{
+    let counts = apportion(edges, nail_count - edges.len());
+    let mut positions = Vec::with_capacity(nail_count);
+    for (i, (edge, count)) in edges.iter().zip(counts).enumerate() {
+        let prev = edges[(i + edges.len() - 1) % edges.len()].normal;
+        positions.push((
+            edge.start,
+            (prev.sin() + edge.normal.sin()).atan2(prev.cos() + edge.normal.cos()),
+        ));
+        let step = edge.length / (count + 1).as_();
+        positions.extend((1..=count).map(|j| {
+            (
+                edge.start + edge.direction * (step * j.as_()),
+                edge.normal,
+            )
+        }));
+    }
+    positions
+}
+
+fn skipped<S: Float>(edges: &[Edge<S>], nail_count: usize) -> Vec<(Point<S>, S)>
+where
+    usize: AsPrimitive<S>,
+{
+    let counts = apportion(edges, nail_count);
+    let mut positions = Vec::with_capacity(nail_count);
+    for (edge, count) in edges.iter().zip(counts) {
+        let step = edge.length / count.as_();
+        positions.extend((0..count).map(|j| {
+            (
+                edge.start + edge.direction * (step * (j.as_() + S::HALF)),
+                edge.normal,
+            )
+        }));
+    }
+    positions
+}
+
+// Splits `total` nails between the edges proportionally to their length (largest remainder method).
+fn apportion<S: Float>(edges: &[Edge<S>], total: usize) -> Vec<usize>
+where
+    usize: AsPrimitive<S>,
+{
+    let perimeter = edges.iter().fold(S::ZERO, |acc, edge| acc + edge.length);
+    let mut counts: Vec<(usize, S)> = edges
+        .iter()
+        .map(|edge| {
+            let exact = total.as_() * edge.length / perimeter;
+            let floor = exact.floor();
+            (floor.to_usize().unwrap_or(0), exact - floor)
+        })
+        .collect();
+    let assigned: usize = counts.iter().map(|(count, _)| count).sum();
+    let mut order: Vec<usize> = (0..counts.len()).collect();
+    order.sort_by(|&a, &b| {
+        counts[b]
+            .1
+            .partial_cmp(&counts[a].1)
+            .unwrap_or(core::cmp::Ordering::Equal)
+    });
+    for idx in order.into_iter().take(total.saturating_sub(assigned)) {
+        counts[idx].0 += 1;
+    }
+    counts.into_iter().map(|(count, _)| count).collect()
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("A polygonal frame needs at least three distinct vertices.")]
+pub struct PolygonError;
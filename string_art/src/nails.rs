@@ -1,10 +1,17 @@
 pub mod circular;
+pub mod hook;
+pub mod point;
+pub mod polygonal;
 
 use crate::Float;
+use num_traits::ConstZero;
 pub use circular::Circular;
+pub use hook::Hook;
+pub use point::{PointNail, SingleLink, SingleLinks};
+pub use polygonal::{Hexagonal, Polygonal, Square};
 use svg::Node;
 
-use crate::geometry::{Point, Segment};
+use crate::geometry::{circle, Point, Segment};
 
 pub trait Builder {
     type Scalar: Float;
@@ -16,6 +23,9 @@ pub trait Builder {
     fn build_handle(self) -> Self::Handle;
 }
 
+/// # Safety
+/// `LEN` must match the number of links yielded by the iterator, and every link must convert into
+/// a distinct `usize` smaller than `LEN`.
 pub unsafe trait Links: IntoIterator<Item = Self::Link> {
     type Link: Copy + Into<usize> + Send + Sync;
 
@@ -43,5 +53,24 @@ pub trait Handle: Copy + Send + Sync {
         prev_link: <Self::Links as IntoIterator>::Item,
     ) -> <Self::Links as IntoIterator>::Item;
 
+    fn position(self, nail: Self::Nail) -> Point<Self::Scalar>;
+
+    /// Length of thread wrapped around `nail` between `arrival` and `departure`, the segments
+    /// reaching and leaving it. Nails are treated as points unless overridden.
+    fn wrap_length(
+        self,
+        _nail: Self::Nail,
+        _arrival: &Segment<Self::Scalar>,
+        _departure: &Segment<Self::Scalar>,
+    ) -> Self::Scalar {
+        Self::Scalar::ZERO
+    }
+
+    /// Sense the thread turns around `nail` when wrapped through `link`, `None` if it turns with
+    /// the thread, as around point nails.
+    fn wrap_direction(self, _nail: Self::Nail, _link: Self::Link) -> Option<circle::Direction> {
+        None
+    }
+
     fn draw_svg(self, nail: Self::Nail) -> impl Into<Box<dyn Node>>;
 }
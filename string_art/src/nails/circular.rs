@@ -67,6 +67,22 @@ impl<T: Float> Handle for Circular<T> {
     fn get_next_link(self, prev_link: Direction) -> Direction {
         prev_link
     }
+
+    fn position(self, nail: Self::Nail) -> Point<T> {
+        nail
+    }
+
+    fn wrap_length(self, nail: Self::Nail, arrival: &Segment<T>, departure: &Segment<T>) -> T {
+        Circle {
+            center: nail,
+            radius: self.0,
+        }
+        .wrap_length(arrival, departure)
+    }
+
+    fn wrap_direction(self, _: Self::Nail, link: Direction) -> Option<circle::Direction> {
+        Some(link.0)
+    }
     
     fn draw_svg(self, nail: Self::Nail) -> impl Into<Box<dyn svg::Node>> {
         svg::node::element::Circle::new()
@@ -98,7 +114,7 @@ impl IntoIterator for CircularLinks {
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
-pub struct Direction(circle::Direction);
+pub struct Direction(pub(crate) circle::Direction);
 
 
 
@@ -133,7 +149,7 @@ impl From<Direction> for usize {
 
 impl Distribution<Direction> for Standard{
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Direction {
-        Direction(unsafe { core::mem::transmute(rng.gen_range::<u8,_>(0..2)) })
+        Direction(unsafe { core::mem::transmute::<u8, circle::Direction>(rng.gen_range::<u8,_>(0..2)) })
     }
 }
 
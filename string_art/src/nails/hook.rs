@@ -0,0 +1,173 @@
+use crate::{
+    geometry::{circle, Circle, Point, Segment},
+    nails::{Builder, Handle, SingleLink, SingleLinks},
+    Float,
+};
+
+/// Hook shaped nail of radius `radius`, the thread can only wrap it in the direction of the
+/// nail, so each nail offers a single link and the direction never shows in the instructions.
+#[derive(Clone, Copy)]
+pub struct Hook<T = f32> {
+    radius: T,
+}
+
+impl<T> Hook<T> {
+    /// Every nail built by this hook is wrapped in `direction`.
+    pub fn uniform(
+        radius: T,
+        direction: circle::Direction,
+    ) -> HookBuilder<T, impl Fn(Point<T>, T) -> circle::Direction + Clone> {
+        Self::with_directions(radius, move |_, _| direction)
+    }
+
+    /// Each nail is wrapped in the direction `directions` gives for its position and rotation.
+    pub fn with_directions<F: Fn(Point<T>, T) -> circle::Direction>(
+        radius: T,
+        directions: F,
+    ) -> HookBuilder<T, F> {
+        HookBuilder {
+            hook: Self { radius },
+            directions,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct HookBuilder<T, F> {
+    hook: Hook<T>,
+    directions: F,
+}
+
+#[derive(Clone, Copy)]
+pub struct HookNail<T> {
+    center: Point<T>,
+    direction: circle::Direction,
+}
+
+impl<T> HookNail<T> {
+    pub fn new(center: Point<T>, direction: circle::Direction) -> Self {
+        Self { center, direction }
+    }
+
+    pub fn direction(&self) -> circle::Direction {
+        self.direction
+    }
+}
+
+impl<T: Float, F: Fn(Point<T>, T) -> circle::Direction> Builder for HookBuilder<T, F> {
+    type Scalar = T;
+    type Handle = Hook<T>;
+    type Nail = HookNail<T>;
+
+    fn build_nail(&self, center: Point<T>, rotation: T) -> Self::Nail {
+        HookNail::new(center, (self.directions)(center, rotation))
+    }
+
+    fn build_handle(self) -> Self::Handle {
+        self.hook
+    }
+}
+
+impl<T: Float> Handle for Hook<T> {
+    type Scalar = T;
+
+    type Nail = HookNail<T>;
+
+    type Links = SingleLinks;
+
+    type Link = SingleLink;
+
+    type Error = Error;
+
+    const LINKS: Self::Links = SingleLinks;
+
+    fn get_segment(
+        self,
+        start: (&Self::Nail, SingleLink),
+        end: (&Self::Nail, SingleLink),
+    ) -> Result<Segment<T>, Self::Error> {
+        Circle {
+            center: start.0.center,
+            radius: self.radius,
+        }
+        .tangent(
+            start.0.direction,
+            Circle {
+                center: end.0.center,
+                radius: self.radius,
+            },
+            end.0.direction,
+        )
+        .ok_or(Error)
+    }
+
+    fn get_next_link(self, prev_link: SingleLink) -> SingleLink {
+        prev_link
+    }
+
+    fn position(self, nail: Self::Nail) -> Point<T> {
+        nail.center
+    }
+
+    fn wrap_length(self, nail: Self::Nail, arrival: &Segment<T>, departure: &Segment<T>) -> T {
+        Circle {
+            center: nail.center,
+            radius: self.radius,
+        }
+        .wrap_length(arrival, departure)
+    }
+
+    fn wrap_direction(self, nail: Self::Nail, _: SingleLink) -> Option<circle::Direction> {
+        Some(nail.direction)
+    }
+
+    fn draw_svg(self, nail: Self::Nail) -> impl Into<Box<dyn svg::Node>> {
+        svg::node::element::Circle::new()
+            .set("cx", nail.center.x)
+            .set("cy", nail.center.y)
+            .set("r", self.radius)
+            .set("fill", "black")
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("The nails are overlapping")]
+pub struct Error;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_nail_keeps_its_direction() {
+        let builder = Hook::with_directions(1.0, |_, rotation: f32| {
+            if rotation < 0.0 {
+                circle::Direction::ClockWise
+            } else {
+                circle::Direction::CounterClockWise
+            }
+        });
+        let start = builder.build_nail(Point { x: 0.0, y: 0.0 }, -1.0);
+        let end = builder.build_nail(Point { x: 10.0, y: 0.0 }, 1.0);
+        assert_eq!(start.direction(), circle::Direction::ClockWise);
+        assert_eq!(end.direction(), circle::Direction::CounterClockWise);
+        let hook = builder.build_handle();
+        let segment = hook.get_segment((&start, SingleLink), (&end, SingleLink)).unwrap();
+        let expected = Circle {
+            center: start.center,
+            radius: 1.0,
+        }
+        .tangent(
+            circle::Direction::ClockWise,
+            Circle {
+                center: end.center,
+                radius: 1.0,
+            },
+            circle::Direction::CounterClockWise,
+        )
+        .unwrap();
+        assert!(segment.start.aprox_eq(&expected.start) && segment.end.aprox_eq(&expected.end));
+        // Crossing between both nails, the thread leaves and reaches them on opposite sides.
+        assert!(segment.start.y * segment.end.y < 0.0);
+    }
+}
@@ -0,0 +1,151 @@
+use std::marker::PhantomData;
+
+use rand::{distributions::Standard, prelude::Distribution, Rng};
+
+use crate::{
+    geometry::{Point, Segment},
+    nails::{Builder, Handle, Links},
+    Float,
+};
+
+/// Zero radius nail, threads go straight from one nail center to the next one.
+#[derive(Clone, Copy)]
+pub struct PointNail<T = f32>(PhantomData<T>);
+
+impl<T> PointNail<T> {
+    // Radius of the dot drawn for each nail in the svg output.
+    const SVG_RADIUS: f32 = 0.5;
+
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T> Default for PointNail<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Float> Builder for PointNail<T> {
+    type Scalar = T;
+    type Handle = Self;
+    type Nail = Point<T>;
+
+    fn build_nail(&self, point: Point<T>, _: T) -> Self::Nail {
+        point
+    }
+
+    fn build_handle(self) -> Self::Handle {
+        self
+    }
+}
+
+impl<T: Float> Handle for PointNail<T> {
+    type Scalar = T;
+
+    type Nail = Point<T>;
+
+    type Links = SingleLinks;
+
+    type Link = SingleLink;
+
+    type Error = Error;
+
+    const LINKS: Self::Links = SingleLinks;
+
+    fn get_segment(
+        self,
+        start: (&Self::Nail, SingleLink),
+        end: (&Self::Nail, SingleLink),
+    ) -> Result<Segment<T>, Self::Error> {
+        if start.0.aprox_eq(end.0) {
+            Err(Error)
+        } else {
+            Ok(Segment::new(*start.0, *end.0))
+        }
+    }
+
+    fn get_next_link(self, prev_link: SingleLink) -> SingleLink {
+        prev_link
+    }
+
+    fn position(self, nail: Self::Nail) -> Point<T> {
+        nail
+    }
+
+    fn draw_svg(self, nail: Self::Nail) -> impl Into<Box<dyn svg::Node>> {
+        svg::node::element::Circle::new()
+            .set("cx", nail.x)
+            .set("cy", nail.y)
+            .set("r", Self::SVG_RADIUS)
+            .set("fill", "black")
+    }
+}
+
+pub struct SingleLinks;
+
+unsafe impl Links for SingleLinks {
+    const LEN: usize = 1;
+
+    type Link = SingleLink;
+}
+
+impl IntoIterator for SingleLinks {
+    type Item = SingleLink;
+
+    type IntoIter = core::array::IntoIter<SingleLink, 1>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        [SingleLink].into_iter()
+    }
+}
+
+/// The only way a thread can go around a nail that allows a single one.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub struct SingleLink;
+
+impl core::fmt::Display for SingleLink {
+    fn fmt(&self, _: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Ok(())
+    }
+}
+
+impl From<SingleLink> for usize {
+    fn from(_: SingleLink) -> Self {
+        0
+    }
+}
+
+impl Distribution<SingleLink> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, _: &mut R) -> SingleLink {
+        SingleLink
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("The nails are overlapping")]
+pub struct Error;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn threads_join_the_centers() {
+        let handle = PointNail::<f32>::new();
+        let start = handle.build_nail(Point { x: 1.0, y: 2.0 }, 0.7);
+        let end = handle.build_nail(Point { x: 5.0, y: -1.0 }, 2.0);
+        let segment = handle.get_segment((&start, SingleLink), (&end, SingleLink)).unwrap();
+        assert!(segment.start.aprox_eq(&start) && segment.end.aprox_eq(&end));
+        assert!(handle.get_segment((&start, SingleLink), (&start, SingleLink)).is_err());
+    }
+
+    #[test]
+    fn offers_a_single_link() {
+        assert_eq!(SingleLinks.into_iter().count(), <SingleLinks as Links>::LEN);
+        assert_eq!(usize::from(SingleLink), 0);
+        assert!(PointNail::<f32>::new().get_next_link(SingleLink) == SingleLink);
+        assert_eq!(SingleLink.to_string(), "");
+    }
+}
@@ -0,0 +1,313 @@
+use num_traits::AsPrimitive;
+
+use crate::{
+    geometry::{circle, Point, Segment},
+    nails::{
+        circular::{CircularLinks, Direction},
+        Builder, Handle,
+    },
+    Float,
+};
+
+/// Nail with the cross section of a regular polygon of `SIDES` sides, threads wrap around its
+/// corners. Each nail is rotated so one of its faces looks along the rotation it is built with.
+#[derive(Clone, Copy)]
+pub struct Polygonal<T = f32, const SIDES: usize = 4>(T);
+
+pub type Square<T = f32> = Polygonal<T, 4>;
+pub type Hexagonal<T = f32> = Polygonal<T, 6>;
+
+impl<T, const SIDES: usize> Polygonal<T, SIDES> {
+    /// `radius` is the distance from the center of the nail to its corners.
+    pub fn new(radius: T) -> Self {
+        const { assert!(SIDES >= 3, "a polygonal nail needs at least three sides") };
+        Self(radius)
+    }
+}
+
+impl<T: Float> Square<T> {
+    pub fn with_side(side: T) -> Self {
+        Self::new(side * T::HALF * num_traits::Float::sqrt(T::TWO))
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct PolygonalNail<T, const SIDES: usize> {
+    center: Point<T>,
+    corners: [Point<T>; SIDES],
+}
+
+impl<T: Float, const SIDES: usize> PolygonalNail<T, SIDES> {
+    pub fn center(&self) -> Point<T> {
+        self.center
+    }
+
+    pub fn corners(&self) -> &[Point<T>; SIDES] {
+        &self.corners
+    }
+
+    // Corner farthest along `normal`.
+    fn support(&self, normal: Point<T>) -> Point<T> {
+        let mut best = self.corners[0];
+        let mut best_dot = best.x * normal.x + best.y * normal.y;
+        for &corner in &self.corners[1..] {
+            let dot = corner.x * normal.x + corner.y * normal.y;
+            if dot > best_dot {
+                best = corner;
+                best_dot = dot;
+            }
+        }
+        best
+    }
+
+    // Index of the corner closest to `point`.
+    fn corner_at(&self, point: Point<T>) -> usize {
+        let mut best = 0;
+        for idx in 1..SIDES {
+            if self.corners[idx].sq_distance(&point) < self.corners[best].sq_distance(&point) {
+                best = idx;
+            }
+        }
+        best
+    }
+}
+
+impl<T: Float, const SIDES: usize> Builder for Polygonal<T, SIDES>
+where
+    usize: AsPrimitive<T>,
+{
+    type Scalar = T;
+    type Handle = Self;
+    type Nail = PolygonalNail<T, SIDES>;
+
+    fn build_nail(&self, center: Point<T>, rotation: T) -> Self::Nail {
+        let step = T::TWO * T::PI / SIDES.as_();
+        let first = rotation + step * T::HALF;
+        PolygonalNail {
+            center,
+            corners: core::array::from_fn(|idx| {
+                let angle = first + step * idx.as_();
+                center
+                    + Point {
+                        x: angle.cos(),
+                        y: angle.sin(),
+                    } * self.0
+            }),
+        }
+    }
+
+    fn build_handle(self) -> Self::Handle {
+        self
+    }
+}
+
+// Pair of corners whose line leaves the rest of both nails the least on its wrong side.
+fn closest_tangent<T: Float, const SIDES: usize>(
+    (start, start_side): (&PolygonalNail<T, SIDES>, T),
+    (end, end_side): (&PolygonalNail<T, SIDES>, T),
+) -> Segment<T> {
+    let mut best = (T::INFINITY, Segment::new(start.center, end.center));
+    for &from in &start.corners {
+        for &to in &end.corners {
+            let segment = Segment::new(from, to);
+            let normal = normal(&segment);
+            let length = from.distance(&to);
+            let behind = |corners: &[Point<T>; SIDES], anchor: Point<T>, side: T| {
+                corners.iter().fold(T::ZERO, |worst, &corner| {
+                    let offset = corner - anchor;
+                    worst.max((offset.x * normal.x + offset.y * normal.y) * side / length)
+                })
+            };
+            let overlap = behind(&start.corners, from, start_side)
+                .max(behind(&end.corners, to, end_side));
+            if overlap < best.0 {
+                best = (overlap, segment);
+            }
+        }
+    }
+    best.1
+}
+
+impl<T: Float, const SIDES: usize> Handle for Polygonal<T, SIDES> {
+    type Scalar = T;
+
+    type Nail = PolygonalNail<T, SIDES>;
+
+    type Links = CircularLinks;
+
+    type Link = Direction;
+
+    type Error = Error;
+
+    const LINKS: Self::Links = CircularLinks;
+
+    fn get_segment(
+        self,
+        start: (&Self::Nail, Direction),
+        end: (&Self::Nail, Direction),
+    ) -> Result<Segment<T>, Self::Error> {
+        let diameter = T::TWO * self.0;
+        if start.0.center.sq_distance(&end.0.center) <= diameter * diameter {
+            return Err(Error);
+        }
+        // Same sides as `Circle::tangent`, clockwise touches the left of the line from start to end.
+        let side = |direction: Direction| match direction.0 {
+            circle::Direction::ClockWise => T::ONE,
+            circle::Direction::CounterClockWise => -T::ONE,
+        };
+        let start_side = side(start.1);
+        let end_side = if start.1 == end.1 {
+            start_side
+        } else {
+            -start_side
+        };
+        // Walks the support corners, every step rotates the line towards the tangent so it
+        // settles within a few corners. Nearly parallel faces could keep it swapping between
+        // corners, so past a turn around the nail every pair of corners is tried instead.
+        let mut segment = Segment::new(start.0.center, end.0.center);
+        for _ in 0..SIDES + 2 {
+            let next = Segment::new(
+                start.0.support(normal(&segment) * start_side),
+                end.0.support(normal(&segment) * end_side),
+            );
+            if next.start.aprox_eq(&segment.start) && next.end.aprox_eq(&segment.end) {
+                return Ok(segment);
+            }
+            segment = next;
+        }
+        Ok(closest_tangent((start.0, start_side), (end.0, end_side)))
+    }
+
+    fn get_next_link(self, prev_link: Direction) -> Direction {
+        prev_link
+    }
+
+    fn position(self, nail: Self::Nail) -> Point<T> {
+        nail.center
+    }
+
+    fn wrap_length(self, nail: Self::Nail, arrival: &Segment<T>, departure: &Segment<T>) -> T {
+        // Ends of a segment as the corner touching the nail and the other one.
+        let ends = |segment: &Segment<T>| {
+            if segment.start.sq_distance(&nail.center) <= segment.end.sq_distance(&nail.center) {
+                (segment.start, segment.end)
+            } else {
+                (segment.end, segment.start)
+            }
+        };
+        let (arrival_point, arrival_from) = ends(arrival);
+        let arrival_corner = nail.corner_at(arrival_point);
+        let departure_corner = nail.corner_at(ends(departure).0);
+        let radius = arrival_point - nail.center;
+        let along = arrival_point - arrival_from;
+        // Corners go counter clockwise, the thread keeps the sense it arrives with.
+        let corners = if radius.x * along.y - radius.y * along.x < T::ZERO {
+            (arrival_corner + SIDES - departure_corner) % SIDES
+        } else {
+            (departure_corner + SIDES - arrival_corner) % SIDES
+        };
+        let side = nail.corners[0].distance(&nail.corners[1]);
+        side * T::from(corners).unwrap_or(T::ZERO)
+    }
+
+    fn wrap_direction(self, _: Self::Nail, link: Direction) -> Option<circle::Direction> {
+        Some(link.0)
+    }
+
+    fn draw_svg(self, nail: Self::Nail) -> impl Into<Box<dyn svg::Node>> {
+        svg::node::element::Polygon::new()
+            .set(
+                "points",
+                nail.corners
+                    .iter()
+                    .map(|corner| format!("{},{}", corner.x, corner.y))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            )
+            .set("fill", "black")
+    }
+}
+
+// Normal to the left of the line from the start of `segment` to its end.
+fn normal<T: Float>(segment: &Segment<T>) -> Point<T> {
+    Point {
+        x: segment.start.y - segment.end.y,
+        y: segment.end.x - segment.start.x,
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("The nails are overlapping")]
+pub struct Error;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [Direction; 2] = [
+        Direction(circle::Direction::ClockWise),
+        Direction(circle::Direction::CounterClockWise),
+    ];
+
+    // Whether every corner of `nail` lies on the `side` of the line through `segment`.
+    fn behind<const SIDES: usize>(
+        segment: &Segment<f32>,
+        nail: &PolygonalNail<f32, SIDES>,
+        side: f32,
+    ) -> bool {
+        let normal = normal(segment);
+        let length = segment.start.distance(&segment.end);
+        nail.corners.iter().all(|corner| {
+            let offset = *corner - segment.start;
+            (offset.x * normal.x + offset.y * normal.y) * side / length <= 1e-4
+        })
+    }
+
+    fn check_tangents<const SIDES: usize>() {
+        let handle = Polygonal::<f32, SIDES>::new(1.0);
+        for rotation in [0.0, 0.3, 1.0, 2.5] {
+            let start = handle.build_nail(Point { x: 0.0, y: 0.0 }, rotation);
+            let end = handle.build_nail(Point { x: 7.0, y: 3.0 }, -rotation);
+            for start_link in ALL {
+                for end_link in ALL {
+                    let segment = handle
+                        .get_segment((&start, start_link), (&end, end_link))
+                        .unwrap();
+                    let start_side = if start_link == ALL[0] { 1.0 } else { -1.0 };
+                    let end_side = if start_link == end_link { start_side } else { -start_side };
+                    assert!(start.corners.iter().any(|corner| corner.aprox_eq(&segment.start)));
+                    assert!(end.corners.iter().any(|corner| corner.aprox_eq(&segment.end)));
+                    assert!(behind(&segment, &start, start_side));
+                    assert!(behind(&segment, &end, end_side));
+                    let closest = closest_tangent((&start, start_side), (&end, end_side));
+                    assert!(behind(&closest, &start, start_side));
+                    assert!(behind(&closest, &end, end_side));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn square_tangents() {
+        check_tangents::<4>();
+    }
+
+    #[test]
+    fn hexagonal_tangents() {
+        check_tangents::<6>();
+    }
+
+    #[test]
+    fn wraps_around_corners() {
+        let handle = Square::new(1.0);
+        let side = f32::sqrt(2.0);
+        let [left, middle, right] =
+            [0.0, 10.0, 20.0].map(|x| handle.build_nail(Point { x, y: 0.0 }, 0.3));
+        let clockwise = ALL[0];
+        let arrival = handle.get_segment((&left, clockwise), (&middle, clockwise)).unwrap();
+        let on = handle.get_segment((&middle, clockwise), (&right, clockwise)).unwrap();
+        let back = handle.get_segment((&middle, clockwise), (&left, clockwise)).unwrap();
+        assert!(handle.wrap_length(middle, &arrival, &on) < 1e-4);
+        assert!((handle.wrap_length(middle, &arrival, &back) - 2.0 * side).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,360 @@
+use image::RgbImage;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    geometry::{Point, Segment},
+    grid::Grid,
+};
+
+pub const MM_PER_INCH: f32 = 25.4;
+
+/// Most samples a raster is rendered with, its pixels times the supersampling along both axes.
+/// Each one takes 12 bytes while rendering.
+pub const MAX_RASTER_SAMPLES: usize = 1 << 28;
+
+/// Size of a rasterized result.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum RasterSize {
+    /// Pixels along the longest side of the image.
+    Pixels(u32),
+    /// Printed at `dpi` dots per inch, with the longest side of the image spanning the `diameter`
+    /// of the board in millimeters.
+    Physical { dpi: f32, diameter: f32 },
+}
+
+impl RasterSize {
+    /// Pixels along the longest side of the image.
+    pub fn longest_side(&self) -> u32 {
+        match *self {
+            RasterSize::Pixels(pixels) => pixels.max(1),
+            RasterSize::Physical { dpi, diameter } => {
+                ((dpi * diameter / MM_PER_INCH).round() as u32).max(1)
+            }
+        }
+    }
+
+    /// Dots per inch to store in the metadata of the output, if it has a physical size.
+    pub fn dpi(&self) -> Option<f32> {
+        match *self {
+            RasterSize::Pixels(_) => None,
+            RasterSize::Physical { dpi, .. } => Some(dpi),
+        }
+    }
+}
+
+/// Width of the threads.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ThreadWidth {
+    /// Pixels of the image, like the stroke width of the SVG.
+    Pixels(f32),
+    /// Millimeters on a board whose diameter spans the longest side of the image.
+    Millimeters { width: f32, board_diameter: f32 },
+}
+
+impl ThreadWidth {
+    /// Width in pixels of an image whose longest side is `longest_side`.
+    pub fn pixels(&self, longest_side: f32) -> f32 {
+        match *self {
+            ThreadWidth::Pixels(width) => width,
+            ThreadWidth::Millimeters {
+                width,
+                board_diameter,
+            } => width * longest_side / board_diameter,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NailStyle {
+    /// Radius in pixels of the image.
+    pub radius: f32,
+    pub color: (u8, u8, u8),
+}
+
+/// How a result is rasterized, following the model of the SVG output: threads are strokes of a
+/// fixed width drawn over the nails in the order of the document. Threads are composited in linear
+/// light, so translucent threads build up like real ones do.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RasterStyle {
+    pub size: RasterSize,
+    pub thread_width: ThreadWidth,
+    /// Opacity of every thread, the SVG draws them fully opaque.
+    pub opacity: f32,
+    pub background: (u8, u8, u8),
+    /// Nails are not drawn when `None`.
+    pub nails: Option<NailStyle>,
+    /// Samples per pixel along each axis.
+    pub supersampling: u32,
+}
+
+impl RasterStyle {
+    pub fn new(size: RasterSize, thread_width: ThreadWidth) -> Self {
+        Self {
+            size,
+            thread_width,
+            opacity: 1.0,
+            background: (255, 255, 255),
+            nails: None,
+            supersampling: 1,
+        }
+    }
+
+    pub fn with_opacity(self, opacity: f32) -> Self {
+        Self { opacity, ..self }
+    }
+
+    pub fn with_background(self, background: (u8, u8, u8)) -> Self {
+        Self { background, ..self }
+    }
+
+    pub fn with_nails(self, nails: NailStyle) -> Self {
+        Self {
+            nails: Some(nails),
+            ..self
+        }
+    }
+
+    pub fn with_supersampling(self, supersampling: u32) -> Self {
+        Self {
+            supersampling,
+            ..self
+        }
+    }
+}
+
+/// Antialiased painter of threads and nails, given in pixels of the image. Colors are kept in
+/// linear light at the supersampled resolution.
+pub(crate) struct Canvas {
+    // Output grid, the canvas is `samples` times larger along each axis.
+    grid: Grid,
+    samples: usize,
+    canvas: Grid,
+    scale: f32,
+    thread_width: f32,
+    opacity: f32,
+    pixels: Vec<[f32; 3]>,
+}
+
+impl Canvas {
+    pub(crate) fn new(image: Grid, style: &RasterStyle) -> Result<Self, RasterError> {
+        let longest = image.width.max(image.height).max(1) as f32;
+        let scale = style.size.longest_side() as f32 / longest;
+        let grid = Grid::new(
+            ((image.height as f32 * scale).round() as usize).max(1),
+            ((image.width as f32 * scale).round() as usize).max(1),
+        );
+        let samples = style.supersampling.max(1) as usize;
+        let too_big = RasterError {
+            width: grid.width,
+            height: grid.height,
+            supersampling: samples,
+        };
+        let len = (grid.width * grid.height)
+            .checked_mul(samples * samples)
+            .filter(|&len| len <= MAX_RASTER_SAMPLES)
+            .ok_or(too_big)?;
+        let canvas = Grid::new(grid.height * samples, grid.width * samples);
+        let scale = scale * samples as f32;
+        Ok(Self {
+            grid,
+            samples,
+            canvas,
+            scale,
+            thread_width: style.thread_width.pixels(longest) * scale,
+            opacity: style.opacity,
+            pixels: vec![linear(style.background); len],
+        })
+    }
+
+    pub(crate) fn nail(&mut self, center: Point<f32>, style: &NailStyle) {
+        let center = center * self.scale;
+        let radius = style.radius * self.scale;
+        // The border is smoothed half a pixel outwards.
+        let reach = radius + 0.5;
+        let rows = (center.y - reach).floor().max(0.0) as usize
+            ..((center.y + reach).ceil().max(0.0) as usize).min(self.canvas.height);
+        let columns = (center.x - reach).floor().max(0.0) as usize
+            ..((center.x + reach).ceil().max(0.0) as usize).min(self.canvas.width);
+        let color = linear(style.color);
+        for y in rows {
+            for x in columns.clone() {
+                let (dx, dy) = (x as f32 + 0.5 - center.x, y as f32 + 0.5 - center.y);
+                // Fraction of the pixel inside the disk, approximated across its border.
+                let coverage = (radius - (dx * dx + dy * dy).sqrt() + 0.5).clamp(0.0, 1.0);
+                self.blend(y * self.canvas.width + x, color, coverage);
+            }
+        }
+    }
+
+    pub(crate) fn thread(&mut self, segment: Segment<f32>, color: (u8, u8, u8)) {
+        let canvas = self.canvas;
+        let color = linear(color);
+        for (idx, coverage) in
+            canvas.get_pixel_coverage_in_segment(&(segment * self.scale), self.thread_width)
+        {
+            self.blend(idx, color, coverage.min(1.0) * self.opacity);
+        }
+    }
+
+    fn blend(&mut self, idx: usize, color: [f32; 3], alpha: f32) {
+        for (channel, value) in self.pixels[idx].iter_mut().zip(color) {
+            *channel += (value - *channel) * alpha;
+        }
+    }
+}
+
+impl From<Canvas> for RgbImage {
+    fn from(canvas: Canvas) -> Self {
+        let samples = canvas.samples;
+        let weight = 1.0 / (samples * samples) as f32;
+        let mut buffer = Vec::with_capacity(canvas.grid.width * canvas.grid.height * 3);
+        for y in 0..canvas.grid.height {
+            for x in 0..canvas.grid.width {
+                let mut sum = [0.0; 3];
+                for row in y * samples..(y + 1) * samples {
+                    let first = row * canvas.canvas.width + x * samples;
+                    for pixel in &canvas.pixels[first..first + samples] {
+                        for (sum, channel) in sum.iter_mut().zip(pixel) {
+                            *sum += channel;
+                        }
+                    }
+                }
+                buffer.extend(sum.map(|channel| encode(channel * weight)));
+            }
+        }
+        RgbImage::from_vec(canvas.grid.width as u32, canvas.grid.height as u32, buffer)
+            .expect("the buffer matches the grid")
+    }
+}
+
+fn linear(color: (u8, u8, u8)) -> [f32; 3] {
+    [color.0, color.1, color.2].map(|channel| {
+        let channel = channel as f32 / 255.0;
+        if channel <= 0.04045 {
+            channel / 12.92
+        } else {
+            ((channel + 0.055) / 1.055).powf(2.4)
+        }
+    })
+}
+
+fn encode(channel: f32) -> u8 {
+    let channel = channel.clamp(0.0, 1.0);
+    let channel = if channel <= 0.0031308 {
+        channel * 12.92
+    } else {
+        1.055 * channel.powf(1.0 / 2.4) - 0.055
+    };
+    (channel * 255.0).round() as u8
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error(
+    "A raster of {width}x{height} pixels with {supersampling}x supersampling is too big to render."
+)]
+pub struct RasterError {
+    pub width: usize,
+    pub height: usize,
+    pub supersampling: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn horizontal(y: f32) -> Segment<f32> {
+        Segment {
+            start: Point { x: 0.0, y },
+            end: Point { x: 10.0, y },
+        }
+    }
+
+    #[test]
+    fn physical_sizes_follow_the_dpi() {
+        let size = RasterSize::Physical {
+            dpi: 254.0,
+            diameter: 100.0,
+        };
+        assert_eq!(size.longest_side(), 1000);
+        assert_eq!(size.dpi(), Some(254.0));
+        assert_eq!(RasterSize::Pixels(0).longest_side(), 1);
+        assert_eq!(RasterSize::Pixels(640).dpi(), None);
+    }
+
+    #[test]
+    fn thread_widths_scale_with_the_board() {
+        assert_eq!(ThreadWidth::Pixels(1.5).pixels(1000.0), 1.5);
+        let width = ThreadWidth::Millimeters {
+            width: 0.5,
+            board_diameter: 250.0,
+        };
+        assert_eq!(width.pixels(1000.0), 2.0);
+        assert_eq!(width.pixels(500.0), 1.0);
+    }
+
+    #[test]
+    fn canvas_keeps_the_aspect_ratio() {
+        let style = RasterStyle::new(RasterSize::Pixels(200), ThreadWidth::Pixels(1.0));
+        let image: RgbImage = Canvas::new(Grid::new(50, 100), &style).unwrap().into();
+        assert_eq!(image.dimensions(), (200, 100));
+    }
+
+    #[test]
+    fn threads_blend_in_linear_light() {
+        let style = RasterStyle::new(RasterSize::Pixels(20), ThreadWidth::Pixels(1.0))
+            .with_opacity(0.5)
+            .with_background((255, 255, 255));
+        let mut canvas = Canvas::new(Grid::new(10, 10), &style).unwrap();
+        canvas.thread(horizontal(5.0), (0, 0, 0));
+        let image: RgbImage = canvas.into();
+        // Twice the resolution, the thread is two pixels wide. Half the light is left, which
+        // encodes brighter than half the sRGB value.
+        for y in [9, 10] {
+            assert_eq!(image.get_pixel(10, y).0, [188; 3]);
+        }
+        assert_eq!(image.get_pixel(10, 8).0, [255; 3]);
+        assert_eq!(image.get_pixel(10, 11).0, [255; 3]);
+    }
+
+    #[test]
+    fn supersampling_averages_down_to_the_size() {
+        let style = RasterStyle::new(RasterSize::Pixels(20), ThreadWidth::Pixels(1.0))
+            .with_background((255, 255, 255))
+            .with_supersampling(4);
+        let mut canvas = Canvas::new(Grid::new(10, 10), &style).unwrap();
+        // Half a pixel of the output is covered on each side of the thread.
+        canvas.thread(horizontal(5.25), (0, 0, 0));
+        let image: RgbImage = canvas.into();
+        assert_eq!(image.dimensions(), (20, 20));
+        assert_eq!(image.get_pixel(10, 10).0, [0; 3]);
+        assert_eq!(image.get_pixel(10, 9).0, [188; 3]);
+        assert_eq!(image.get_pixel(10, 11).0, [188; 3]);
+        assert_eq!(image.get_pixel(10, 8).0, [255; 3]);
+    }
+
+    #[test]
+    fn refuses_rasters_too_big_to_render() {
+        let style = RasterStyle::new(RasterSize::Pixels(1 << 14), ThreadWidth::Pixels(1.0))
+            .with_supersampling(2);
+        let err = Canvas::new(Grid::new(10, 10), &style).err().unwrap();
+        assert_eq!((err.width, err.height, err.supersampling), (1 << 14, 1 << 14, 2));
+        let style = style.with_supersampling(1);
+        assert!(Canvas::new(Grid::new(10, 10), &style).is_ok());
+    }
+
+    #[test]
+    fn nails_fill_their_disk() {
+        let nail = NailStyle {
+            radius: 2.0,
+            color: (200, 0, 0),
+        };
+        let style = RasterStyle::new(RasterSize::Pixels(10), ThreadWidth::Pixels(1.0))
+            .with_background((0, 0, 255))
+            .with_nails(nail);
+        let mut canvas = Canvas::new(Grid::new(10, 10), &style).unwrap();
+        canvas.nail(Point { x: 5.0, y: 5.0 }, &nail);
+        let image: RgbImage = canvas.into();
+        assert_eq!(image.get_pixel(5, 5).0, [200, 0, 0]);
+        assert_eq!(image.get_pixel(0, 0).0, [0, 0, 255]);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,164 @@
+use std::{collections::VecDeque, fmt};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Float;
+
+/// Optional criteria ending the computation before the line selector runs out of lines. Every
+/// criterion is disabled by default.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StopCriteria<S> {
+    /// Stops once the best line has a lower weight.
+    pub min_weight: Option<S>,
+    /// Stops once the error stops improving.
+    pub plateau: Option<Plateau<S>>,
+    /// Total length of the threads in pixels of the image, wraps around the nails included. The
+    /// line that would exceed it is not drawn.
+    pub length_budget: Option<S>,
+}
+
+/// Plateau of the error estimate: the sum of the weights left in every color map.
+#[derive(Clone, Copy, Debug)]
+pub struct Plateau<S> {
+    /// Steps the improvement is measured over.
+    pub window: usize,
+    /// Fraction of the initial error the error must decrease by over `window` steps to keep
+    /// going.
+    pub min_improvement: S,
+}
+
+impl<S> StopCriteria<S> {
+    pub fn new() -> Self {
+        Self {
+            min_weight: None,
+            plateau: None,
+            length_budget: None,
+        }
+    }
+
+    pub fn with_min_weight(self, min_weight: S) -> Self {
+        Self {
+            min_weight: Some(min_weight),
+            ..self
+        }
+    }
+
+    pub fn with_plateau(self, window: usize, min_improvement: S) -> Self {
+        Self {
+            plateau: Some(Plateau {
+                window,
+                min_improvement,
+            }),
+            ..self
+        }
+    }
+
+    pub fn with_length_budget(self, length: S) -> Self {
+        Self {
+            length_budget: Some(length),
+            ..self
+        }
+    }
+}
+
+/// Why the computation ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StopReason {
+    /// The line selector ran out of lines.
+    Exhausted,
+    Cancelled,
+    /// The best line was below [`StopCriteria::min_weight`].
+    MinWeight,
+    /// The error improved less than [`Plateau::min_improvement`].
+    Plateau,
+    /// The next line would have exceeded [`StopCriteria::length_budget`].
+    LengthBudget,
+}
+
+impl fmt::Display for StopReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            StopReason::Exhausted => "every thread was drawn",
+            StopReason::Cancelled => "the computation was cancelled",
+            StopReason::MinWeight => "the best line fell below the minimum weight",
+            StopReason::Plateau => "the error stopped improving",
+            StopReason::LengthBudget => "the thread length budget was spent",
+        })
+    }
+}
+
+// Rolling error of the last steps, for `Plateau`. Checkpoints keep it, so a restarted
+// computation plateaus where the original one would have.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct ErrorWindow<S> {
+    initial: S,
+    error: S,
+    history: VecDeque<S>,
+}
+
+impl<S: Float> ErrorWindow<S> {
+    pub(crate) fn new(error: S) -> Self {
+        Self {
+            initial: error,
+            error,
+            history: VecDeque::from([error]),
+        }
+    }
+
+    /// Lowers the error by `improvement` at the end of a step, `true` once it plateaus. The
+    /// history is only kept under a `plateau`, and trimmed to its window.
+    pub(crate) fn step(&mut self, improvement: S, plateau: Option<&Plateau<S>>) -> bool {
+        self.error -= improvement;
+        self.history.push_back(self.error);
+        let Some(plateau) = plateau else {
+            self.history.drain(..self.history.len() - 1);
+            return false;
+        };
+        let window = plateau.window.max(1);
+        if self.history.len() <= window {
+            return false;
+        }
+        self.history.drain(..self.history.len() - window - 1);
+        let start = self.history.pop_front().unwrap_or(self.error);
+        self.initial <= S::ZERO || (start - self.error) / self.initial < plateau.min_improvement
+    }
+
+    /// Converts every error, `None` if any fails to.
+    pub(crate) fn map<T>(&self, mut f: impl FnMut(S) -> Option<T>) -> Option<ErrorWindow<T>> {
+        Some(ErrorWindow {
+            initial: f(self.initial)?,
+            error: f(self.error)?,
+            history: self.history.iter().map(|&error| f(error)).collect::<Option<_>>()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plateaus_once_the_window_improves_too_little() {
+        let plateau = Plateau {
+            window: 2,
+            min_improvement: 0.1,
+        };
+        let mut errors = ErrorWindow::new(10.0);
+        // 40% and then 21% of the initial error over the last two steps.
+        assert!(!errors.step(2.0, Some(&plateau)));
+        assert!(!errors.step(2.0, Some(&plateau)));
+        assert!(!errors.step(0.1, Some(&plateau)));
+        // 2% only.
+        assert!(errors.step(0.1, Some(&plateau)));
+    }
+
+    #[test]
+    fn plateaus_after_the_window_without_any_error() {
+        let plateau = Plateau {
+            window: 1,
+            min_improvement: 0.1,
+        };
+        let mut errors = ErrorWindow::new(0.0);
+        assert!(errors.step(0.0, Some(&plateau)));
+    }
+}
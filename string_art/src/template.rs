@@ -0,0 +1,683 @@
+use std::{fmt::Write as _, io::Write as _};
+
+use num_traits::ToPrimitive;
+use svg::Node;
+
+use crate::{geometry::Point, nails, Board, Grid, NailTable, MM_PER_INCH};
+
+/// Blank space around the drawing of every page, holding the scale bar and the page label.
+const MARGIN: f64 = 15.0;
+/// Width of every stroke.
+const STROKE: f64 = 0.2;
+/// Smallest radius a nail is marked with, so point nails stay visible.
+const MIN_NAIL_RADIUS: f64 = 0.5;
+/// Gap between the mark of a nail and its number.
+const LABEL_GAP: f64 = 1.0;
+const MIN_FONT_SIZE: f64 = 1.0;
+const MAX_FONT_SIZE: f64 = 3.0;
+/// Font size of the page label and the scale bar.
+const MARGIN_FONT_SIZE: f64 = 3.0;
+/// Advance of a digit of Helvetica in ems, used to lay out the text of both outputs.
+const DIGIT_WIDTH: f64 = 0.556;
+const SCALE_BAR: f64 = 50.0;
+const SCALE_TICK: f64 = 10.0;
+const REGISTRATION_RADIUS: f64 = 3.0;
+
+/// Sheet a template is tiled across.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Paper {
+    A4,
+    Letter,
+}
+
+impl Paper {
+    /// Width and height in millimeters of the sheet in portrait.
+    pub fn size(self) -> (f64, f64) {
+        match self {
+            Paper::A4 => (210.0, 297.0),
+            Paper::Letter => (215.9, 279.4),
+        }
+    }
+}
+
+/// Printable template of the nail positions at the real size of the board, every nail marked and
+/// numbered with its index in the [`NailTable`], as used by the instructions.
+#[derive(Clone, Debug)]
+pub struct Template {
+    /// Nail positions in millimeters from the top left corner of the image.
+    nails: Vec<Point<f64>>,
+    nail_radius: f64,
+    width: f64,
+    height: f64,
+    font_size: f64,
+    /// Space around the image holding the numbers of the nails.
+    border: f64,
+    paper: Option<Paper>,
+}
+
+// Page of a template, with the part of the drawing it shows.
+struct Sheet {
+    width: f64,
+    height: f64,
+    // Top left corner of the drawing shown, in template coordinates.
+    origin: Point<f64>,
+    // Size of the drawing shown, `None` for the whole drawing.
+    window: Option<(f64, f64)>,
+    label: String,
+}
+
+impl Template {
+    /// Template of `table`, laid out on `grid`, at the size of `board`.
+    pub fn new<N: nails::Handle>(table: &NailTable<N>, board: &Board, grid: &Grid) -> Self {
+        let scale = 1.0 / board.pixels_per_millimeter(grid) as f64;
+        let handle = table.handle();
+        let nails: Vec<Point<f64>> = table
+            .nails()
+            .iter()
+            .map(|&nail| {
+                let position = handle.position(nail);
+                Point {
+                    x: position.x.to_f64().unwrap_or_default() * scale,
+                    y: position.y.to_f64().unwrap_or_default() * scale,
+                }
+            })
+            .collect();
+        let (width, height) = board.size(grid);
+        let nail_radius = (board.nail_diameter as f64 * 0.5).max(MIN_NAIL_RADIUS);
+
+        // Numbers read along the radius, so they only have to fit between neighbouring nails.
+        let spacing = nails
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, a)| nails[..idx].iter().map(move |b| a.distance(b)))
+            .fold(f64::INFINITY, f64::min);
+        let font_size = (spacing * 0.8).clamp(MIN_FONT_SIZE, MAX_FONT_SIZE);
+        let digits = nails.len().saturating_sub(1).max(1).ilog10() as f64 + 1.0;
+        Self {
+            nails,
+            nail_radius,
+            width: width as f64,
+            height: height as f64,
+            font_size,
+            border: nail_radius + LABEL_GAP + digits * DIGIT_WIDTH * font_size + LABEL_GAP,
+            paper: None,
+        }
+    }
+
+    /// Tiles the template across sheets of `paper` instead of a single page fitting it.
+    pub fn with_paper(self, paper: Paper) -> Self {
+        Self {
+            paper: Some(paper),
+            ..self
+        }
+    }
+
+    /// Number of pages of the template.
+    pub fn page_count(&self) -> usize {
+        self.sheets().len()
+    }
+
+    /// Every page as an SVG document sized in millimeters.
+    pub fn svg_pages(&self) -> Vec<svg::Document> {
+        self.sheets()
+            .iter()
+            .map(|sheet| {
+                let mut painter = SvgPainter::new(sheet);
+                self.draw(sheet, &mut painter);
+                painter.finish()
+            })
+            .collect()
+    }
+
+    /// Every page in a single PDF document.
+    pub fn pdf(&self) -> Vec<u8> {
+        let pages = self
+            .sheets()
+            .iter()
+            .map(|sheet| {
+                let mut painter = PdfPainter::new(sheet);
+                self.draw(sheet, &mut painter);
+                (sheet.width, sheet.height, painter.content)
+            })
+            .collect::<Vec<_>>();
+        write_pdf(&pages)
+    }
+
+    /// Size of the drawing: the image and the numbers around it.
+    fn drawing_size(&self) -> (f64, f64) {
+        (
+            self.width + 2.0 * self.border,
+            self.height + 2.0 * self.border,
+        )
+    }
+
+    fn sheets(&self) -> Vec<Sheet> {
+        let (width, height) = self.drawing_size();
+        let corner = Point {
+            x: -self.border,
+            y: -self.border,
+        };
+        let Some(paper) = self.paper else {
+            return vec![Sheet {
+                width: width + 2.0 * MARGIN,
+                height: height + 2.0 * MARGIN,
+                origin: corner,
+                window: None,
+                label: format!(
+                    "{} nails, {:.1} x {:.1} mm",
+                    self.nails.len(),
+                    self.width,
+                    self.height
+                ),
+            }];
+        };
+
+        // The orientation needing fewer sheets, portrait on ties.
+        let (short, long) = paper.size();
+        let tiles = |sheet_width: f64, sheet_height: f64| {
+            let columns = (width / (sheet_width - 2.0 * MARGIN)).ceil().max(1.0) as usize;
+            let rows = (height / (sheet_height - 2.0 * MARGIN)).ceil().max(1.0) as usize;
+            (sheet_width, sheet_height, columns, rows)
+        };
+        let portrait = tiles(short, long);
+        let landscape = tiles(long, short);
+        let (sheet_width, sheet_height, columns, rows) =
+            if landscape.2 * landscape.3 < portrait.2 * portrait.3 {
+                landscape
+            } else {
+                portrait
+            };
+        let window = (sheet_width - 2.0 * MARGIN, sheet_height - 2.0 * MARGIN);
+        (0..rows)
+            .flat_map(|row| {
+                (0..columns).map(move |column| Sheet {
+                    width: sheet_width,
+                    height: sheet_height,
+                    origin: Point {
+                        x: corner.x + column as f64 * window.0,
+                        y: corner.y + row as f64 * window.1,
+                    },
+                    window: Some(window),
+                    label: format!(
+                        "Row {} of {}, column {} of {}",
+                        row + 1,
+                        rows,
+                        column + 1,
+                        columns
+                    ),
+                })
+            })
+            .collect()
+    }
+
+    fn draw(&self, sheet: &Sheet, painter: &mut impl Painter) {
+        let (window_width, window_height) = sheet.window.unwrap_or(self.drawing_size());
+        let offset = Point {
+            x: MARGIN - sheet.origin.x,
+            y: MARGIN - sheet.origin.y,
+        };
+
+        painter.begin_window((MARGIN, MARGIN, window_width, window_height), offset);
+        self.draw_drawing(sheet.origin, (window_width, window_height), painter);
+        painter.end_window();
+
+        // Registration marks on the corners of the window, shared with the neighbouring sheets.
+        for (x, y) in [
+            (MARGIN, MARGIN),
+            (MARGIN + window_width, MARGIN),
+            (MARGIN, MARGIN + window_height),
+            (MARGIN + window_width, MARGIN + window_height),
+        ] {
+            let center = Point { x, y };
+            painter.circle(center, REGISTRATION_RADIUS);
+            cross(painter, center, REGISTRATION_RADIUS * 5.0 / 3.0);
+        }
+
+        painter.text(
+            Point {
+                x: MARGIN + 2.0 * REGISTRATION_RADIUS,
+                y: MARGIN - 2.0 * REGISTRATION_RADIUS,
+            },
+            MARGIN_FONT_SIZE,
+            0.0,
+            &sheet.label,
+        );
+
+        // Scale bar, 50 mm long once printed at the right size.
+        let y = sheet.height - MARGIN + 2.0 * REGISTRATION_RADIUS;
+        let x = MARGIN + 2.0 * REGISTRATION_RADIUS;
+        painter.line(Point { x, y }, Point { x: x + SCALE_BAR, y });
+        let ticks = (SCALE_BAR / SCALE_TICK) as usize;
+        for tick in 0..=ticks {
+            let x = x + tick as f64 * SCALE_TICK;
+            let length = if tick == 0 || tick == ticks { 2.0 } else { 1.0 };
+            painter.line(Point { x, y: y - length }, Point { x, y });
+        }
+        painter.text(
+            Point {
+                x: x + SCALE_BAR + 2.0,
+                y: y + 0.35 * MARGIN_FONT_SIZE,
+            },
+            MARGIN_FONT_SIZE,
+            0.0,
+            &format!("{} mm", SCALE_BAR),
+        );
+    }
+
+    /// The image bounds, its center, and the nails with their numbers shown through the window
+    /// at `origin`, in template coordinates.
+    fn draw_drawing(&self, origin: Point<f64>, window: (f64, f64), painter: &mut impl Painter) {
+        let (width, height) = (self.width, self.height);
+        let corners = [
+            Point { x: 0.0, y: 0.0 },
+            Point { x: width, y: 0.0 },
+            Point {
+                x: width,
+                y: height,
+            },
+            Point { x: 0.0, y: height },
+        ];
+        for idx in 0..corners.len() {
+            painter.line(corners[idx], corners[(idx + 1) % corners.len()]);
+        }
+        let center = Point {
+            x: width * 0.5,
+            y: height * 0.5,
+        };
+        cross(painter, center, 5.0);
+
+        // Nails whose mark or number may reach the window, the rest would be clipped anyway.
+        let reach = self.border;
+        let visible = |nail: Point<f64>| {
+            nail.x + reach >= origin.x
+                && nail.y + reach >= origin.y
+                && nail.x - reach <= origin.x + window.0
+                && nail.y - reach <= origin.y + window.1
+        };
+        for (idx, &nail) in self.nails.iter().enumerate() {
+            if !visible(nail) {
+                continue;
+            }
+            painter.circle(nail, self.nail_radius);
+            cross(painter, nail, self.nail_radius.min(1.0));
+
+            let outward = nail - center;
+            let distance = outward.x.hypot(outward.y);
+            let angle = if distance > f64::EPSILON {
+                outward.y.atan2(outward.x)
+            } else {
+                -std::f64::consts::FRAC_PI_2
+            };
+            let label = idx.to_string();
+            let size = self.font_size;
+            let length = label.len() as f64 * DIGIT_WIDTH * size;
+            let start = self.nail_radius + LABEL_GAP;
+            let (sin, cos) = angle.sin_cos();
+            // Numbers on the left half are turned over to stay upright, so they end at the nail.
+            let (angle, along) = if cos < -f64::EPSILON {
+                (angle + std::f64::consts::PI, start + length)
+            } else {
+                (angle, start)
+            };
+            let (turned_sin, turned_cos) = angle.sin_cos();
+            // Baseline lowered by about half the height of the digits to center them on the ray.
+            let origin = Point {
+                x: nail.x + cos * along - turned_sin * 0.35 * size,
+                y: nail.y + sin * along + turned_cos * 0.35 * size,
+            };
+            painter.text(origin, size, angle.to_degrees(), &label);
+        }
+    }
+}
+
+fn cross(painter: &mut impl Painter, center: Point<f64>, arm: f64) {
+    painter.line(
+        Point {
+            x: center.x - arm,
+            y: center.y,
+        },
+        Point {
+            x: center.x + arm,
+            y: center.y,
+        },
+    );
+    painter.line(
+        Point {
+            x: center.x,
+            y: center.y - arm,
+        },
+        Point {
+            x: center.x,
+            y: center.y + arm,
+        },
+    );
+}
+
+/// Output of a page, in millimeters from its top left corner.
+trait Painter {
+    /// Clips what follows to `clip`, drawing it moved by `offset`.
+    fn begin_window(&mut self, clip: (f64, f64, f64, f64), offset: Point<f64>);
+
+    fn end_window(&mut self);
+
+    fn line(&mut self, from: Point<f64>, to: Point<f64>);
+
+    fn circle(&mut self, center: Point<f64>, radius: f64);
+
+    /// `text` starting at `origin` on its baseline, turned clockwise by `angle` degrees.
+    fn text(&mut self, origin: Point<f64>, size: f64, angle: f64, text: &str);
+}
+
+/// Rounds `millimeters` to micrometers, keeping the documents short.
+fn round(millimeters: f64) -> f64 {
+    (millimeters * 1000.0).round() / 1000.0
+}
+
+struct SvgPainter {
+    document: svg::Document,
+    window: Option<svg::node::element::Group>,
+}
+
+impl SvgPainter {
+    fn new(sheet: &Sheet) -> Self {
+        Self {
+            document: svg::Document::new()
+                .set("width", format!("{}mm", sheet.width))
+                .set("height", format!("{}mm", sheet.height))
+                .set("viewBox", (0.0, 0.0, sheet.width, sheet.height)),
+            window: None,
+        }
+    }
+
+    fn add(&mut self, node: impl Into<Box<dyn svg::Node>>) {
+        match &mut self.window {
+            Some(window) => window.append(node),
+            None => self.document.append(node),
+        }
+    }
+
+    fn finish(self) -> svg::Document {
+        self.document
+    }
+}
+
+impl Painter for SvgPainter {
+    fn begin_window(&mut self, (x, y, width, height): (f64, f64, f64, f64), offset: Point<f64>) {
+        use svg::node::element::{ClipPath, Definitions, Rectangle};
+
+        self.document.append(
+            Definitions::new().add(
+                ClipPath::new().set("id", "window").add(
+                    Rectangle::new()
+                        .set("x", x)
+                        .set("y", y)
+                        .set("width", width)
+                        .set("height", height),
+                ),
+            ),
+        );
+        self.window = Some(
+            svg::node::element::Group::new()
+                .set(
+                    "transform",
+                    format!("translate({} {})", round(offset.x), round(offset.y)),
+                ),
+        );
+    }
+
+    fn end_window(&mut self) {
+        if let Some(window) = self.window.take() {
+            // The clip is set on an untransformed group, so it stays in page coordinates.
+            self.document.append(
+                svg::node::element::Group::new()
+                    .set("clip-path", "url(#window)")
+                    .add(window),
+            );
+        }
+    }
+
+    fn line(&mut self, from: Point<f64>, to: Point<f64>) {
+        self.add(
+            svg::node::element::Line::new()
+                .set("x1", round(from.x))
+                .set("y1", round(from.y))
+                .set("x2", round(to.x))
+                .set("y2", round(to.y))
+                .set("stroke", "black")
+                .set("stroke-width", STROKE),
+        );
+    }
+
+    fn circle(&mut self, center: Point<f64>, radius: f64) {
+        self.add(
+            svg::node::element::Circle::new()
+                .set("cx", round(center.x))
+                .set("cy", round(center.y))
+                .set("r", round(radius))
+                .set("fill", "none")
+                .set("stroke", "black")
+                .set("stroke-width", STROKE),
+        );
+    }
+
+    fn text(&mut self, origin: Point<f64>, size: f64, angle: f64, text: &str) {
+        self.add(
+            svg::node::element::Text::new(text)
+                .set(
+                    "transform",
+                    format!(
+                        "translate({} {}) rotate({})",
+                        round(origin.x),
+                        round(origin.y),
+                        round(angle)
+                    ),
+                )
+                .set("font-family", "Helvetica, Arial, sans-serif")
+                .set("font-size", size),
+        );
+    }
+}
+
+/// Writes the content stream of a page, in millimeters with the y axis pointing down.
+struct PdfPainter {
+    content: String,
+}
+
+impl PdfPainter {
+    fn new(sheet: &Sheet) -> Self {
+        let scale = 72.0 / MM_PER_INCH as f64;
+        let mut content = String::new();
+        let _ = writeln!(
+            content,
+            "{:.6} 0 0 {:.6} 0 {:.3} cm",
+            scale,
+            -scale,
+            sheet.height * scale
+        );
+        let _ = writeln!(content, "{:.3} w", STROKE);
+        Self { content }
+    }
+}
+
+impl Painter for PdfPainter {
+    fn begin_window(&mut self, (x, y, width, height): (f64, f64, f64, f64), offset: Point<f64>) {
+        let _ = writeln!(
+            self.content,
+            "q {:.3} {:.3} {:.3} {:.3} re W n 1 0 0 1 {:.3} {:.3} cm",
+            x, y, width, height, offset.x, offset.y
+        );
+    }
+
+    fn end_window(&mut self) {
+        self.content.push_str("Q\n");
+    }
+
+    fn line(&mut self, from: Point<f64>, to: Point<f64>) {
+        let _ = writeln!(
+            self.content,
+            "{:.3} {:.3} m {:.3} {:.3} l S",
+            from.x, from.y, to.x, to.y
+        );
+    }
+
+    fn circle(&mut self, center: Point<f64>, radius: f64) {
+        // Four cubic curves, one per quadrant.
+        let k = radius * 0.552_284_75;
+        let (x, y) = (center.x, center.y);
+        let _ = writeln!(
+            self.content,
+            "{:.3} {:.3} m \
+{:.3} {:.3} {:.3} {:.3} {:.3} {:.3} c \
+{:.3} {:.3} {:.3} {:.3} {:.3} {:.3} c \
+{:.3} {:.3} {:.3} {:.3} {:.3} {:.3} c \
+{:.3} {:.3} {:.3} {:.3} {:.3} {:.3} c S",
+            x + radius,
+            y,
+            x + radius,
+            y + k,
+            x + k,
+            y + radius,
+            x,
+            y + radius,
+            x - k,
+            y + radius,
+            x - radius,
+            y + k,
+            x - radius,
+            y,
+            x - radius,
+            y - k,
+            x - k,
+            y - radius,
+            x,
+            y - radius,
+            x + k,
+            y - radius,
+            x + radius,
+            y - k,
+            x + radius,
+            y
+        );
+    }
+
+    fn text(&mut self, origin: Point<f64>, size: f64, angle: f64, text: &str) {
+        // The text space is flipped back so glyphs stand upright on the flipped page.
+        let (sin, cos) = angle.to_radians().sin_cos();
+        let escaped = text
+            .replace('\\', "\\\\")
+            .replace('(', "\\(")
+            .replace(')', "\\)");
+        let _ = writeln!(
+            self.content,
+            "BT /F1 {:.3} Tf {:.6} {:.6} {:.6} {:.6} {:.3} {:.3} Tm ({}) Tj ET",
+            size, cos, sin, sin, -cos, origin.x, origin.y, escaped
+        );
+    }
+}
+
+/// Minimal PDF 1.4 document of pages of the given size in millimeters and content streams, all
+/// sharing the Helvetica font.
+fn write_pdf(pages: &[(f64, f64, String)]) -> Vec<u8> {
+    let scale = 72.0 / MM_PER_INCH as f64;
+    // Catalog, page tree and font come first, then every page and its content.
+    let page_id = |idx: usize| 4 + 2 * idx;
+    let mut objects = vec![
+        String::from("<< /Type /Catalog /Pages 2 0 R >>"),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            (0..pages.len())
+                .map(|idx| format!("{} 0 R", page_id(idx)))
+                .collect::<Vec<_>>()
+                .join(" "),
+            pages.len()
+        ),
+        String::from("<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>"),
+    ];
+    for (idx, (width, height, content)) in pages.iter().enumerate() {
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.3} {:.3}] \
+/Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+            width * scale,
+            height * scale,
+            page_id(idx) + 1
+        ));
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{}endstream",
+            content.len(),
+            content
+        ));
+    }
+
+    let mut pdf = Vec::new();
+    let _ = pdf.write_all(b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n");
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (idx, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        let _ = write!(pdf, "{} 0 obj\n{}\nendobj\n", idx + 1, object);
+    }
+    let xref = pdf.len();
+    let _ = write!(pdf, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        let _ = writeln!(pdf, "{:010} 00000 n ", offset);
+    }
+    let _ = write!(
+        pdf,
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref
+    );
+    pdf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{nails::Circular, verboser::Silent};
+
+    fn template(diameter: f32) -> Template {
+        let grid = Grid::new(400, 400);
+        let table = NailTable::ellipse(grid, Circular::new(2.0), 100, &mut Silent);
+        Template::new(&table, &Board::new(diameter, 2.0, 0.5), &grid)
+    }
+
+    #[test]
+    fn keeps_the_size_of_the_board() {
+        let template = template(200.0);
+        assert_eq!((template.width, template.height), (200.0, 200.0));
+        assert!(template.nails.iter().all(|nail| {
+            (0.0..=200.0).contains(&nail.x) && (0.0..=200.0).contains(&nail.y)
+        }));
+        let sheets = template.sheets();
+        assert_eq!(sheets.len(), 1);
+        assert_eq!(sheets[0].width, 200.0 + 2.0 * (template.border + MARGIN));
+    }
+
+    #[test]
+    fn tiles_big_boards_across_sheets() {
+        assert_eq!(template(150.0).with_paper(Paper::A4).page_count(), 1);
+        let template = template(600.0).with_paper(Paper::A4);
+        let sheets = template.sheets();
+        assert!(sheets.len() > 1);
+        assert!(sheets.iter().all(|sheet| {
+            let size = (sheet.width, sheet.height);
+            size == (210.0, 297.0) || size == (297.0, 210.0)
+        }));
+        // The windows cover the whole drawing.
+        let (width, height) = template.drawing_size();
+        let (window_width, window_height) = sheets[0].window.unwrap();
+        let last = &sheets[sheets.len() - 1].origin;
+        assert!(last.x + window_width >= width - template.border);
+        assert!(last.y + window_height >= height - template.border);
+    }
+
+    #[test]
+    fn writes_every_page() {
+        let template = template(600.0).with_paper(Paper::Letter);
+        assert_eq!(template.svg_pages().len(), template.page_count());
+        let pdf = template.pdf();
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(text.starts_with("%PDF-1.4"));
+        assert!(text.ends_with("%%EOF\n"));
+        assert!(text.contains(&format!("/Count {}", template.page_count())));
+    }
+}
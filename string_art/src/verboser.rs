@@ -1,12 +1,90 @@
+use image::RgbImage;
+
+use crate::{Checkpoint, StopReason};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
 pub enum Message {
     CreatingNail(usize),
     Baking,
+    /// Sent before baking the pixels of every segment, which is skipped if `bytes` exceeds `budget`.
+    BakingPixels { bytes: usize, budget: usize },
     Dithering(usize, usize),
     Computing(usize),
+    /// Sent once the computation ends.
+    Stopped(StopReason),
 }
 
 pub trait Verboser {
     fn verbose(&mut self, message: Message);
+
+    /// Polled between computation steps, the computation stops as soon as it returns `true`.
+    fn is_cancelled(&self) -> bool {
+        false
+    }
+
+    /// Steps between two [`Verboser::preview`] calls, previews are disabled when `None`.
+    fn preview_interval(&self) -> Option<usize> {
+        None
+    }
+
+    /// Receives the threads drawn so far, rendered at the resolution of the image.
+    fn preview(&mut self, _image: RgbImage) {}
+
+    /// Steps between two [`Verboser::checkpoint`] calls, checkpoints are disabled when `None`.
+    fn checkpoint_interval(&self) -> Option<usize> {
+        None
+    }
+
+    /// Receives the state of the computation, to restart it with
+    /// [`crate::Algorithm::from_checkpoint`].
+    fn checkpoint(&mut self, _checkpoint: Checkpoint) {}
+}
+
+/// Shared flag to cancel a computation from another thread.
+#[derive(Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl<V: Verboser + ?Sized> Verboser for &mut V {
+    fn verbose(&mut self, message: Message) {
+        (**self).verbose(message)
+    }
+
+    fn is_cancelled(&self) -> bool {
+        (**self).is_cancelled()
+    }
+
+    fn preview_interval(&self) -> Option<usize> {
+        (**self).preview_interval()
+    }
+
+    fn preview(&mut self, image: RgbImage) {
+        (**self).preview(image)
+    }
+
+    fn checkpoint_interval(&self) -> Option<usize> {
+        (**self).checkpoint_interval()
+    }
+
+    fn checkpoint(&mut self, checkpoint: Checkpoint) {
+        (**self).checkpoint(checkpoint)
+    }
 }
 
 pub struct Silent;
@@ -14,3 +92,17 @@ pub struct Silent;
 impl Verboser for Silent {
     fn verbose(&mut self, _: Message) {}
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clones_share_the_cancellation() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        assert!(!token.is_cancelled());
+        clone.cancel();
+        assert!(token.is_cancelled() && clone.is_cancelled());
+    }
+}
@@ -46,6 +46,26 @@ impl ArgLineCount {
         }
     }
 
+    /// Line count of `threads` more threads on a result, all of the color `color_idx` or shared
+    /// between the colors like the automatic count when `None`.
+    pub fn added(&self, color_idx: Option<usize>, threads: usize) -> Self {
+        match color_idx {
+            Some(color_idx) => Self {
+                manual: LineConfig::new(vec![LineGroupConfig::new(vec![LineItemConfig::new(
+                    color_idx, threads,
+                )])]),
+                state: ArgLineCountState::Manual,
+                ..self.clone()
+            },
+            None => {
+                let mut added = self.clone();
+                added.auto.threads = threads;
+                added.state = ArgLineCountState::Auto;
+                added
+            }
+        }
+    }
+
     pub fn form(&mut self, ui: &mut egui::Ui, palette: &[NamedColor]) {
         egui::ComboBox::from_id_salt(self as *mut _)
             .selected_text(&*self)
@@ -67,10 +87,9 @@ impl ArgLineCount {
         ui.horizontal(|ui| {
             ui.add_space(10.0);
             ui.label("Color order:");
-            if palette.len() > 0 && ui.button("+").clicked() {
+            if !palette.is_empty() && ui.button("+").clicked() {
                 groups.push(LineGroupConfig::new(
                     (0..palette.len())
-                        .into_iter()
                         .map(|idx| LineItemConfig::new(idx, 1000))
                         .collect(),
                 ));
@@ -140,9 +159,9 @@ impl ArgLineCount {
         ui.horizontal(|ui| {
             ui.add_space(10.0);
             ui.label("Color order:");
-            if palette.len() > 0 && ui.button("+").clicked() {
+            if !palette.is_empty() && ui.button("+").clicked() {
                 groups.push(AutoLineGroupConfig::new(
-                    (0..palette.len()).into_iter().collect(),
+                    (0..palette.len()).collect(),
                     0.5,
                 ));
             }
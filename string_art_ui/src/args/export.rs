@@ -0,0 +1,218 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter},
+    path::{Path, PathBuf},
+};
+
+use image::{
+    codecs::jpeg::{JpegEncoder, PixelDensity},
+    error::{EncodingError, ImageFormatHint},
+    ImageError, ImageFormat, ImageResult, RgbImage,
+};
+use serde::{Deserialize, Serialize};
+use string_art::{
+    Board, InstructionsError, NailStyle, RasterSize, RasterStyle, Template, ThreadWidth, MM_PER_INCH,
+};
+
+use super::{Args, NailShape};
+use crate::computation::Computation;
+
+/// Extensions of the raster formats offered when saving the image.
+pub const RASTER_EXTENSIONS: [&str; 4] = ["png", "jpg", "jpeg", "webp"];
+
+/// How the image is rasterized when saved as PNG, JPEG or WebP.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExportArgs {
+    pub size: RasterSize,
+    pub background: (u8, u8, u8),
+    pub opacity: f32,
+    pub nails: bool,
+    /// Width of the threads in millimeters on physical sizes, the thread tickness is used
+    /// otherwise.
+    pub thread_width: Option<f32>,
+    /// Samples per pixel along each axis.
+    pub supersampling: u32,
+}
+
+impl Default for ExportArgs {
+    fn default() -> Self {
+        Self {
+            size: RasterSize::Pixels(2000),
+            background: (255, 255, 255),
+            opacity: 1.0,
+            nails: false,
+            thread_width: None,
+            supersampling: 1,
+        }
+    }
+}
+
+impl ExportArgs {
+    pub fn form(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("Image Export").show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Size:").on_hover_text("Size of PNG, JPEG and WebP images.\n\nPhysical sizes \
+print the longest side of the image at the diameter of the board, and store the resolution in PNG and JPEG \
+files.");
+                let physical = RasterSize::Physical {
+                    dpi: 300.0,
+                    diameter: 500.0,
+                };
+                egui::ComboBox::from_id_salt("Export Size")
+                    .selected_text(match self.size {
+                        RasterSize::Pixels(_) => "Pixels",
+                        RasterSize::Physical { .. } => "Physical",
+                    })
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.size, RasterSize::Pixels(2000), "Pixels");
+                        ui.selectable_value(&mut self.size, physical, "Physical");
+                    });
+                match &mut self.size {
+                    RasterSize::Pixels(pixels) => {
+                        ui.add(egui::DragValue::new(pixels).range(1..=20000).suffix(" px"));
+                    }
+                    RasterSize::Physical { dpi, diameter } => {
+                        ui.add(egui::DragValue::new(diameter).range(1.0..=5000.0).suffix(" mm"));
+                        ui.label("at");
+                        ui.add(egui::DragValue::new(dpi).range(1.0..=1200.0).suffix(" dpi"));
+                        ui.label(format!("({} px)", self.size.longest_side()));
+                    }
+                }
+            });
+            if let RasterSize::Physical { .. } = self.size {
+                ui.horizontal(|ui| {
+                    let mut enabled = self.thread_width.is_some();
+                    ui.checkbox(&mut enabled, "Thread Width:").on_hover_text("Width of the real \
+thread, overriding the thread tickness so the image looks like the board at its size.");
+                    match (enabled, &mut self.thread_width) {
+                        (true, Some(width)) => {
+                            ui.add(egui::DragValue::new(width).range(0.01..=10.0).speed(0.01).suffix(" mm"));
+                        }
+                        (true, width @ None) => *width = Some(0.3),
+                        (false, width) => *width = None,
+                    }
+                });
+            }
+            ui.horizontal(|ui| {
+                ui.label("Background:");
+                let mut background = self.background.into();
+                ui.color_edit_button_srgb(&mut background);
+                self.background = background.into();
+                ui.label("Thread Opacity:").on_hover_text("Opacity of every thread, overlapping \
+threads build up darker tones like the real ones do.");
+                ui.add(egui::Slider::new(&mut self.opacity, 0.05..=1.0));
+                ui.checkbox(&mut self.nails, "Draw Nails");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Supersampling:").on_hover_text("Samples per pixel along each side, \
+smoothing thin threads at the cost of time and memory.");
+                ui.add(egui::Slider::new(&mut self.supersampling, 1..=4).suffix("x"));
+            });
+        });
+    }
+
+    /// Style of the images saved, `tickness` and `nail_shape` in pixels of the computed image. The
+    /// thread keeps its physical width on `board`, unless this export sets its own.
+    pub fn style(&self, tickness: f32, nail_shape: NailShape, board: Option<Board>) -> RasterStyle {
+        let thread_width = match (self.size, self.thread_width, board) {
+            (RasterSize::Physical { diameter, .. }, Some(width), _) => ThreadWidth::Millimeters {
+                width,
+                board_diameter: diameter,
+            },
+            (_, _, Some(board)) => board.raster_thread_width(),
+            _ => ThreadWidth::Pixels(tickness),
+        };
+        let style = RasterStyle::new(self.size, thread_width)
+            .with_opacity(self.opacity)
+            .with_background(self.background)
+            .with_supersampling(self.supersampling);
+        if self.nails {
+            style.with_nails(NailStyle {
+                radius: nail_shape.radius(),
+                color: (0, 0, 0),
+            })
+        } else {
+            style
+        }
+    }
+}
+
+/// Saves `image` in the format of the extension of `path`, storing `dpi` in PNG and JPEG files.
+pub fn save_raster(image: &RgbImage, path: &Path, dpi: Option<f32>) -> ImageResult<()> {
+    let format = ImageFormat::from_path(path)?;
+    match (format, dpi) {
+        (ImageFormat::Png, Some(dpi)) => {
+            let file = BufWriter::new(File::create(path)?);
+            let mut encoder = png::Encoder::new(file, image.width(), image.height());
+            encoder.set_color(png::ColorType::Rgb);
+            encoder.set_depth(png::BitDepth::Eight);
+            let pixels_per_meter = (dpi * 1000.0 / MM_PER_INCH).round() as u32;
+            encoder.set_pixel_dims(Some(png::PixelDimensions {
+                xppu: pixels_per_meter,
+                yppu: pixels_per_meter,
+                unit: png::Unit::Meter,
+            }));
+            encoder
+                .write_header()
+                .and_then(|mut writer| writer.write_image_data(image.as_raw()))
+                .map_err(|err| {
+                    ImageError::Encoding(EncodingError::new(ImageFormatHint::Exact(format), err))
+                })
+        }
+        (ImageFormat::Jpeg, dpi) => {
+            let mut encoder =
+                JpegEncoder::new_with_quality(BufWriter::new(File::create(path)?), 95);
+            if let Some(dpi) = dpi {
+                encoder.set_pixel_density(PixelDensity::dpi(dpi.round() as u16));
+            }
+            encoder.encode_image(image)
+        }
+        _ => image.save_with_format(path, format),
+    }
+}
+
+/// Saves `template` as a PDF document if `path` ends in `.pdf`, as SVG documents otherwise, one
+/// per page numbered after the file name when there are several. Returns the files written.
+pub fn save_template(template: &Template, path: &Path) -> io::Result<Vec<PathBuf>> {
+    let is_pdf = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"));
+    if is_pdf {
+        fs::write(path, template.pdf())?;
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let pages = template.svg_pages();
+    if let [page] = pages.as_slice() {
+        svg::save(path, page)?;
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let ext = path.extension().map_or(String::from("svg"), |ext| ext.to_string_lossy().into_owned());
+    pages
+        .iter()
+        .enumerate()
+        .map(|(idx, page)| {
+            let page_path = path.with_file_name(format!("{}-{}.{}", stem, idx + 1, ext));
+            svg::save(&page_path, page).map(|_| page_path)
+        })
+        .collect()
+}
+
+/// Saves the instructions of `computation` in the structured layout of
+/// [`string_art::Instructions`] if `path` ends in `.json` or `.csv`, as text otherwise.
+pub fn save_instructions(
+    computation: &dyn Computation,
+    args: &Args,
+    path: &Path,
+) -> Result<(), InstructionsError> {
+    let is_structured = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json") || ext.eq_ignore_ascii_case("csv"));
+    if is_structured {
+        args.instructions(computation).save(path)
+    } else {
+        fs::write(path, computation.build_instructions()).map_err(InstructionsError::Io)
+    }
+}
@@ -0,0 +1,175 @@
+use egui::{RichText, WidgetText};
+use num_traits::AsPrimitive;
+use rfd::FileDialog;
+use serde::{Deserialize, Serialize};
+use string_art::{geometry::Point, Corners, Float, Normalization};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum FrameShape {
+    Ellipse,
+    Rectangle(Corners),
+    Polygon {
+        sides: usize,
+        rotation: f32,
+        corners: Corners,
+    },
+    Imported {
+        file_path: Option<String>,
+        fit: NailFit,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum NailFit {
+    Fit,
+    Fill,
+    Scale(f32),
+}
+
+impl<S: Float> From<NailFit> for Normalization<S>
+where
+    f32: AsPrimitive<S>,
+{
+    fn from(value: NailFit) -> Self {
+        match value {
+            NailFit::Fit => Normalization::Fit,
+            NailFit::Fill => Normalization::Fill,
+            NailFit::Scale(scale) => Normalization::Explicit {
+                scale: scale.as_(),
+                offset: Point {
+                    x: S::ZERO,
+                    y: S::ZERO,
+                },
+            },
+        }
+    }
+}
+
+impl FrameShape {
+    pub fn form(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Frame Shape:").on_hover_text("Shape of the frame the nails are placed on.");
+            egui::ComboBox::from_id_salt("Frame Shape")
+                .selected_text(&*self)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(self, FrameShape::Ellipse, "Ellipse")
+                        .on_hover_text("Nails are placed on the ellipse inscribed in the image.");
+                    ui.selectable_value(self, FrameShape::Rectangle(Corners::Nailed), "Rectangle")
+                        .on_hover_text("Nails are placed on the border of the image.");
+                    ui.selectable_value(
+                        self,
+                        FrameShape::Polygon {
+                            sides: 6,
+                            rotation: 0.0,
+                            corners: Corners::Nailed,
+                        },
+                        "Polygon",
+                    )
+                    .on_hover_text("Nails are placed on a regular polygon inscribed in the image.");
+                    ui.selectable_value(
+                        self,
+                        FrameShape::Imported {
+                            file_path: None,
+                            fit: NailFit::Fit,
+                        },
+                        "Imported",
+                    )
+                    .on_hover_text("Nail positions are loaded from a CSV or JSON file.\n\nEach row of a CSV \
+file holds the \"x, y\" coordinates of a nail and optionally its rotation in radians. A JSON file holds an array of \
+[x, y], [x, y, rotation] or {\"x\", \"y\", \"rotation\"} items.");
+                });
+        });
+        match self {
+            FrameShape::Ellipse => {}
+            FrameShape::Rectangle(corners) => {
+                Self::corners_form(corners, ui);
+            }
+            FrameShape::Polygon {
+                sides,
+                rotation,
+                corners,
+            } => {
+                ui.horizontal(|ui| {
+                    ui.label("Sides:");
+                    ui.add(egui::Slider::new(sides, 3..=12).clamping(egui::SliderClamping::Never));
+                    if *sides < 3 {
+                        *sides = 3;
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Rotation:");
+                    ui.add(egui::Slider::new(rotation, 0.0..=360.0).suffix("°"));
+                });
+                Self::corners_form(corners, ui);
+            }
+            FrameShape::Imported { file_path, fit } => {
+                ui.horizontal(|ui| {
+                    if ui.button("Select Nail File").clicked() {
+                        if let Some(path) = FileDialog::new()
+                            .add_filter("Nail positions", &["csv", "txt", "json"])
+                            .add_filter("Comma Separated Values", &["csv", "txt"])
+                            .add_filter("JSON", &["json"])
+                            .pick_file()
+                        {
+                            *file_path = Some(path.display().to_string());
+                        }
+                    }
+                    if let Some(path) = file_path {
+                        ui.label(path.as_str());
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Placement:").on_hover_text("How the imported positions are mapped into the image.");
+                    egui::ComboBox::from_id_salt("Nail Fit")
+                        .selected_text(match fit {
+                            NailFit::Fit => "Fit",
+                            NailFit::Fill => "Fill",
+                            NailFit::Scale(_) => "Scale",
+                        })
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(fit, NailFit::Fit, "Fit")
+                                .on_hover_text("Uniformly scales the positions to fit inside the image, keeping them centered.");
+                            ui.selectable_value(fit, NailFit::Fill, "Fill")
+                                .on_hover_text("Stretches each axis independently so the positions span the whole image.");
+                            ui.selectable_value(fit, NailFit::Scale(1.0), "Scale")
+                                .on_hover_text("Multiplies the positions by the given factor, in pixels per file unit.");
+                        });
+                    if let NailFit::Scale(scale) = fit {
+                        ui.add(egui::DragValue::new(scale).speed(0.01).range(0.001..=f32::MAX));
+                    }
+                });
+            }
+        }
+    }
+
+    pub fn is_imported(&self) -> bool {
+        matches!(self, FrameShape::Imported { .. })
+    }
+
+    fn corners_form(corners: &mut Corners, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Corners:").on_hover_text("How nails are placed around the corners of the frame.");
+            egui::ComboBox::from_id_salt("Frame Corners")
+                .selected_text(format!("{:?}", *corners))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(corners, Corners::Nailed, "Nailed")
+                        .on_hover_text("Every corner gets a nail and the remaining ones are spread along the sides.");
+                    ui.selectable_value(corners, Corners::Skipped, "Skipped")
+                        .on_hover_text("Corners are left empty and the nails of each side are centered on it.");
+                    ui.selectable_value(corners, Corners::Continuous, "Continuous")
+                        .on_hover_text("Nails are evenly spaced along the whole perimeter, ignoring where the corners are.");
+                });
+        });
+    }
+}
+
+impl From<&FrameShape> for WidgetText {
+    fn from(value: &FrameShape) -> Self {
+        WidgetText::RichText(RichText::new(match value {
+            FrameShape::Ellipse => "Ellipse",
+            FrameShape::Rectangle(_) => "Rectangle",
+            FrameShape::Polygon { .. } => "Polygon",
+            FrameShape::Imported { .. } => "Imported",
+        }))
+    }
+}
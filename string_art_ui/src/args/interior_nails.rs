@@ -0,0 +1,175 @@
+use egui::{RichText, WidgetText};
+use num_traits::AsPrimitive;
+use serde::{Deserialize, Serialize};
+use string_art::{Float, Interior};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum InteriorNails {
+    None,
+    Grid {
+        spacing: f32,
+    },
+    Poisson {
+        min_distance: f32,
+        seed: u64,
+    },
+    Detail {
+        count: usize,
+        min_distance: f32,
+        seed: u64,
+    },
+}
+
+impl InteriorNails {
+    /// `unit` is the suffix of the distances.
+    pub fn form(&mut self, ui: &mut egui::Ui, unit: &str) {
+        ui.horizontal(|ui| {
+            ui.label("Interior Nails:").on_hover_text("Additional nails placed inside the frame.\n\n\
+Interior nails let the threads reach the details in the middle of the image. When they are enabled the linked \
+nails are chosen with the geometric link rule.");
+            egui::ComboBox::from_id_salt("Interior Nails")
+                .selected_text(*self)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(self, InteriorNails::None, "None")
+                        .on_hover_text("Nails are only placed on the frame.");
+                    ui.selectable_value(self, InteriorNails::Grid { spacing: 50.0 }, "Grid")
+                        .on_hover_text("Nails are placed on a square lattice.");
+                    ui.selectable_value(
+                        self,
+                        InteriorNails::Poisson {
+                            min_distance: 50.0,
+                            seed: 0,
+                        },
+                        "Random",
+                    )
+                    .on_hover_text("Nails are randomly scattered, keeping a minimum distance between them.");
+                    ui.selectable_value(
+                        self,
+                        InteriorNails::Detail {
+                            count: 100,
+                            min_distance: 20.0,
+                            seed: 0,
+                        },
+                        "Detail",
+                    )
+                    .on_hover_text("Nails are concentrated where the image has more edges.");
+                });
+        });
+        match self {
+            InteriorNails::None => {}
+            InteriorNails::Grid { spacing } => {
+                ui.horizontal(|ui| {
+                    ui.label("Spacing:");
+                    ui.add(
+                        egui::Slider::new(spacing, 5.0..=500.0)
+                            .suffix(unit)
+                            .clamping(egui::SliderClamping::Never),
+                    );
+                    if *spacing < 1.0 {
+                        *spacing = 1.0;
+                    }
+                });
+            }
+            InteriorNails::Poisson { min_distance, seed } => {
+                Self::distance_form(min_distance, unit, ui);
+                Self::seed_form(seed, ui);
+            }
+            InteriorNails::Detail {
+                count,
+                min_distance,
+                seed,
+            } => {
+                ui.horizontal(|ui| {
+                    ui.label("Count:");
+                    ui.add(egui::Slider::new(count, 1..=1000).clamping(egui::SliderClamping::Never));
+                });
+                Self::distance_form(min_distance, unit, ui);
+                Self::seed_form(seed, ui);
+            }
+        }
+    }
+
+    /// Same nails with their distances multiplied by `factor`.
+    pub fn scaled(self, factor: f32) -> Self {
+        match self {
+            InteriorNails::None => InteriorNails::None,
+            InteriorNails::Grid { spacing } => InteriorNails::Grid {
+                spacing: spacing * factor,
+            },
+            InteriorNails::Poisson { min_distance, seed } => InteriorNails::Poisson {
+                min_distance: min_distance * factor,
+                seed,
+            },
+            InteriorNails::Detail {
+                count,
+                min_distance,
+                seed,
+            } => InteriorNails::Detail {
+                count,
+                min_distance: min_distance * factor,
+                seed,
+            },
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !matches!(self, InteriorNails::None)
+    }
+
+    pub fn interior<S: Float>(&self) -> Option<Interior<S>>
+    where
+        f32: AsPrimitive<S>,
+    {
+        match *self {
+            InteriorNails::None => None,
+            InteriorNails::Grid { spacing } => Some(Interior::Grid {
+                spacing: spacing.as_(),
+            }),
+            InteriorNails::Poisson { min_distance, seed } => Some(Interior::Poisson {
+                min_distance: min_distance.as_(),
+                seed,
+            }),
+            InteriorNails::Detail {
+                count,
+                min_distance,
+                seed,
+            } => Some(Interior::Detail {
+                count,
+                min_distance: min_distance.as_(),
+                seed,
+            }),
+        }
+    }
+
+    fn distance_form(min_distance: &mut f32, unit: &str, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Spacing:").on_hover_text("Minimum distance between two nails.");
+            ui.add(
+                egui::Slider::new(min_distance, 5.0..=500.0)
+                    .suffix(unit)
+                    .clamping(egui::SliderClamping::Never),
+            );
+            if *min_distance < 1.0 {
+                *min_distance = 1.0;
+            }
+        });
+    }
+
+    fn seed_form(seed: &mut u64, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Seed:").on_hover_text("Seed of the random generator, the same seed always places the same nails.");
+            ui.add(egui::DragValue::new(seed));
+        });
+    }
+}
+
+impl From<InteriorNails> for WidgetText {
+    fn from(value: InteriorNails) -> Self {
+        WidgetText::RichText(RichText::new(match value {
+            InteriorNails::None => "None",
+            InteriorNails::Grid { .. } => "Grid",
+            InteriorNails::Poisson { .. } => "Random",
+            InteriorNails::Detail { .. } => "Detail",
+        }))
+    }
+}
@@ -0,0 +1,135 @@
+use egui::{RichText, WidgetText};
+use num_traits::AsPrimitive;
+use serde::{Deserialize, Serialize};
+use string_art::{Float, GeometricRule, NailDistance};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum LinkRule {
+    Ring,
+    Geometric {
+        min_length: f32,
+        min_edge_angle: f32,
+        distinct_sides: bool,
+    },
+}
+
+impl LinkRule {
+    const GEOMETRIC: Self = LinkRule::Geometric {
+        min_length: 100.0,
+        min_edge_angle: 0.0,
+        distinct_sides: true,
+    };
+
+    /// `ring` tells whether the nails follow the frame, otherwise only geometric rules are offered.
+    /// `unit` is the suffix of the minimum link length.
+    pub fn form(
+        &mut self,
+        ui: &mut egui::Ui,
+        min_nail_distance: &mut usize,
+        nails: usize,
+        ring: bool,
+        unit: &str,
+    ) {
+        if !ring && *self == LinkRule::Ring {
+            *self = Self::GEOMETRIC;
+        }
+        ui.horizontal(|ui| {
+            ui.label("Link Rule:").on_hover_text("How the pairs of nails that can not be linked are chosen.");
+            ui.add_enabled_ui(ring, |ui| {
+                egui::ComboBox::from_id_salt("Link Rule")
+                    .selected_text(*self)
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(self, LinkRule::Ring, "Ring")
+                            .on_hover_text("Nails closer than a number of positions around the frame are not linked.");
+                        ui.selectable_value(self, Self::GEOMETRIC, "Geometric")
+                            .on_hover_text("Pairs are discarded from the nail positions.\n\nThis suits rectangular, \
+polygonal and imported frames, where the index distance around the frame says little about the actual link.");
+                    });
+            })
+            .response
+            .on_disabled_hover_text("Interior nails do not follow the frame, so only geometric rules apply.");
+        });
+        match self {
+            LinkRule::Ring => {
+                ui.horizontal(|ui| {
+                    ui.label("Minimum Nail Distance:").on_hover_text("Number of continuous nails that \
+the algorithm will ignore when computing the next line.\n\nThis prevents the algorithm from drifting excessively along \
+the edges of the image. Additionally, the algorithm does not consider a nail when tracing a thread if it is not the \
+starting or ending nail of the line, so avoiding the tracing of edges is usually a good idea.");
+                    ui.add(egui::Slider::new(
+                        min_nail_distance,
+                        0..=(nails / 2).saturating_sub(1),
+                    ));
+                });
+            }
+            LinkRule::Geometric {
+                min_length,
+                min_edge_angle,
+                distinct_sides,
+            } => {
+                ui.horizontal(|ui| {
+                    ui.label("Minimum Link Length:")
+                        .on_hover_text("Minimum distance between two linked nails.");
+                    ui.add(
+                        egui::Slider::new(min_length, 0.0..=1000.0)
+                            .suffix(unit)
+                            .clamping(egui::SliderClamping::Never),
+                    );
+                    if *min_length < 0.0 {
+                        *min_length = 0.0;
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Minimum Edge Angle:").on_hover_text("Minimum angle between a link and the frame \
+at each of its frame nails.\n\nLinks almost tangent to the frame barely cross the image.");
+                    ui.add(egui::Slider::new(min_edge_angle, 0.0..=90.0).suffix("°"));
+                });
+                ui.checkbox(distinct_sides, "Distinct Sides")
+                    .on_hover_text("Nails lying on the same straight side of the frame are not linked.");
+            }
+        }
+    }
+
+    /// Same rule with its minimum length multiplied by `factor`.
+    pub fn scaled(self, factor: f32) -> Self {
+        match self {
+            LinkRule::Ring => LinkRule::Ring,
+            LinkRule::Geometric {
+                min_length,
+                min_edge_angle,
+                distinct_sides,
+            } => LinkRule::Geometric {
+                min_length: min_length * factor,
+                min_edge_angle,
+                distinct_sides,
+            },
+        }
+    }
+
+    pub fn nail_distance<S: Float>(&self, min_nail_distance: usize) -> NailDistance<S>
+    where
+        f32: AsPrimitive<S>,
+    {
+        match *self {
+            LinkRule::Ring => NailDistance::Ring(min_nail_distance),
+            LinkRule::Geometric {
+                min_length,
+                min_edge_angle,
+                distinct_sides,
+            } => NailDistance::Geometric(GeometricRule {
+                min_distance: min_length.as_(),
+                min_edge_angle: min_edge_angle.to_radians().as_(),
+                distinct_sides,
+            }),
+        }
+    }
+}
+
+impl From<LinkRule> for WidgetText {
+    fn from(value: LinkRule) -> Self {
+        WidgetText::RichText(RichText::new(match value {
+            LinkRule::Ring => "Ring",
+            LinkRule::Geometric { .. } => "Geometric",
+        }))
+    }
+}
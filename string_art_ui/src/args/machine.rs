@@ -0,0 +1,95 @@
+use std::{fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+use string_art::{machine, Machine, Motion};
+
+/// Winding machine the result is exported for.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MachineArgs {
+    /// Degrees added to the ring angle of every nail.
+    pub angle_offset: f32,
+    /// Ring angles grow counterclockwise on the image.
+    pub counterclockwise: bool,
+    /// Degrees the ring turns past each side of a nail.
+    pub wrap_angle: f32,
+    /// Arm positions in millimeters inside and outside the ring of nails.
+    pub arm_inside: f32,
+    pub arm_outside: f32,
+    /// Feed rates of the ring in degrees per minute, between nails and wrapping them.
+    pub travel_feed: f32,
+    pub wrap_feed: f32,
+    /// Feed rate of the arm in millimeters per minute.
+    pub arm_feed: f32,
+}
+
+impl Default for MachineArgs {
+    fn default() -> Self {
+        let machine = Machine::default();
+        Self {
+            angle_offset: machine.angle_offset as f32,
+            counterclockwise: machine.counterclockwise,
+            wrap_angle: machine.wrap_angle as f32,
+            arm_inside: machine.arm_inside as f32,
+            arm_outside: machine.arm_outside as f32,
+            travel_feed: machine.travel_feed as f32,
+            wrap_feed: machine.wrap_feed as f32,
+            arm_feed: machine.arm_feed as f32,
+        }
+    }
+}
+
+impl MachineArgs {
+    pub fn form(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("Winding Machine").show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Ring Offset:").on_hover_text("Ring angle of the first nail, the others \
+follow their angle around the center of the image.");
+                ui.add(egui::DragValue::new(&mut self.angle_offset).range(-360.0..=360.0).speed(0.1).suffix("°"));
+                ui.checkbox(&mut self.counterclockwise, "Counterclockwise").on_hover_text("Ring \
+angles grow counterclockwise on the image.");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Wrap Angle:").on_hover_text("Degrees the ring turns past each side of a \
+nail while the arm wraps it.");
+                ui.add(egui::DragValue::new(&mut self.wrap_angle).range(0.0..=45.0).speed(0.1).suffix("°"));
+            });
+            ui.horizontal(|ui| {
+                ui.label("Arm:").on_hover_text("Positions of the arm inside the ring of nails, \
+where it crosses the board, and outside, where it wraps the nails.");
+                ui.add(egui::DragValue::new(&mut self.arm_inside).speed(0.1).prefix("inside ").suffix(" mm"));
+                ui.add(egui::DragValue::new(&mut self.arm_outside).speed(0.1).prefix("outside ").suffix(" mm"));
+            });
+            ui.horizontal(|ui| {
+                ui.label("Feed Rates:");
+                ui.add(egui::DragValue::new(&mut self.travel_feed).range(1.0..=100000.0).prefix("travel ").suffix(" °/min"));
+                ui.add(egui::DragValue::new(&mut self.wrap_feed).range(1.0..=100000.0).prefix("wrap ").suffix(" °/min"));
+                ui.add(egui::DragValue::new(&mut self.arm_feed).range(1.0..=100000.0).prefix("arm ").suffix(" mm/min"));
+            });
+        });
+    }
+
+    pub fn machine(&self) -> Machine {
+        Machine::new()
+            .with_ring(self.angle_offset as f64, self.counterclockwise)
+            .with_wrap_angle(self.wrap_angle as f64)
+            .with_arm(self.arm_inside as f64, self.arm_outside as f64)
+            .with_feeds(
+                self.travel_feed as f64,
+                self.wrap_feed as f64,
+                self.arm_feed as f64,
+            )
+    }
+}
+
+/// Saves `motions` as a motion command stream if `path` ends in `.txt`, as G-code otherwise.
+pub fn save_motions(motions: &[Motion], path: &Path) -> io::Result<()> {
+    let is_text = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("txt"));
+    if is_text {
+        fs::write(path, machine::commands(motions))
+    } else {
+        fs::write(path, machine::gcode(motions))
+    }
+}
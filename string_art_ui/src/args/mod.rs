@@ -1,29 +1,48 @@
-use crate::synced::{Message, MessageType, SyncedVerboser};
+use crate::computation::{Computation, Progress, Run};
+use image::DynamicImage;
 use num_traits::AsPrimitive;
 use rfd::FileDialog;
-use serde::{Deserialize, Serialize};
-use std::num::NonZero;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{borrow::Cow, num::NonZero, str::FromStr};
 use string_art::{
     auto_line_config::{AutoLineConfig, AutoLineGroupConfig},
     darkness::{Darkness, FlatDarkness, PercentageDarkness},
     line_config::{LineGroupConfig, LineItemConfig},
-    nails::{self, Circular},
-    AsLab, ColorConfig, Float, Image, Lab, NailTable,
+    geometry::{circle, Point},
+    instructions::PaletteColor,
+    nails::{self, Circular, Hexagonal, Hook, PointNail, Square},
+    grid::Grid,
+    AsLab, Board, Checkpoint, ColorConfig, Instructions, RasterSize, Float, Image, Lab,
+    LineCoverage, NailPosition, NailTable, ParseError, InteriorError, PolygonError,
 };
 
-use super::synced::Computation;
-
 mod arg_line_count;
 mod darkness_mode;
+mod export;
+mod frame_shape;
+mod interior_nails;
+mod link_rule;
+mod machine;
 mod nail_shape;
 mod precision;
+mod stop;
 
 pub use arg_line_count::{ArgLineCount, ArgLineCountState};
 pub use darkness_mode::DarknessMode;
-pub use nail_shape::NailShape;
+pub use export::{save_instructions, save_raster, save_template, ExportArgs, RASTER_EXTENSIONS};
+pub use frame_shape::FrameShape;
+pub use interior_nails::InteriorNails;
+pub use link_rule::LinkRule;
+pub use machine::{save_motions, MachineArgs};
+pub use nail_shape::{HookWrap, NailShape};
 pub use precision::Precision;
+pub use stop::StopArgs;
+
+pub const MEGABYTE: usize = 1 << 20;
 
+/// Missing fields take their default value, so hand written configurations may be partial.
 #[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Args {
     /// Input file path.
     #[serde(skip_serializing)]
@@ -31,6 +50,10 @@ pub struct Args {
     pub file_path: Option<String>,
     /// Number of nails surrounding the image.    
     pub nails: NonZero<usize>,
+    /// Shape of the frame the nails are placed on.
+    pub frame_shape: FrameShape,
+    /// Nails placed inside the frame.
+    pub interior_nails: InteriorNails,
     pub nail_shape: NailShape,
     /// Size in pixels of the longest side of the image.
     pub resolution: NonZero<u32>,
@@ -40,11 +63,25 @@ pub struct Args {
     pub darkness_mode: DarknessMode,
     /// Minimum nail count between linked nails.
     pub min_nail_distance: usize,
-    /// Colors of the palete. Acepta sintaxis del tipo "white:FFF", "white:FFFFFF",
-    /// "black:0,0,0" y varios colores comunes identificados directamente por su nombre
+    /// Rule discarding the pairs of nails that can not be linked.
+    pub link_rule: LinkRule,
+    /// Colors of the palete. Parsed from strings like "white:FFF", "white:FFFFFF",
+    /// "black:0,0,0" or a few common colors identified by their name.
     pub palette: Vec<NamedColor>,
     pub tickness: f32,
+    /// Memory budget in megabytes to keep the pixels of every line baked, disabled when `None`.
+    pub pixel_cache: Option<u32>,
     pub line_config: ArgLineCount,
+    /// Diameter in millimeters of the board. Nail sizes, the thread tickness, the interior nail
+    /// spacing, the minimum link length and the length budget are millimeters when set, pixels of
+    /// the image otherwise.
+    pub board_diameter: Option<f32>,
+    /// Settings of the raster images saved.
+    pub export: ExportArgs,
+    /// Criteria ending the computation early.
+    pub stop: StopArgs,
+    /// Winding machine the motions are saved for.
+    pub machine: MachineArgs,
 }
 
 impl Default for Args {
@@ -52,11 +89,14 @@ impl Default for Args {
         Self {
             file_path: None,
             nails: unsafe { NonZero::new_unchecked(512) },
+            frame_shape: FrameShape::Ellipse,
+            interior_nails: InteriorNails::None,
             nail_shape: NailShape::Circular(1.0),
             resolution: unsafe { NonZero::new_unchecked(1000) },
             precision: Precision::Single,
             darkness_mode: DarknessMode::Percentage(0.93),
             min_nail_distance: 20,
+            link_rule: LinkRule::Ring,
             palette: vec![NamedColor {
                 name: String::from("Black"),
                 color: (0, 0, 0),
@@ -69,6 +109,11 @@ impl Default for Args {
                 ArgLineCountState::Auto,
             ),
             tickness: 1.0,
+            pixel_cache: Some(1024),
+            board_diameter: None,
+            export: ExportArgs::default(),
+            stop: StopArgs::default(),
+            machine: MachineArgs::default(),
         }
     }
 }
@@ -102,6 +147,148 @@ impl Args {
         }
     }
 
+    pub fn board_form(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let mut enabled = self.board_diameter.is_some();
+            ui.checkbox(&mut enabled, "Physical Units:").on_hover_text("Diameter of the board, or \
+its longest side, spanning the longest side of the image.\n\nNail sizes, the thread tickness, the spacing of interior \
+nails, the minimum link length and the length budget are then given in millimeters, so the result keeps its physical \
+proportions at any resolution.");
+            // Sizes keep their look at the current resolution when switching units.
+            let pixels_per_millimeter = |diameter: f32| self.resolution.get() as f32 / diameter;
+            match (enabled, self.board_diameter) {
+                (true, Some(mut diameter)) => {
+                    ui.add(egui::DragValue::new(&mut diameter).range(1.0..=5000.0).suffix(" mm"));
+                    self.board_diameter = Some(diameter);
+                }
+                (true, None) => {
+                    let diameter = 500.0;
+                    self.scale_sizes(1.0 / pixels_per_millimeter(diameter));
+                    self.board_diameter = Some(diameter);
+                }
+                (false, Some(diameter)) => {
+                    self.scale_sizes(pixels_per_millimeter(diameter));
+                    self.board_diameter = None;
+                }
+                (false, None) => {}
+            }
+        });
+    }
+
+    /// Physical model of the board, if sizes are given in millimeters.
+    pub fn board(&self) -> Option<Board> {
+        self.board_diameter.map(|diameter| {
+            Board::new(diameter, self.nail_shape.diameter(), self.tickness)
+        })
+    }
+
+    /// Copy of these arguments with every size in pixels of `grid`.
+    pub fn in_pixels(&self, grid: &Grid) -> Cow<'_, Self> {
+        match self.board() {
+            Some(board) => {
+                let mut args = self.clone();
+                args.nail_shape = self.nail_shape.with_radius(board.nail_radius(grid));
+                args.tickness = board.thread_width(grid);
+                args.scale_lengths(board.pixels_per_millimeter(grid));
+                args.board_diameter = None;
+                Cow::Owned(args)
+            }
+            None => Cow::Borrowed(self),
+        }
+    }
+
+    /// Diameter in millimeters physical lengths are measured on: the board, or the image export
+    /// when only it has a physical size.
+    pub fn physical_diameter(&self) -> Option<f32> {
+        match (self.board_diameter, self.export.size) {
+            (Some(diameter), _) | (None, RasterSize::Physical { diameter, .. }) => Some(diameter),
+            (None, RasterSize::Pixels(_)) => None,
+        }
+    }
+
+    /// Board of [`Args::board`], or of the physical export diameter with the sizes in pixels of
+    /// `grid` converted to millimeters. `None` without any physical size.
+    pub fn physical_board(&self, grid: &Grid) -> Option<Board> {
+        self.board().or_else(|| {
+            self.physical_diameter().map(|diameter| {
+                let millimeters = diameter / grid.width.max(grid.height) as f32;
+                Board::new(
+                    diameter,
+                    self.nail_shape.diameter() * millimeters,
+                    self.tickness * millimeters,
+                )
+            })
+        })
+    }
+
+    /// Structured instructions of `computation`, with the physical board and these settings in
+    /// their header.
+    pub fn instructions(&self, computation: &dyn Computation) -> Instructions {
+        let instructions = computation.instructions();
+        let instructions = match self.physical_board(&computation.grid()) {
+            Some(board) => instructions.with_board(board),
+            None => instructions,
+        };
+        match serde_json::to_value(self) {
+            Ok(parameters) => instructions.with_parameters(parameters),
+            Err(_) => instructions,
+        }
+    }
+
+    /// Labels `document`, laid out on `grid`, with the size of the board in millimeters.
+    pub fn label_svg(&self, document: svg::Document, grid: &Grid) -> svg::Document {
+        match self.board() {
+            Some(board) => {
+                let (width, height) = board.size(grid);
+                document
+                    .set("width", format!("{}mm", width))
+                    .set("height", format!("{}mm", height))
+            }
+            None => document,
+        }
+    }
+
+    fn scale_sizes(&mut self, factor: f32) {
+        self.nail_shape = self.nail_shape.scaled(factor);
+        self.tickness *= factor;
+        self.scale_lengths(factor);
+    }
+
+    // Sizes given in millimeters with a board, besides the nails and the thread.
+    fn scale_lengths(&mut self, factor: f32) {
+        self.interior_nails = self.interior_nails.scaled(factor);
+        self.link_rule = self.link_rule.scaled(factor);
+        self.stop = self.stop.scaled(factor);
+    }
+
+    /// Suffix of the sizes shown in the forms, millimeters on a board and pixels otherwise.
+    pub fn length_unit(&self) -> &'static str {
+        match self.board_diameter {
+            Some(_) => " mm",
+            None => " px",
+        }
+    }
+
+    pub fn pixel_cache_form(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let mut enabled = self.pixel_cache.is_some();
+            ui.checkbox(&mut enabled, "Pixel Cache:").on_hover_text("Keeps the pixels under every \
+possible line in memory, so they are not rasterized again on each step.\n\nIt speeds up the computation a lot with \
+many nails, but it is skipped with a warning when its estimated size exceeds the budget.");
+            match (enabled, &mut self.pixel_cache) {
+                (true, Some(budget)) => {
+                    ui.add(
+                        egui::Slider::new(budget, 64..=16384)
+                            .suffix(" MB")
+                            .logarithmic(true),
+                    );
+                }
+                (true, cache @ None) => *cache = Some(1024),
+                (false, cache) => *cache = None,
+            }
+        });
+    }
+
     pub fn image_picker(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
             if ui.button("Select Image").clicked() {
@@ -151,7 +338,7 @@ impl Args {
                 std::cmp::Ordering::Equal => false,
                 std::cmp::Ordering::Greater => true,
             });
-            group.len() > 0
+            !group.is_empty()
         });
         self.line_config.auto.retain_mut(|group| {
             group.retain_mut(|item| match index.cmp(item) {
@@ -162,57 +349,140 @@ impl Args {
                 std::cmp::Ordering::Equal => false,
                 std::cmp::Ordering::Greater => true,
             });
-            group.len() > 0
+            !group.is_empty()
         });
         self.palette.remove(index);
     }
 
     pub fn create_algorithm(
         &self,
-        verboser: &mut SyncedVerboser,
+        verboser: &mut impl Progress,
+    ) -> Result<Box<dyn Computation>, Error> {
+        self.create(Origin::New, verboser)
+    }
+
+    /// Rebuilds the result of `instructions` on the image and nails of these settings, see
+    /// [`string_art::Algorithm::from_instructions`].
+    pub fn replay(
+        &self,
+        instructions: &Instructions,
+        verboser: &mut impl Progress,
     ) -> Result<Box<dyn Computation>, Error> {
-        match self.precision {
-            Precision::Single => self.create_algorithm_with_scalar::<f32>(verboser),
-            Precision::Double => self.create_algorithm_with_scalar::<f64>(verboser),
+        self.create(Origin::Replay(instructions), verboser)
+    }
+
+    /// Restarts the computation `checkpoint` was taken from on these settings, see
+    /// [`string_art::Algorithm::from_checkpoint`].
+    pub fn restart(
+        &self,
+        checkpoint: &Checkpoint,
+        verboser: &mut impl Progress,
+    ) -> Result<Box<dyn Computation>, Error> {
+        self.create(Origin::Restart(checkpoint), verboser)
+    }
+
+    fn create(
+        &self,
+        origin: Origin,
+        verboser: &mut impl Progress,
+    ) -> Result<Box<dyn Computation>, Error> {
+        let file_path = self.file_path.as_ref().ok_or(Error::MissingFilePath)?;
+        verboser.loading_image();
+        let source = image::open(file_path).map_err(Error::Image)?.resize(
+            self.resolution.get(),
+            self.resolution.get(),
+            image::imageops::FilterType::Lanczos3,
+        );
+        verboser.source(&source);
+        let grid = Grid::new(source.height() as usize, source.width() as usize);
+        let args = self.in_pixels(&grid);
+        match args.precision {
+            Precision::Single => {
+                args.create_algorithm_with_scalar::<f32>(source, origin, verboser)
+            }
+            Precision::Double => {
+                args.create_algorithm_with_scalar::<f64>(source, origin, verboser)
+            }
         }
     }
 
-    fn create_algorithm_with_scalar<S: Float>(
+    fn create_algorithm_with_scalar<S: Float + DeserializeOwned>(
         &self,
-        verboser: &mut SyncedVerboser,
+        source: DynamicImage,
+        origin: Origin,
+        verboser: &mut impl Progress,
     ) -> Result<Box<dyn Computation>, Error>
     where
         f32: AsPrimitive<S>,
         usize: AsPrimitive<S>,
         u8: AsPrimitive<S>,
     {
+        let image: Image<S> = source.into();
         match self.darkness_mode {
-            DarknessMode::Flat(flat) => {
-                self.create_algorithm_with_darkness::<S, _>(FlatDarkness(flat.as_()), verboser)
-            }
-            DarknessMode::Percentage(per) => {
-                self.create_algorithm_with_darkness::<S, _>(PercentageDarkness(per.as_()), verboser)
-            }
+            DarknessMode::Flat(flat) => self.create_algorithm_with_darkness::<S, _>(
+                FlatDarkness(flat.as_()),
+                image,
+                origin,
+                verboser,
+            ),
+            DarknessMode::Percentage(per) => self.create_algorithm_with_darkness::<S, _>(
+                PercentageDarkness(per.as_()),
+                image,
+                origin,
+                verboser,
+            ),
         }
     }
 
     fn create_algorithm_with_darkness<S, D>(
         &self,
         darkness: D,
-        verboser: &mut SyncedVerboser,
+        image: Image<S>,
+        origin: Origin,
+        verboser: &mut impl Progress,
     ) -> Result<Box<dyn Computation>, Error>
     where
         usize: AsPrimitive<S>,
         f32: AsPrimitive<S>,
         u8: AsPrimitive<S>,
-        S: Float,
-        D: Darkness<S> + Send + Sync + 'static,
+        S: Float + DeserializeOwned,
+        D: Darkness<S> + Clone + Send + Sync + 'static,
     {
         match self.nail_shape {
             NailShape::Circular(radius) => {
-                self.create_algorithm_with_nails(darkness, Circular::new(radius.as_()), verboser)
+                self.create_algorithm_with_nails(darkness, Circular::new(radius.as_()), image, origin, verboser)
+            }
+            NailShape::Point => {
+                self.create_algorithm_with_nails(darkness, PointNail::new(), image, origin, verboser)
             }
-            NailShape::Point => Err(Error::UnimplementedFeature("Point nail kind")),
+            NailShape::Square(side) => {
+                self.create_algorithm_with_nails(darkness, Square::with_side(side.as_()), image, origin, verboser)
+            }
+            NailShape::Hexagonal(radius) => {
+                self.create_algorithm_with_nails(darkness, Hexagonal::new(radius.as_()), image, origin, verboser)
+            }
+            NailShape::Hook { radius, wrap } => match wrap {
+                HookWrap::Clockwise | HookWrap::CounterClockwise => {
+                    let direction = hook_direction(wrap == HookWrap::Clockwise);
+                    self.create_algorithm_with_nails(darkness, Hook::uniform(radius.as_(), direction), image, origin, verboser)
+                }
+                HookWrap::Outward | HookWrap::Inward => {
+                    let grid = *image.grid();
+                    let center = Point {
+                        x: grid.width.as_(),
+                        y: grid.height.as_(),
+                    } * S::HALF;
+                    let outward = wrap == HookWrap::Outward;
+                    // The hook tip points along the rotation of the nail, or against it when inward.
+                    // Threads wrap clockwise the hooks whose tip looks away from the center.
+                    let hook = Hook::with_directions(radius.as_(), move |position: Point<S>, rotation: S| {
+                        let offset = position - center;
+                        let away = rotation.cos() * offset.x + rotation.sin() * offset.y >= S::ZERO;
+                        hook_direction(away == outward)
+                    });
+                    self.create_algorithm_with_nails(darkness, hook, image, origin, verboser)
+                }
+            },
         }
     }
 
@@ -220,64 +490,141 @@ impl Args {
         &self,
         darkness: D,
         handle: N,
-        verboser: &mut SyncedVerboser,
+        image: Image<N::Scalar>,
+        origin: Origin,
+        verboser: &mut impl Progress,
     ) -> Result<Box<dyn Computation>, Error>
     where
         usize: AsPrimitive<N::Scalar>,
         u8: AsPrimitive<N::Scalar>,
         f32: AsPrimitive<N::Scalar>,
-        D: Darkness<N::Scalar> + Send + Sync + 'static,
+        D: Darkness<N::Scalar> + Clone + Send + Sync + 'static,
         N: nails::Builder<
-            Scalar: Float,
+            Scalar: Float + DeserializeOwned,
             Handle: nails::Handle<Nail: Send + Sync, Link: Default + Send + Sync + ToString>
                         + Send
                         + Sync
                         + 'static,
-        >,
+        > + Clone,
     {
-        match &self.file_path {
-            Some(file_path) => {
-                verboser.verbose(Message::new(MessageType::LoadingImage, "Loading image..."));
-                let image: Image<N::Scalar> = image::open(file_path)
-                    .map_err(|err| Error::ImageError(err))?
-                    .resize(
-                        self.resolution.get(),
-                        self.resolution.get(),
-                        image::imageops::FilterType::Lanczos3,
-                    )
-                    .into();
-                let table = NailTable::ellipse(*image.grid(), handle, self.nails.get(), verboser);
-                match string_art::Algorithm::new(
-                    table,
-                    self.min_nail_distance,
-                    &image,
-                    self.palette.iter().map(|color| {
-                        ColorConfig::new(
-                            color.name.clone(),
-                            color.color.into(),
-                            0,
-                            Default::default(),
-                        )
-                    }),
-                    darkness,
-                    &self.line_config,
-                    verboser,
-                ) {
-                    Ok(algorithm) => Ok(Box::new(algorithm)),
-                    Err(err) => Err(Error::AlgorithmError(Box::new(err))),
-                }
+        let grid = *image.grid();
+        let nails = self.nails.get();
+        let builder = handle.clone();
+        let mut table = match &self.frame_shape {
+            FrameShape::Ellipse => NailTable::ellipse(grid, builder, nails, verboser),
+            &FrameShape::Rectangle(corners) => {
+                NailTable::rectangle(grid, builder, nails, corners, verboser)
+                    .map_err(Error::Frame)?
+            }
+            &FrameShape::Polygon {
+                sides,
+                rotation,
+                corners,
+            } => NailTable::regular_polygon(
+                grid,
+                builder,
+                sides,
+                rotation.to_radians().as_(),
+                nails,
+                corners,
+                verboser,
+            )
+            .map_err(Error::Frame)?,
+            FrameShape::Imported { file_path, fit } => {
+                let positions =
+                    NailPosition::read(file_path.as_ref().ok_or(Error::MissingNailFile)?)
+                        .map_err(Error::NailFile)?;
+                NailTable::from_positions(grid, builder, &positions, (*fit).into(), verboser)
+                    .map_err(|err| Error::Import(Box::new(err)))?
             }
-            None => Err(Error::MissingFilePath),
+        };
+        if let Some(interior) = self.interior_nails.interior() {
+            table
+                .add_interior(&handle, &image, interior, verboser)
+                .map_err(Error::Interior)?;
+        }
+        let min_nail_distance = self.link_rule.nail_distance(self.min_nail_distance);
+        let coverage = match self.pixel_cache {
+            Some(budget) => LineCoverage::new(self.tickness.as_())
+                .with_pixel_budget(budget as usize * MEGABYTE),
+            None => LineCoverage::new(self.tickness.as_()),
+        };
+        let palette = self.palette.iter().map(|color| {
+            ColorConfig::new(color.name.clone(), color.color, 0, Default::default())
+        });
+        let algorithm = match origin {
+            Origin::New => string_art::Algorithm::new(
+                table,
+                min_nail_distance,
+                &image,
+                palette,
+                darkness.clone(),
+                coverage,
+                &self.line_config,
+                self.stop.criteria(),
+                verboser,
+            ),
+            Origin::Replay(instructions) => string_art::Algorithm::from_instructions(
+                table,
+                min_nail_distance,
+                &image,
+                instructions,
+                darkness.clone(),
+                coverage,
+                verboser,
+            ),
+            Origin::Restart(checkpoint) => string_art::Algorithm::from_checkpoint(
+                table,
+                min_nail_distance,
+                &image,
+                palette,
+                darkness.clone(),
+                coverage,
+                &self.line_config,
+                self.stop.criteria(),
+                checkpoint,
+                verboser,
+            ),
+        };
+        match algorithm {
+            Ok(algorithm) => Ok(Box::new(Run::new(algorithm, image, darkness))),
+            Err(err) => Err(err.into()),
         }
     }
 }
 
+// Instructions are reversed to the build process, so the wrapping is swapped too.
+fn hook_direction(clockwise: bool) -> circle::Direction {
+    if clockwise {
+        circle::Direction::CounterClockWise
+    } else {
+        circle::Direction::ClockWise
+    }
+}
+
+// What the algorithm is built from besides the settings.
+#[derive(Clone, Copy)]
+enum Origin<'a> {
+    New,
+    Replay(&'a Instructions),
+    Restart(&'a Checkpoint),
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NamedColor {
     pub name: String,
     pub color: (u8, u8, u8),
 }
 
+impl From<&PaletteColor> for NamedColor {
+    fn from(color: &PaletteColor) -> Self {
+        NamedColor {
+            name: color.name.clone(),
+            color: (color.rgb[0], color.rgb[1], color.rgb[2]),
+        }
+    }
+}
+
 impl<S: Float> AsLab<S> for NamedColor
 where
     u8: AsPrimitive<S>,
@@ -287,17 +634,167 @@ where
     }
 }
 
+impl FromStr for NamedColor {
+    type Err = ParseColorError;
+
+    /// Parses `name:RRGGBB`, `name:RGB`, `name:r,g,b` or one of a few common color names.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        let Some((name, color)) = value.split_once(':') else {
+            return COMMON_COLORS
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(value))
+                .map(|&(name, color)| NamedColor {
+                    name: String::from(name),
+                    color,
+                })
+                .ok_or_else(|| ParseColorError(String::from(value)));
+        };
+        let error = || ParseColorError(String::from(value));
+        let hex = |digits: &str| u8::from_str_radix(digits, 16).map_err(|_| error());
+        let color = match color.trim_start_matches('#') {
+            rgb if rgb.contains(',') => {
+                let mut channels = rgb.split(',').map(|channel| channel.trim().parse::<u8>());
+                match (channels.next(), channels.next(), channels.next(), channels.next()) {
+                    (Some(Ok(r)), Some(Ok(g)), Some(Ok(b)), None) => (r, g, b),
+                    _ => return Err(error()),
+                }
+            }
+            rgb if rgb.len() == 3 && rgb.is_ascii() => {
+                let (r, g, b) = (hex(&rgb[0..1])?, hex(&rgb[1..2])?, hex(&rgb[2..3])?);
+                (r * 17, g * 17, b * 17)
+            }
+            rgb if rgb.len() == 6 && rgb.is_ascii() => {
+                (hex(&rgb[0..2])?, hex(&rgb[2..4])?, hex(&rgb[4..6])?)
+            }
+            _ => return Err(error()),
+        };
+        Ok(NamedColor {
+            name: String::from(name),
+            color,
+        })
+    }
+}
+
+const COMMON_COLORS: [(&str, (u8, u8, u8)); 8] = [
+    ("Black", (0, 0, 0)),
+    ("White", (255, 255, 255)),
+    ("Red", (255, 0, 0)),
+    ("Green", (0, 255, 0)),
+    ("Blue", (0, 0, 255)),
+    ("Yellow", (255, 255, 0)),
+    ("Cyan", (0, 255, 255)),
+    ("Magenta", (255, 0, 255)),
+];
+
+#[derive(Debug, thiserror::Error)]
+#[error("Invalid color `{0}`, expected `name:RRGGBB`, `name:RGB`, `name:r,g,b` or a common color name.")]
+pub struct ParseColorError(String);
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("Missing file path.")]
     MissingFilePath,
 
     #[error(transparent)]
-    AlgorithmError(Box<dyn std::error::Error>),
+    Import(Box<dyn std::error::Error>),
+
+    #[error(transparent)]
+    Baking(Box<dyn std::error::Error>),
 
-    #[error("Unimplemented feature: {0}.")]
-    UnimplementedFeature(&'static str),
+    #[error("The initial nail index must be smaller than the total number of nails.")]
+    InvalidInitialNail,
 
     #[error(transparent)]
-    ImageError(image::ImageError),
+    LineSelector(string_art::line_selector::Error),
+
+    #[error("The computation was cancelled.")]
+    Cancelled,
+
+    #[error("The instructions were computed on another image or nail table.")]
+    Mismatch,
+
+    #[error("Step {0} of the instructions is not a line of the nail table.")]
+    InvalidStep(usize),
+
+    #[error("The checkpoint was taken on another image or configuration.")]
+    CheckpointMismatch,
+
+    #[error(transparent)]
+    Image(image::ImageError),
+
+    #[error(transparent)]
+    Frame(PolygonError),
+
+    #[error(transparent)]
+    Interior(InteriorError),
+
+    #[error("Missing nail positions file.")]
+    MissingNailFile,
+
+    #[error(transparent)]
+    NailFile(ParseError),
+}
+
+impl<E: std::error::Error + 'static> From<string_art::Error<E>> for Error {
+    fn from(err: string_art::Error<E>) -> Self {
+        match err {
+            string_art::Error::Baking(err) => Error::Baking(Box::new(err)),
+            string_art::Error::InvalidInitialNail => Error::InvalidInitialNail,
+            string_art::Error::LineSelector(err) => Error::LineSelector(err),
+            string_art::Error::Cancelled => Error::Cancelled,
+            string_art::Error::Mismatch => Error::Mismatch,
+            string_art::Error::InvalidStep(step) => Error::InvalidStep(step),
+            string_art::Error::CheckpointMismatch => Error::CheckpointMismatch,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use string_art::ThreadWidth;
+
+    #[test]
+    fn parses_named_colors() {
+        let parse = |value: &str| value.parse::<NamedColor>().map(|color| (color.name, color.color));
+        assert_eq!(parse("sky:87CEEB").unwrap(), (String::from("sky"), (135, 206, 235)));
+        assert_eq!(parse("sky:#8CE").unwrap(), (String::from("sky"), (136, 204, 238)));
+        assert_eq!(parse(" rust: 183, 65,14 ").unwrap(), (String::from("rust"), (183, 65, 14)));
+        assert_eq!(parse("WHITE").unwrap(), (String::from("White"), (255, 255, 255)));
+        for invalid in ["mauve", "sky:87CEE", "sky:1,2", "sky:1,2,300", "sky:GGG"] {
+            assert!(parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn boards_give_the_same_physical_result_at_any_resolution() {
+        let args = Args {
+            nail_shape: NailShape::Square(2.0),
+            tickness: 0.5,
+            board_diameter: Some(500.0),
+            stop: StopArgs {
+                length_budget: Some(1e5),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        for resolution in [500, 1000] {
+            let grid = Grid::new(resolution * 4 / 5, resolution);
+            let scale = resolution as f32 / 500.0;
+            let pixels = args.in_pixels(&grid);
+            assert_eq!(pixels.board_diameter, None);
+            assert_eq!(pixels.nail_shape, NailShape::Square(2.0 * scale));
+            assert_eq!(pixels.tickness, 0.5 * scale);
+            assert_eq!(pixels.stop.length_budget, Some(1e5 * scale));
+            let style = pixels.export.style(pixels.tickness, pixels.nail_shape, args.board());
+            assert_eq!(
+                style.thread_width,
+                ThreadWidth::Millimeters {
+                    width: 0.5,
+                    board_diameter: 500.0
+                }
+            );
+        }
+    }
 }
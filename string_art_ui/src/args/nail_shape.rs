@@ -5,6 +5,20 @@ use serde::{Deserialize, Serialize};
 pub enum NailShape {
     Circular(f32),
     Point,
+    Square(f32),
+    Hexagonal(f32),
+    Hook { radius: f32, wrap: HookWrap },
+}
+
+/// Direction the thread wraps each hook, as seen when following the instructions.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum HookWrap {
+    Clockwise,
+    CounterClockwise,
+    /// Hook tips point along the rotation of their nail, away from the center of the frame.
+    Outward,
+    /// Hook tips point against the rotation of their nail, toward the center of the frame.
+    Inward,
 }
 
 impl NailShape {
@@ -15,28 +29,121 @@ impl NailShape {
                 .selected_text(*self)
                 .show_ui(ui, |ui| {
                     ui.selectable_value(self, NailShape::Point, "Point")
-                        .on_hover_text("A classic circular nail.");
+                        .on_hover_text("It is equivalent to a circular nail with a radius of zero.\n\nAlthough \
+physically impossible, it significantly accelerates calculations by avoiding the need to compute tangents and the entry \
+and exit points of the nail. Threads have a single way around each nail, so the instructions carry no direction.");
                     ui.selectable_value(
                         self,
                         NailShape::Circular(1.0),
                         "Circular",
-                    ).on_hover_text("It is equivalent to a circular nail with a radius of zero.\n\nAlthough \
-physically impossible, it significantly accelerates calculations by avoiding the need to compute tangents and the entry \
-and exit points of the nail.");
+                    ).on_hover_text("A classic circular nail.\n\nThreads wrap around it clockwise or counterclockwise, \
+and the instructions tell which way to go.");
+                    ui.selectable_value(self, NailShape::Square(1.5), "Square")
+                        .on_hover_text("A square-section nail with one face looking away from the center of the \
+frame.\n\nThreads wrap around its corners clockwise or counterclockwise.");
+                    ui.selectable_value(self, NailShape::Hexagonal(1.0), "Hexagonal")
+                        .on_hover_text("A hexagonal standoff with one face looking away from the center of the \
+frame.\n\nThreads wrap around its corners clockwise or counterclockwise.");
+                    ui.selectable_value(
+                        self,
+                        NailShape::Hook {
+                            radius: 1.0,
+                            wrap: HookWrap::Clockwise,
+                        },
+                        "Hook",
+                    )
+                    .on_hover_text("A hook the thread can only pass on one side of.\n\nEach hook is wrapped \
+in a single direction, so the instructions carry no direction.");
                 });
-            if let NailShape::Circular(radius) = self {
-                ui.label("Radius:");
-                ui.add(egui::Slider::new(radius, 0.1..=10.0));
+            match self {
+                NailShape::Circular(radius) | NailShape::Hexagonal(radius) => {
+                    ui.label("Radius:");
+                    ui.add(egui::Slider::new(radius, 0.1..=10.0));
+                }
+                NailShape::Square(side) => {
+                    ui.label("Side:");
+                    ui.add(egui::Slider::new(side, 0.1..=15.0));
+                }
+                NailShape::Hook { radius, wrap } => {
+                    ui.label("Radius:");
+                    ui.add(egui::Slider::new(radius, 0.1..=10.0));
+                    egui::ComboBox::from_id_salt("Hook Wrap")
+                        .selected_text(format!("{wrap:?}"))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(wrap, HookWrap::Clockwise, "Clockwise")
+                                .on_hover_text("Every hook is wrapped clockwise.");
+                            ui.selectable_value(wrap, HookWrap::CounterClockwise, "CounterClockwise")
+                                .on_hover_text("Every hook is wrapped counterclockwise.");
+                            ui.selectable_value(wrap, HookWrap::Outward, "Outward")
+                                .on_hover_text("Each hook points along the rotation of its nail, away from \
+the center of the frame. Hooks pointing away from the center are wrapped clockwise, and the others \
+counterclockwise.");
+                            ui.selectable_value(wrap, HookWrap::Inward, "Inward")
+                                .on_hover_text("Each hook points against the rotation of its nail, toward \
+the center of the frame. Hooks pointing away from the center are wrapped clockwise, and the others \
+counterclockwise.");
+                        });
+                }
+                NailShape::Point => {}
             }
         });
     }
 }
 
+impl NailShape {
+    /// Distance from the center of the nail to its outline, the point nail is drawn half a
+    /// pixel wide.
+    pub fn radius(&self) -> f32 {
+        match *self {
+            NailShape::Point => 0.5,
+            _ => self.diameter() * 0.5,
+        }
+    }
+
+    /// Width of the nail across its center, zero for the point nail.
+    pub fn diameter(&self) -> f32 {
+        match *self {
+            NailShape::Circular(radius) | NailShape::Hexagonal(radius) => radius * 2.0,
+            NailShape::Point => 0.0,
+            NailShape::Square(side) => side,
+            NailShape::Hook { radius, .. } => radius * 2.0,
+        }
+    }
+
+    /// Nail of the same shape with every size multiplied by `factor`.
+    pub fn scaled(self, factor: f32) -> Self {
+        match self {
+            NailShape::Circular(radius) => NailShape::Circular(radius * factor),
+            NailShape::Point => NailShape::Point,
+            NailShape::Square(side) => NailShape::Square(side * factor),
+            NailShape::Hexagonal(radius) => NailShape::Hexagonal(radius * factor),
+            NailShape::Hook { radius, wrap } => NailShape::Hook {
+                radius: radius * factor,
+                wrap,
+            },
+        }
+    }
+
+    /// Nail of the same shape whose [`NailShape::diameter`] is twice `radius`.
+    pub fn with_radius(self, radius: f32) -> Self {
+        match self {
+            NailShape::Circular(_) => NailShape::Circular(radius),
+            NailShape::Point => NailShape::Point,
+            NailShape::Square(_) => NailShape::Square(radius * 2.0),
+            NailShape::Hexagonal(_) => NailShape::Hexagonal(radius),
+            NailShape::Hook { wrap, .. } => NailShape::Hook { radius, wrap },
+        }
+    }
+}
+
 impl From<NailShape> for WidgetText {
     fn from(value: NailShape) -> Self {
         WidgetText::RichText(RichText::new(match value {
             NailShape::Circular(_) => "Circular",
             NailShape::Point => "Point",
+            NailShape::Square(_) => "Square",
+            NailShape::Hexagonal(_) => "Hexagonal",
+            NailShape::Hook { .. } => "Hook",
         }))
     }
 }
@@ -0,0 +1,83 @@
+use num_traits::AsPrimitive;
+use serde::{Deserialize, Serialize};
+use string_art::{Float, StopCriteria};
+
+/// Criteria ending the computation before every thread is drawn, all disabled by default.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct StopArgs {
+    /// Minimum weight of the best line.
+    pub min_weight: Option<f32>,
+    /// Steps and minimum improvement of the error in percent over them.
+    pub plateau: Option<(usize, f32)>,
+    /// Total length of the threads, in millimeters on a board or pixels of the image.
+    pub length_budget: Option<f32>,
+}
+
+impl StopArgs {
+    /// `unit` is the suffix of the length budget.
+    pub fn form(&mut self, ui: &mut egui::Ui, unit: &str) {
+        egui::CollapsingHeader::new("Stopping").show(ui, |ui| {
+            ui.horizontal(|ui| {
+                let mut enabled = self.min_weight.is_some();
+                ui.checkbox(&mut enabled, "Minimum Weight:").on_hover_text("Stops once the best \
+line is worth less than this weight, from 0 up to about 374 for a perfect match of the thread color.");
+                match (enabled, &mut self.min_weight) {
+                    (true, Some(weight)) => {
+                        ui.add(egui::DragValue::new(weight).range(0.0..=400.0));
+                    }
+                    (true, weight @ None) => *weight = Some(100.0),
+                    (false, weight) => *weight = None,
+                }
+            });
+            ui.horizontal(|ui| {
+                let mut enabled = self.plateau.is_some();
+                ui.checkbox(&mut enabled, "Plateau:").on_hover_text("Stops once the error, the \
+weight left in every color, decreases less than this percentage of its initial value over the \
+last threads.");
+                match (enabled, &mut self.plateau) {
+                    (true, Some((window, improvement))) => {
+                        ui.add(egui::DragValue::new(improvement).range(0.0..=100.0).speed(0.01).suffix(" %"));
+                        ui.label("over");
+                        ui.add(egui::DragValue::new(window).range(1..=100000).suffix(" threads"));
+                    }
+                    (true, plateau @ None) => *plateau = Some((500, 0.1)),
+                    (false, plateau) => *plateau = None,
+                }
+            });
+            ui.horizontal(|ui| {
+                let mut enabled = self.length_budget.is_some();
+                ui.checkbox(&mut enabled, "Length Budget:").on_hover_text("Stops before the \
+total length of the threads exceeds this length.");
+                match (enabled, &mut self.length_budget) {
+                    (true, Some(length)) => {
+                        ui.add(egui::DragValue::new(length).range(1.0..=f32::MAX).speed(100.0).suffix(unit));
+                    }
+                    (true, length @ None) => *length = Some(1_000_000.0),
+                    (false, length) => *length = None,
+                }
+            });
+        });
+    }
+
+    /// Same criteria with the length budget multiplied by `factor`.
+    pub fn scaled(self, factor: f32) -> Self {
+        Self {
+            length_budget: self.length_budget.map(|length| length * factor),
+            ..self
+        }
+    }
+
+    pub fn criteria<S: Float>(&self) -> StopCriteria<S>
+    where
+        f32: AsPrimitive<S>,
+    {
+        StopCriteria {
+            min_weight: self.min_weight.map(AsPrimitive::as_),
+            plateau: self.plateau.map(|(window, improvement)| string_art::Plateau {
+                window,
+                min_improvement: (improvement / 100.0).as_(),
+            }),
+            length_budget: self.length_budget.map(AsPrimitive::as_),
+        }
+    }
+}
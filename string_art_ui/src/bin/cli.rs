@@ -0,0 +1,904 @@
+//! Headless front end of `string_art_ui`, taking the same [`Args`] as the GUI from a configuration
+//! file, command line flags or both.
+
+use clap::Parser;
+use std::{
+    fs,
+    io::{self, IsTerminal, Write},
+    num::NonZero,
+    path::PathBuf,
+    process::ExitCode,
+};
+use string_art::{
+    machine, verboser, Instructions, InstructionsError, Machine, Motion, Paper, RasterSize,
+    RasterStyle,
+};
+use string_art_ui::{
+    args::{
+        self, ArgLineCountState, Args, DarknessMode, HookWrap, NailShape, NamedColor, Precision,
+        MEGABYTE,
+    },
+    checkpoint,
+    computation::Progress,
+    config,
+};
+
+/// Generates string art from an image without opening a window.
+#[derive(Parser)]
+#[command(name = "string_art_cli", version, after_help = EXIT_CODES)]
+struct Cli {
+    /// Image to generate the string art from.
+    image: PathBuf,
+
+    /// Configuration saved by the GUI, as `.sac`, `.json` or `.toml`. Flags override its values.
+    #[arg(short, long)]
+    config: Option<PathBuf>,
+
+    /// Rebuild the result from instructions saved as JSON or CSV instead of computing it. The
+    /// image and the nails must be the ones they were computed on, the palette is taken from them.
+    #[arg(long, conflicts_with = "restart")]
+    replay: Option<PathBuf>,
+
+    /// Save the state of the computation to this file every `--checkpoint-interval` threads, to
+    /// restart it with `--restart` after a crash.
+    #[arg(long)]
+    checkpoint: Option<PathBuf>,
+
+    /// Threads computed between two checkpoints.
+    #[arg(long, default_value_t = 500, requires = "checkpoint")]
+    checkpoint_interval: usize,
+
+    /// Restart the computation saved by `--checkpoint`. The image and the flags must be the ones
+    /// it was started with.
+    #[arg(long)]
+    restart: Option<PathBuf>,
+
+    /// Draw more threads on the result once computed or replayed, as `<count>` shared between the
+    /// colors like `--threads`, or `<color>:<count>` of a single color of the palette.
+    #[arg(long, value_parser = parse_added_threads)]
+    add_threads: Option<(Option<String>, usize)>,
+
+    /// Number of nails surrounding the image.
+    #[arg(short, long)]
+    nails: Option<NonZero<usize>>,
+
+    /// Size in pixels of the longest side of the image while computing.
+    #[arg(short, long)]
+    resolution: Option<NonZero<u32>>,
+
+    /// Floating point precision: `single` or `double`.
+    #[arg(long, value_parser = parse_precision)]
+    precision: Option<Precision>,
+
+    /// Weight decay of each pixel: `flat:<value>` or `percentage:<value>`.
+    #[arg(short, long, value_parser = parse_darkness)]
+    darkness: Option<DarknessMode>,
+
+    /// Thread colors as `name:RRGGBB`, `name:RGB`, `name:r,g,b` or a common color name. Replaces
+    /// the whole palette and switches the line count to automatic with every color in one group.
+    #[arg(short, long, value_delimiter = ';')]
+    palette: Vec<NamedColor>,
+
+    /// Number of threads, switching the line count to automatic.
+    #[arg(short, long)]
+    threads: Option<usize>,
+
+    /// Minimum nail count between linked nails.
+    #[arg(long)]
+    min_nail_distance: Option<usize>,
+
+    /// Nail shape: `point`, `circular:<radius>`, `square:<side>`, `hexagonal:<radius>` or
+    /// `hook:<radius>[:cw|:ccw|:out|:in]`, hooks pointing outward or inward along each nail.
+    #[arg(long, value_parser = parse_nail_shape)]
+    nail_shape: Option<NailShape>,
+
+    /// Thread thickness in pixels, or millimeters with `--board-diameter`.
+    #[arg(long)]
+    thickness: Option<f32>,
+
+    /// Diameter of the board in millimeters, giving nail sizes and the thread thickness in
+    /// millimeters.
+    #[arg(long)]
+    board_diameter: Option<f32>,
+
+    /// Memory budget in megabytes for the pixel cache, `0` disables it.
+    #[arg(long)]
+    pixel_cache: Option<u32>,
+
+    /// Stop once the best line is worth less than this weight.
+    #[arg(long)]
+    min_weight: Option<f32>,
+
+    /// Stop once the error decreases less than `<percent>` over the last `<threads>`, as
+    /// `<threads>:<percent>`.
+    #[arg(long, value_parser = parse_plateau)]
+    plateau: Option<(usize, f32)>,
+
+    /// Stop before the total thread length exceeds this length, in pixels of the image or
+    /// millimeters with `--board-diameter`.
+    #[arg(long)]
+    length_budget: Option<f32>,
+
+    #[command(flatten)]
+    outputs: Outputs,
+
+    /// Size in pixels of the longest side of the raster output.
+    #[arg(long, visible_alias = "png-size", conflicts_with = "dpi")]
+    raster_size: Option<u32>,
+
+    /// Resolution of the raster output, printing the longest side of the image at `--diameter`.
+    #[arg(long, requires = "diameter")]
+    dpi: Option<f32>,
+
+    /// Diameter of the board in millimeters, the raster output is sized from it and `--dpi`.
+    #[arg(long, requires = "dpi")]
+    diameter: Option<f32>,
+
+    /// Background of the raster output, as `RRGGBB`, `RGB`, `r,g,b` or a common color name.
+    #[arg(long, value_parser = parse_background)]
+    background: Option<(u8, u8, u8)>,
+
+    /// Opacity of every thread in the raster output.
+    #[arg(long)]
+    opacity: Option<f32>,
+
+    /// Draw the nails in the raster output.
+    #[arg(long)]
+    draw_nails: bool,
+
+    /// Width of the real thread in millimeters, drawing the raster output at `--diameter`.
+    #[arg(long, requires = "diameter")]
+    thread_width: Option<f32>,
+
+    /// Samples per pixel along each axis of the raster output.
+    #[arg(long)]
+    supersampling: Option<u32>,
+
+    /// Tile the template across sheets of `a4` or `letter` paper instead of a single page.
+    #[arg(long, requires = "template", value_parser = parse_paper)]
+    paper: Option<Paper>,
+
+    /// Ring angle in degrees of every nail, one per line, instead of their angle around the center
+    /// of the image.
+    #[arg(long)]
+    nail_angles: Option<PathBuf>,
+
+    /// Degrees added to the ring angle of every nail.
+    #[arg(long, allow_negative_numbers = true)]
+    ring_offset: Option<f32>,
+
+    /// Ring angles grow counterclockwise on the image.
+    #[arg(long)]
+    ring_ccw: bool,
+
+    /// Degrees the ring turns past each side of a nail while wrapping it.
+    #[arg(long)]
+    wrap_angle: Option<f32>,
+
+    /// Arm positions in millimeters inside and outside the ring of nails, as `<inside>:<outside>`.
+    #[arg(long, value_parser = parse_arm, allow_hyphen_values = true)]
+    arm: Option<(f32, f32)>,
+
+    /// Feed rates of the ring in degrees per minute between nails and wrapping them, and of the
+    /// arm in millimeters per minute, as `<travel>:<wrap>:<arm>`.
+    #[arg(long, value_parser = parse_feeds)]
+    feeds: Option<(f32, f32, f32)>,
+
+    /// Print nothing but errors.
+    #[arg(short, long)]
+    quiet: bool,
+}
+
+#[derive(clap::Args)]
+#[group(required = true, multiple = true)]
+struct Outputs {
+    /// Write the threads as an SVG document.
+    #[arg(long)]
+    svg: Option<PathBuf>,
+
+    /// Write the threads as a PNG, JPEG or WebP image, chosen by the extension.
+    #[arg(long, visible_alias = "png")]
+    raster: Option<PathBuf>,
+
+    /// Write the threading instructions, as JSON or CSV if the path ends in `.json` or `.csv` and
+    /// as text otherwise.
+    #[arg(long)]
+    instructions: Option<PathBuf>,
+
+    /// Print the quality of the result, measured against the image in Lab space.
+    #[arg(long)]
+    metrics: bool,
+
+    /// Write the per pixel error of the result as a heatmap image.
+    #[arg(long)]
+    heatmap: Option<PathBuf>,
+
+    /// Write a printable template of the nails at the size of the board, as PDF if the path ends
+    /// in `.pdf` and as SVG otherwise. Needs `--board-diameter` or `--diameter`.
+    #[arg(long)]
+    template: Option<PathBuf>,
+
+    /// Write the moves winding the result on a ring machine as G-code.
+    #[arg(long)]
+    gcode: Option<PathBuf>,
+
+    /// Write the moves winding the result on a ring machine as a motion command stream.
+    #[arg(long)]
+    motions: Option<PathBuf>,
+}
+
+const EXIT_CODES: &str = "\
+Exit codes:
+  1   Failed to write an output
+  2   Invalid command line
+  3   Failed to load the image
+  4   Failed to load the configuration, the instructions, the checkpoint, the nail positions or
+      angles, or to build the frame or the interior nails
+  10  Failed to bake the nail table
+  11  Invalid initial nail
+  12  Invalid line configuration
+  13  Cancelled
+  14  The instructions to replay or the checkpoint to restart do not match the image or the
+      configuration";
+
+#[derive(Debug, thiserror::Error)]
+enum Error {
+    #[error("Failed to load {}: {source}", .path.display())]
+    Config { path: PathBuf, source: config::Error },
+
+    #[error(transparent)]
+    Args(args::Error),
+
+    #[error("Failed to write {}: {message}", .path.display())]
+    Write { path: PathBuf, message: String },
+
+    #[error("Failed to read {}: {source}", .path.display())]
+    Instructions {
+        path: PathBuf,
+        source: InstructionsError,
+    },
+
+    #[error("Failed to read {}: {source}", .path.display())]
+    Checkpoint {
+        path: PathBuf,
+        source: checkpoint::Error,
+    },
+
+    #[error("Failed to read {}: {message}", .path.display())]
+    NailAngles { path: PathBuf, message: String },
+
+    #[error(transparent)]
+    Machine(string_art::MachineError),
+
+    #[error("The nail template needs the size of the board, set `--board-diameter` or `--diameter`")]
+    MissingBoard,
+
+    #[error("No color of the palette is named {0}")]
+    UnknownColor(String),
+}
+
+impl Error {
+    fn exit_code(&self) -> u8 {
+        match self {
+            Error::Write { .. } => 1,
+            Error::MissingBoard | Error::UnknownColor(_) => 2,
+            Error::NailAngles { .. } | Error::Machine(_) => 4,
+            Error::Config { .. } | Error::Instructions { .. } | Error::Checkpoint { .. } => 4,
+            Error::Args(err) => match err {
+                args::Error::MissingFilePath => 2,
+                args::Error::Image(_) => 3,
+                args::Error::Frame(_)
+                | args::Error::Interior(_)
+                | args::Error::MissingNailFile
+                | args::Error::NailFile(_)
+                | args::Error::Import(_) => 4,
+                args::Error::Baking(_) => 10,
+                args::Error::InvalidInitialNail => 11,
+                args::Error::LineSelector(_) => 12,
+                args::Error::Cancelled => 13,
+                args::Error::Mismatch
+                | args::Error::InvalidStep(_)
+                | args::Error::CheckpointMismatch => 14,
+            },
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(&cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::from(err.exit_code())
+        }
+    }
+}
+
+fn run(cli: &Cli) -> Result<(), Error> {
+    let mut args = cli.args()?;
+    if cli.outputs.template.is_some() && args.physical_diameter().is_none() {
+        return Err(Error::MissingBoard);
+    }
+    let replay = match &cli.replay {
+        Some(path) => {
+            let instructions = Instructions::read(path).map_err(|source| Error::Instructions {
+                path: path.clone(),
+                source,
+            })?;
+            args.palette = instructions.header.palette.iter().map(NamedColor::from).collect();
+            Some(instructions)
+        }
+        None => None,
+    };
+    let added_color = match &cli.add_threads {
+        Some((Some(name), _)) => Some(
+            args.palette
+                .iter()
+                .position(|color| color.name.eq_ignore_ascii_case(name))
+                .ok_or_else(|| Error::UnknownColor(name.clone()))?,
+        ),
+        _ => None,
+    };
+    let restart = match &cli.restart {
+        Some(path) => Some(checkpoint::load(path, &args).map_err(|source| Error::Checkpoint {
+            path: path.clone(),
+            source,
+        })?),
+        None => None,
+    };
+    let mut verboser = TerminalVerboser::new(&args, cli.quiet);
+    if let Some(path) = &cli.checkpoint {
+        verboser.checkpoints = Some(Checkpoints {
+            path: path.clone(),
+            interval: cli.checkpoint_interval,
+            args: args.clone(),
+        });
+    }
+    let mut computation = match (&replay, &restart) {
+        (Some(instructions), _) => args.replay(instructions, &mut verboser),
+        (None, Some(checkpoint)) => args.restart(checkpoint, &mut verboser),
+        (None, None) => args.create_algorithm(&mut verboser),
+    }
+    .map_err(Error::Args)?;
+    if let Some((_, threads)) = cli.add_threads {
+        verboser.threads = computation.threads() + threads;
+        computation
+            .resume(
+                &args.line_config.added(added_color, threads),
+                &args.stop,
+                &mut verboser,
+            )
+            .map_err(Error::Args)?;
+    }
+    verboser.finish();
+    if !cli.quiet {
+        let length = computation.thread_length();
+        let scale = args
+            .physical_diameter()
+            .map(|diameter| length.millimeters_per_pixel(diameter as f64));
+        let format = |pixels: f64| match scale {
+            Some(scale) => format!("{:.2} m", pixels * scale / 1000.0),
+            None => format!("{:.0} px", pixels),
+        };
+        eprintln!("Thread length: {}", format(length.total()));
+        for (color, &pixels) in args.palette.iter().zip(length.per_color()) {
+            eprintln!("  {}: {}", color.name, format(pixels));
+        }
+    }
+    let grid = computation.grid();
+    let pixels = args.in_pixels(&grid);
+    if let Some(path) = &cli.outputs.svg {
+        svg::save(path, &args.label_svg(computation.build_svg(pixels.tickness), &grid))
+            .map_err(|err| Error::Write {
+                path: path.clone(),
+                message: err.to_string(),
+            })?;
+    }
+    if let Some(path) = &cli.outputs.raster {
+        let style = pixels.export.style(pixels.tickness, pixels.nail_shape, args.board());
+        computation
+            .build_raster(&style)
+            .map_err(|err| err.to_string())
+            .and_then(|image| {
+                args::save_raster(&image, path, style.size.dpi()).map_err(|err| err.to_string())
+            })
+            .map_err(|message| Error::Write {
+                path: path.clone(),
+                message,
+            })?;
+    }
+    if let Some(path) = &cli.outputs.instructions {
+        args::save_instructions(computation.as_ref(), &args, path)
+            .map_err(|err| Error::Write {
+                path: path.clone(),
+                message: err.to_string(),
+            })?;
+    }
+    if let Some(path) = &cli.outputs.template {
+        let board = args.physical_board(&grid).ok_or(Error::MissingBoard)?;
+        let template = computation.template(&board);
+        let template = match cli.paper {
+            Some(paper) => template.with_paper(paper),
+            None => template,
+        };
+        args::save_template(&template, path).map_err(|err| Error::Write {
+            path: path.clone(),
+            message: err.to_string(),
+        })?;
+    }
+    if cli.outputs.gcode.is_some() || cli.outputs.motions.is_some() {
+        let machine = cli.machine(&args)?;
+        let motions = computation.motions(&machine).map_err(Error::Machine)?;
+        for (path, text) in [
+            (&cli.outputs.gcode, machine::gcode as fn(&[Motion]) -> String),
+            (&cli.outputs.motions, machine::commands),
+        ] {
+            if let Some(path) = path {
+                fs::write(path, text(&motions)).map_err(|err| Error::Write {
+                    path: path.clone(),
+                    message: err.to_string(),
+                })?;
+            }
+        }
+    }
+    if cli.outputs.metrics || cli.outputs.heatmap.is_some() {
+        // Nails are not part of the image, so only the threads are measured.
+        let style = RasterStyle {
+            nails: None,
+            ..pixels.export.style(pixels.tickness, pixels.nail_shape, args.board())
+        };
+        if let Some(quality) = computation.quality(&style) {
+            if cli.outputs.metrics {
+                println!("mean delta e: {:.4}", quality.mean_delta_e);
+                println!("ciede2000:    {:.4}", quality.mean_ciede2000);
+                println!("ssim:         {:.4}", quality.ssim);
+                println!("psnr:         {:.4} dB", quality.psnr);
+            }
+            if let Some(path) = &cli.outputs.heatmap {
+                args::save_raster(&quality.heatmap, path, None).map_err(|err| Error::Write {
+                    path: path.clone(),
+                    message: err.to_string(),
+                })?;
+            }
+        }
+    }
+    Ok(())
+}
+
+impl Cli {
+    fn args(&self) -> Result<Args, Error> {
+        let mut args = match &self.config {
+            Some(path) => config::load(path).map_err(|source| Error::Config {
+                path: path.clone(),
+                source,
+            })?,
+            None => Args::default(),
+        };
+        args.file_path = Some(self.image.display().to_string());
+        if let Some(nails) = self.nails {
+            args.nails = nails;
+        }
+        if let Some(resolution) = self.resolution {
+            args.resolution = resolution;
+        }
+        if let Some(precision) = self.precision {
+            args.precision = precision;
+        }
+        if let Some(darkness) = self.darkness {
+            args.darkness_mode = darkness;
+        }
+        if !self.palette.is_empty() {
+            args.palette = self.palette.clone();
+            args.line_config.auto.groups = vec![string_art::auto_line_config::AutoLineGroupConfig::new(
+                (0..args.palette.len()).collect(),
+                0.5,
+            )];
+            args.line_config.state = ArgLineCountState::Auto;
+        }
+        if let Some(threads) = self.threads {
+            args.line_config.auto.threads = threads;
+            args.line_config.state = ArgLineCountState::Auto;
+        }
+        if let Some(distance) = self.min_nail_distance {
+            args.min_nail_distance = distance;
+        }
+        if let Some(shape) = self.nail_shape {
+            args.nail_shape = shape;
+        }
+        if let Some(thickness) = self.thickness {
+            args.tickness = thickness;
+        }
+        if let Some(diameter) = self.board_diameter {
+            args.board_diameter = Some(diameter);
+        }
+        if let Some(budget) = self.pixel_cache {
+            args.pixel_cache = (budget > 0).then_some(budget);
+        }
+        if let Some(weight) = self.min_weight {
+            args.stop.min_weight = Some(weight);
+        }
+        if let Some(plateau) = self.plateau {
+            args.stop.plateau = Some(plateau);
+        }
+        if let Some(length) = self.length_budget {
+            args.stop.length_budget = Some(length);
+        }
+        if let Some(pixels) = self.raster_size {
+            args.export.size = RasterSize::Pixels(pixels);
+        }
+        if let (Some(dpi), Some(diameter)) = (self.dpi, self.diameter) {
+            args.export.size = RasterSize::Physical { dpi, diameter };
+        }
+        if let Some(background) = self.background {
+            args.export.background = background;
+        }
+        if let Some(opacity) = self.opacity {
+            args.export.opacity = opacity;
+        }
+        args.export.nails |= self.draw_nails;
+        if let Some(width) = self.thread_width {
+            args.export.thread_width = Some(width);
+        }
+        if let Some(supersampling) = self.supersampling {
+            args.export.supersampling = supersampling;
+        }
+        if let Some(offset) = self.ring_offset {
+            args.machine.angle_offset = offset;
+        }
+        args.machine.counterclockwise |= self.ring_ccw;
+        if let Some(angle) = self.wrap_angle {
+            args.machine.wrap_angle = angle;
+        }
+        if let Some((inside, outside)) = self.arm {
+            args.machine.arm_inside = inside;
+            args.machine.arm_outside = outside;
+        }
+        if let Some((travel, wrap, arm)) = self.feeds {
+            args.machine.travel_feed = travel;
+            args.machine.wrap_feed = wrap;
+            args.machine.arm_feed = arm;
+        }
+        Ok(args)
+    }
+
+    fn machine(&self, args: &Args) -> Result<Machine, Error> {
+        let machine = args.machine.machine();
+        let Some(path) = &self.nail_angles else {
+            return Ok(machine);
+        };
+        let error = |message: String| Error::NailAngles {
+            path: path.clone(),
+            message,
+        };
+        let angles = fs::read_to_string(path)
+            .map_err(|err| error(err.to_string()))?
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| line.parse().map_err(|err| error(format!("`{}`: {}", line, err))))
+            .collect::<Result<_, _>>()?;
+        Ok(machine.with_nail_angles(angles))
+    }
+}
+
+fn parse_precision(value: &str) -> Result<Precision, String> {
+    match value.to_ascii_lowercase().as_str() {
+        "single" => Ok(Precision::Single),
+        "double" => Ok(Precision::Double),
+        _ => Err(String::from("expected `single` or `double`")),
+    }
+}
+
+fn parse_added_threads(value: &str) -> Result<(Option<String>, usize), String> {
+    let (color, count) = match value.rsplit_once(':') {
+        Some((color, count)) => (Some(String::from(color.trim())), count),
+        None => (None, value),
+    };
+    let count = count
+        .trim()
+        .parse()
+        .map_err(|_| String::from("expected `<count>` or `<color>:<count>`"))?;
+    Ok((color, count))
+}
+
+fn parse_paper(value: &str) -> Result<Paper, String> {
+    match value.to_ascii_lowercase().as_str() {
+        "a4" => Ok(Paper::A4),
+        "letter" => Ok(Paper::Letter),
+        _ => Err(String::from("expected `a4` or `letter`")),
+    }
+}
+
+fn parse_arm(value: &str) -> Result<(f32, f32), String> {
+    let (inside, outside) = value
+        .split_once(':')
+        .ok_or_else(|| String::from("expected `<inside>:<outside>`"))?;
+    Ok((
+        inside.parse().map_err(|err| format!("{}", err))?,
+        outside.parse().map_err(|err| format!("{}", err))?,
+    ))
+}
+
+fn parse_feeds(value: &str) -> Result<(f32, f32, f32), String> {
+    let feeds = value
+        .split(':')
+        .map(|feed| feed.parse().map_err(|err| format!("{}", err)))
+        .collect::<Result<Vec<f32>, _>>()?;
+    match feeds[..] {
+        [travel, wrap, arm] => Ok((travel, wrap, arm)),
+        _ => Err(String::from("expected `<travel>:<wrap>:<arm>`")),
+    }
+}
+
+fn parse_darkness(value: &str) -> Result<DarknessMode, String> {
+    let (mode, amount) = value
+        .split_once(':')
+        .ok_or_else(|| String::from("expected `flat:<value>` or `percentage:<value>`"))?;
+    let amount = amount.parse().map_err(|err| format!("{}", err))?;
+    match mode.to_ascii_lowercase().as_str() {
+        "flat" => Ok(DarknessMode::Flat(amount)),
+        "percentage" => Ok(DarknessMode::Percentage(amount)),
+        _ => Err(format!("unknown darkness mode `{}`", mode)),
+    }
+}
+
+fn parse_plateau(value: &str) -> Result<(usize, f32), String> {
+    let (threads, percent) = value
+        .split_once(':')
+        .ok_or_else(|| String::from("expected `<threads>:<percent>`"))?;
+    Ok((
+        threads.parse().map_err(|err| format!("{}", err))?,
+        percent.parse().map_err(|err| format!("{}", err))?,
+    ))
+}
+
+fn parse_background(value: &str) -> Result<(u8, u8, u8), String> {
+    value
+        .parse::<NamedColor>()
+        .or_else(|_| format!("background:{}", value).parse::<NamedColor>())
+        .map(|color| color.color)
+        .map_err(|_| format!("invalid color `{}`", value))
+}
+
+fn parse_nail_shape(value: &str) -> Result<NailShape, String> {
+    let mut parts = value.split(':');
+    let kind = parts.next().unwrap_or_default().to_ascii_lowercase();
+    let mut size = || -> Result<f32, String> {
+        parts
+            .next()
+            .ok_or_else(|| format!("`{}` needs a size", kind))?
+            .parse()
+            .map_err(|err| format!("{}", err))
+    };
+    let shape = match kind.as_str() {
+        "point" => NailShape::Point,
+        "circular" => NailShape::Circular(size()?),
+        "square" => NailShape::Square(size()?),
+        "hexagonal" => NailShape::Hexagonal(size()?),
+        "hook" => {
+            let radius = size()?;
+            let wrap = match parts.next() {
+                None | Some("cw") => HookWrap::Clockwise,
+                Some("ccw") => HookWrap::CounterClockwise,
+                Some("out") => HookWrap::Outward,
+                Some("in") => HookWrap::Inward,
+                Some(other) => return Err(format!("unknown hook direction `{}`", other)),
+            };
+            NailShape::Hook { radius, wrap }
+        }
+        _ => return Err(format!("unknown nail shape `{}`", kind)),
+    };
+    match parts.next() {
+        Some(extra) => Err(format!("unexpected `{}`", extra)),
+        None => Ok(shape),
+    }
+}
+
+/// Prints the progress on a single line of the standard error.
+struct TerminalVerboser {
+    threads: usize,
+    nails: Option<usize>,
+    // Threads computed so far.
+    computed: usize,
+    quiet: bool,
+    // Rewrites the line on every step, otherwise only the start of each phase is printed.
+    terminal: bool,
+    // Last message printed, so each phase ends on its own line.
+    last: Option<String>,
+    checkpoints: Option<Checkpoints>,
+}
+
+// Where and how often the computation is saved.
+struct Checkpoints {
+    path: PathBuf,
+    interval: usize,
+    args: Args,
+}
+
+impl TerminalVerboser {
+    fn new(args: &Args, quiet: bool) -> Self {
+        Self {
+            threads: match args.line_config.state {
+                ArgLineCountState::Manual => args
+                    .line_config
+                    .manual
+                    .iter()
+                    .map(|group| group.iter().map(|item| item.cap).sum::<usize>())
+                    .sum(),
+                ArgLineCountState::Auto => args.line_config.auto.threads,
+            },
+            nails: (!args.frame_shape.is_imported()).then(|| args.nails.get()),
+            computed: 0,
+            quiet,
+            terminal: io::stderr().is_terminal(),
+            last: None,
+            checkpoints: None,
+        }
+    }
+
+    fn print(&mut self, phase: &str, message: String) {
+        let same_phase = self.last.as_deref() == Some(phase);
+        if self.quiet || (same_phase && !self.terminal) {
+            return;
+        }
+        let mut stderr = io::stderr().lock();
+        if self.last.is_some() && !same_phase {
+            let _ = writeln!(stderr);
+        }
+        if self.terminal {
+            let _ = write!(stderr, "\r{}\x1b[K", message);
+        } else {
+            let _ = write!(stderr, "{}", message);
+        }
+        let _ = stderr.flush();
+        self.last = Some(String::from(phase));
+    }
+
+    fn finish(&mut self) {
+        if !self.quiet && self.last.take().is_some() {
+            eprintln!();
+        }
+    }
+}
+
+impl Progress for TerminalVerboser {
+    fn loading_image(&mut self) {
+        self.print("loading", String::from("Loading image..."));
+    }
+}
+
+impl verboser::Verboser for TerminalVerboser {
+    fn verbose(&mut self, message: verboser::Message) {
+        match message {
+            verboser::Message::CreatingNail(idx) => {
+                let message = match self.nails {
+                    Some(nails) => format!("Nailing {}/{}, {}%", idx, nails, (idx * 100) / nails),
+                    None => format!("Nailing {}", idx),
+                };
+                self.print("nailing", message)
+            }
+            verboser::Message::Baking => self.print("baking", String::from("Baking")),
+            verboser::Message::BakingPixels { bytes, budget } => {
+                if bytes > budget && !self.quiet {
+                    self.finish();
+                    eprintln!(
+                        "warning: the pixel cache would take {} MB, over the {} MB budget, so \
+lines are rasterized on every step.",
+                        bytes.div_ceil(MEGABYTE),
+                        budget / MEGABYTE
+                    );
+                }
+                self.print(
+                    "pixels",
+                    format!("Baking pixels, {} MB", bytes.div_ceil(MEGABYTE)),
+                )
+            }
+            verboser::Message::Dithering(idx, total) => self.print(
+                "dithering",
+                format!("Dithering {}/{}, {}%", idx, total, (idx * 100) / total.max(1)),
+            ),
+            verboser::Message::Computing(idx) => {
+                self.computed = idx;
+                let message = format!(
+                    "Computing {}/{}, {}%",
+                    idx,
+                    self.threads,
+                    (idx * 100) / self.threads.max(1)
+                );
+                self.print("computing", message)
+            }
+            verboser::Message::Stopped(reason) => {
+                let message = format!("Stopped after {} threads: {}.", self.computed, reason);
+                self.print("stopped", message)
+            }
+        }
+    }
+
+    fn checkpoint_interval(&self) -> Option<usize> {
+        self.checkpoints.as_ref().map(|checkpoints| checkpoints.interval)
+    }
+
+    fn checkpoint(&mut self, checkpoint: string_art::Checkpoint) {
+        let Some(checkpoints) = &self.checkpoints else {
+            return;
+        };
+        // A failed checkpoint does not stop the computation, the previous one is kept.
+        if let Err(err) = checkpoint::save(checkpoint, &checkpoints.args, &checkpoints.path) {
+            let path = checkpoints.path.display().to_string();
+            self.finish();
+            eprintln!("warning: failed to write {}: {}", path, err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_nail_shapes() {
+        assert_eq!(parse_nail_shape("point"), Ok(NailShape::Point));
+        assert_eq!(parse_nail_shape("Circular:2.5"), Ok(NailShape::Circular(2.5)));
+        assert_eq!(
+            parse_nail_shape("hook:1.5"),
+            Ok(NailShape::Hook {
+                radius: 1.5,
+                wrap: HookWrap::Clockwise
+            })
+        );
+        assert_eq!(
+            parse_nail_shape("hook:1:out"),
+            Ok(NailShape::Hook {
+                radius: 1.0,
+                wrap: HookWrap::Outward
+            })
+        );
+        for invalid in ["square", "point:1", "hook:1:sideways", "hook:1:in:2", "star:1"] {
+            assert!(parse_nail_shape(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn flags_override_the_defaults() {
+        let cli = Cli::try_parse_from([
+            "string_art_cli",
+            "image.png",
+            "--svg",
+            "out.svg",
+            "--palette",
+            "black;cyan:0FF;warm:250,200,150",
+            "--threads",
+            "300",
+            "--darkness",
+            "percentage:0.9",
+            "--pixel-cache",
+            "0",
+        ])
+        .unwrap();
+        let args = cli.args().unwrap();
+        assert_eq!(args.file_path.as_deref(), Some("image.png"));
+        let colors: Vec<_> = args.palette.iter().map(|color| color.color).collect();
+        assert_eq!(colors, [(0, 0, 0), (0, 255, 255), (250, 200, 150)]);
+        assert!(args.line_config.state == ArgLineCountState::Auto);
+        assert_eq!(args.line_config.auto.threads, 300);
+        assert_eq!(args.line_config.auto.groups[0].len(), 3);
+        assert_eq!(args.darkness_mode, DarknessMode::Percentage(0.9));
+        assert_eq!(args.pixel_cache, None);
+    }
+
+    #[test]
+    fn needs_an_output() {
+        assert!(Cli::try_parse_from(["string_art_cli", "image.png"]).is_err());
+    }
+
+    #[test]
+    fn exit_codes_tell_failures_apart() {
+        assert_eq!(Error::Args(args::Error::MissingFilePath).exit_code(), 2);
+        assert_eq!(Error::Args(args::Error::MissingNailFile).exit_code(), 4);
+        assert_eq!(Error::Args(args::Error::InvalidInitialNail).exit_code(), 11);
+        assert_eq!(Error::Args(args::Error::Cancelled).exit_code(), 13);
+        let cli = Cli::try_parse_from(["string_art_cli", "image.png", "-c", "missing.sac", "--svg", "out.svg"]);
+        assert!(matches!(cli.unwrap().args(), Err(ref err) if err.exit_code() == 4));
+    }
+}
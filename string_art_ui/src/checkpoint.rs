@@ -0,0 +1,192 @@
+//! Checkpoint files of a running computation, to restart it after a crash.
+//!
+//! A [`MAGIC`] header and the version come before the bincode encoded [`Checkpoint`] and the
+//! darkness it was computed with, which the hash of the checkpoint leaves out.
+
+use std::{fs, io, path::Path};
+
+use bincode::Options;
+use serde::{Deserialize, Serialize};
+use string_art::Checkpoint;
+
+use crate::args::{Args, DarknessMode};
+
+/// Version of the layout written by [`save`].
+pub const VERSION: u32 = 1;
+
+pub const MAGIC: &[u8; 4] = b"SACK";
+
+/// Extension of checkpoint files.
+pub const EXTENSION: &str = "sack";
+
+#[derive(Serialize, Deserialize)]
+struct CheckpointFile {
+    darkness: DarknessMode,
+    checkpoint: Checkpoint,
+}
+
+/// Writes `checkpoint` of a computation started with `args`. The file is replaced at once, so a
+/// crash while saving keeps the previous checkpoint.
+pub fn save(checkpoint: Checkpoint, args: &Args, path: &Path) -> Result<(), Error> {
+    let mut bytes = MAGIC.to_vec();
+    bytes.extend(VERSION.to_le_bytes());
+    let file = CheckpointFile {
+        darkness: args.darkness_mode,
+        checkpoint,
+    };
+    options()
+        .serialize_into(&mut bytes, &file)
+        .map_err(Error::Binary)?;
+    let partial = path.with_extension("partial");
+    fs::write(&partial, bytes).map_err(Error::Io)?;
+    fs::rename(&partial, path).map_err(Error::Io)
+}
+
+/// Reads a checkpoint to restart with `args`, failing if they use another darkness. The rest of
+/// the inputs are checked when restarting.
+pub fn load(path: &Path, args: &Args) -> Result<Checkpoint, Error> {
+    let bytes = fs::read(path).map_err(Error::Io)?;
+    let versioned = bytes.strip_prefix(MAGIC).ok_or(Error::NotACheckpoint)?;
+    let (version, file) = versioned
+        .split_first_chunk::<4>()
+        .ok_or(Error::NotACheckpoint)?;
+    match u32::from_le_bytes(*version) {
+        VERSION => {
+            let file: CheckpointFile = options().deserialize(file).map_err(Error::Binary)?;
+            if file.darkness != args.darkness_mode {
+                return Err(Error::Darkness);
+            }
+            Ok(file.checkpoint)
+        }
+        version => Err(Error::UnsupportedVersion(version)),
+    }
+}
+
+fn options() -> impl Options {
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .reject_trailing_bytes()
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(io::Error),
+    #[error(transparent)]
+    Binary(bincode::Error),
+    #[error("Not a checkpoint file.")]
+    NotACheckpoint,
+    #[error("The checkpoint was saved by a newer version (format {0}).")]
+    UnsupportedVersion(u32),
+    #[error("The checkpoint was computed with another darkness.")]
+    Darkness,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZero;
+
+    use string_art::{
+        verboser::{Message, Verboser},
+        StopReason,
+    };
+
+    use super::*;
+    use crate::{
+        args::StopArgs,
+        computation::{Computation, Progress},
+    };
+
+    // Keeps every checkpoint of a computation.
+    #[derive(Default)]
+    struct Recorder(Vec<Checkpoint>);
+
+    impl Verboser for Recorder {
+        fn verbose(&mut self, _message: Message) {}
+
+        fn checkpoint_interval(&self) -> Option<usize> {
+            Some(40)
+        }
+
+        fn checkpoint(&mut self, checkpoint: Checkpoint) {
+            self.0.push(checkpoint);
+        }
+    }
+
+    impl Progress for Recorder {}
+
+    // Small settings stopping on a plateau, over a gradient saved under `name`.
+    fn args(name: &str) -> Args {
+        let path = std::env::temp_dir().join(format!("string_art_{}.png", name));
+        image::RgbImage::from_fn(64, 64, |x, y| {
+            let value = ((x * 4) ^ (y * 3)) as u8;
+            image::Rgb([value, value, value])
+        })
+        .save(&path)
+        .unwrap();
+        Args {
+            file_path: Some(path.display().to_string()),
+            nails: NonZero::new(80).unwrap(),
+            resolution: NonZero::new(64).unwrap(),
+            min_nail_distance: 5,
+            pixel_cache: None,
+            stop: StopArgs {
+                plateau: Some((60, 2.0)),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn restarts_where_the_computation_was() {
+        let args = args("restart");
+        let mut recorder = Recorder::default();
+        let full = args.create_algorithm(&mut recorder).unwrap();
+        assert_eq!(full.stop_reason(), StopReason::Plateau);
+        let checkpoint = recorder.0.last().unwrap().clone();
+        assert!(checkpoint.threads() > 0 && checkpoint.threads() < full.threads());
+
+        let path = Path::new(args.file_path.as_ref().unwrap()).with_extension(EXTENSION);
+        save(checkpoint, &args, &path).unwrap();
+        let loaded = load(&path, &args).unwrap();
+        let restarted = args.restart(&loaded, &mut Recorder::default()).unwrap();
+        assert_eq!(restarted.stop_reason(), StopReason::Plateau);
+        assert_eq!(restarted.instructions(), full.instructions());
+
+        // Caching the pixels of the lines is not part of the inputs, the threads drawn before the
+        // checkpoint are kept.
+        let cached = Args {
+            pixel_cache: Some(64),
+            ..args.clone()
+        };
+        let restarted = cached.restart(&loaded, &mut Recorder::default()).unwrap();
+        // Instructions run backwards, the threads kept come last.
+        let lines = |computation: &dyn Computation| -> Vec<_> {
+            let steps = computation.instructions().steps;
+            steps
+                .iter()
+                .rev()
+                .take(loaded.threads())
+                .map(|step| (step.color, step.from, step.from_link, step.to, step.to_link))
+                .collect()
+        };
+        assert_eq!(lines(&*restarted), lines(&*full));
+    }
+
+    #[test]
+    fn rejects_checkpoints_of_other_settings() {
+        let args = args("mismatch");
+        let mut recorder = Recorder::default();
+        args.create_algorithm(&mut recorder).unwrap();
+        let checkpoint = recorder.0.pop().unwrap();
+        let other = Args {
+            nails: NonZero::new(81).unwrap(),
+            ..args.clone()
+        };
+        assert!(matches!(
+            other.restart(&checkpoint, &mut Recorder::default()),
+            Err(crate::args::Error::CheckpointMismatch)
+        ));
+    }
+}
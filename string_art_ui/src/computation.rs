@@ -0,0 +1,155 @@
+use image::{DynamicImage, RgbImage};
+use num_traits::AsPrimitive;
+use string_art::{
+    darkness::Darkness, grid::Grid, nails, verboser::Verboser, Algorithm, Board, Float, Image,
+    Instructions, Machine, MachineError, Motion, Quality, RasterError, RasterStyle, StopReason,
+    Template, ThreadLength,
+};
+
+use crate::args::{self, ArgLineCount, StopArgs};
+
+/// Result of a computation with its scalar and nail types erased.
+pub trait Computation: Send + Sync {
+    fn build_svg(&self, tickness: f32) -> svg::Document;
+
+    fn build_rgb(&self, resolution: Grid<usize>) -> RgbImage;
+
+    fn build_raster(&self, style: &RasterStyle) -> Result<RgbImage, RasterError>;
+
+    fn build_instructions(&self) -> String;
+
+    /// Structured threading instructions, see [`Algorithm::instructions`].
+    fn instructions(&self) -> Instructions;
+
+    fn get_line_config(&self) -> string_art::LineConfig;
+
+    /// Measures the result rendered with `style` against the computed image.
+    fn quality(&self, style: &RasterStyle) -> Option<Quality>;
+
+    fn stop_reason(&self) -> StopReason;
+
+    fn thread_length(&self) -> ThreadLength;
+
+    /// Threads drawn so far.
+    fn threads(&self) -> usize;
+
+    /// Grid of the computed image.
+    fn grid(&self) -> Grid;
+
+    /// Printable template of the nails at the size of `board`.
+    fn template(&self, board: &Board) -> Template;
+
+    /// Moves winding the result on `machine`.
+    fn motions(&self, machine: &Machine) -> Result<Vec<Motion>, MachineError>;
+
+    /// Draws the threads `line_config` selects on top of the result, see [`Algorithm::resume`].
+    fn resume(
+        &mut self,
+        line_config: &ArgLineCount,
+        stop: &StopArgs,
+        verboser: &mut dyn Verboser,
+    ) -> Result<(), args::Error>;
+}
+
+/// Algorithm kept along with the image it was computed for and its darkness, to resume it.
+pub struct Run<N: nails::Handle, D> {
+    algorithm: Algorithm<N>,
+    source: Image<N::Scalar>,
+    darkness: D,
+}
+
+impl<N: nails::Handle, D> Run<N, D> {
+    pub fn new(algorithm: Algorithm<N>, source: Image<N::Scalar>, darkness: D) -> Self {
+        Self {
+            algorithm,
+            source,
+            darkness,
+        }
+    }
+}
+
+impl<N, D> Computation for Run<N, D>
+where
+    N: nails::Handle<Scalar: Float, Link: ToString, Error: 'static>,
+    D: Darkness<N::Scalar> + Clone,
+    usize: AsPrimitive<N::Scalar>,
+    u8: AsPrimitive<N::Scalar>,
+    f32: AsPrimitive<N::Scalar>,
+{
+    fn build_svg(&self, tickness: f32) -> svg::Document {
+        self.algorithm.build_svg(tickness)
+    }
+
+    fn build_rgb(&self, resolution: Grid<usize>) -> RgbImage {
+        self.algorithm.build_rgb(resolution)
+    }
+
+    fn build_raster(&self, style: &RasterStyle) -> Result<RgbImage, RasterError> {
+        self.algorithm.build_raster(style)
+    }
+
+    fn build_instructions(&self) -> String {
+        self.algorithm.build_instructions()
+    }
+
+    fn instructions(&self) -> Instructions {
+        self.algorithm.instructions()
+    }
+
+    fn get_line_config(&self) -> string_art::LineConfig {
+        self.algorithm.line_selector().into()
+    }
+
+    fn quality(&self, style: &RasterStyle) -> Option<Quality> {
+        self.algorithm.quality(&self.source, style)
+    }
+
+    fn stop_reason(&self) -> StopReason {
+        self.algorithm.stop_reason()
+    }
+
+    fn thread_length(&self) -> ThreadLength {
+        self.algorithm.thread_length()
+    }
+
+    fn threads(&self) -> usize {
+        self.algorithm.threads()
+    }
+
+    fn grid(&self) -> Grid {
+        *self.source.grid()
+    }
+
+    fn template(&self, board: &Board) -> Template {
+        Template::new(&self.algorithm, board, self.source.grid())
+    }
+
+    fn motions(&self, machine: &Machine) -> Result<Vec<Motion>, MachineError> {
+        self.algorithm.motions(machine)
+    }
+
+    fn resume(
+        &mut self,
+        line_config: &ArgLineCount,
+        stop: &StopArgs,
+        mut verboser: &mut dyn Verboser,
+    ) -> Result<(), args::Error> {
+        self.algorithm
+            .resume(
+                &self.source,
+                self.darkness.clone(),
+                line_config,
+                stop.criteria(),
+                &mut verboser,
+            )
+            .map_err(args::Error::from)
+    }
+}
+
+/// [`Verboser`] also told about the steps done before the algorithm starts.
+pub trait Progress: Verboser {
+    fn loading_image(&mut self) {}
+
+    /// Receives the image once it is loaded and resized.
+    fn source(&mut self, _image: &DynamicImage) {}
+}
@@ -0,0 +1,643 @@
+//! Loading and saving [`Args`] as versioned configuration files.
+//!
+//! `.json` and `.toml` files keep the fields of [`Args`] next to a `version` field, other files
+//! are binary `.sac` files: a [`MAGIC`] header and the version before the bincode encoded [`Args`].
+//! Files written by older versions are migrated to the current layout when loaded.
+
+use std::{fs, num::NonZero, path::Path};
+
+use bincode::Options;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use string_art::RasterSize;
+
+use crate::args::{
+    ArgLineCount, Args, DarknessMode, ExportArgs, FrameShape, InteriorNails, LinkRule, NailShape, NamedColor,
+    Precision, StopArgs,
+};
+
+/// Version of the layout written by [`save`].
+pub const VERSION: u32 = 6;
+
+/// Header of the binary files since version 1, unversioned files are read as older layouts.
+pub const MAGIC: &[u8; 4] = b"SAC\0";
+
+/// Extensions recognized by [`Format::from_path`].
+pub const EXTENSIONS: [&str; 3] = ["sac", "json", "toml"];
+
+// Migrates a text configuration from the version at its index to the next one.
+const MIGRATIONS: [fn(&mut Map<String, Value>); VERSION as usize] = [from_v0, from_v1, from_v2, from_v3, from_v4, from_v5];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Binary,
+    Json,
+    Toml,
+}
+
+impl Format {
+    /// Format matching the extension of `path`, binary unless it is `json` or `toml`.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => Format::Json,
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => Format::Toml,
+            _ => Format::Binary,
+        }
+    }
+}
+
+pub fn load(path: &Path) -> Result<Args, Error> {
+    from_slice(&fs::read(path).map_err(Error::Io)?, Format::from_path(path))
+}
+
+pub fn save(args: &Args, path: &Path) -> Result<(), Error> {
+    fs::write(path, to_vec(args, Format::from_path(path))?).map_err(Error::Io)
+}
+
+pub fn from_slice(bytes: &[u8], format: Format) -> Result<Args, Error> {
+    let value = match format {
+        Format::Binary => return from_binary(bytes),
+        Format::Json => serde_json::from_slice(bytes).map_err(Error::Json)?,
+        Format::Toml => {
+            let text = std::str::from_utf8(bytes).map_err(|err| Error::Toml(err.to_string()))?;
+            let value: toml::Value = toml::from_str(text).map_err(|err| Error::Toml(err.to_string()))?;
+            let mut value = serde_json::to_value(value).map_err(Error::Json)?;
+            disabled_from_toml(&mut value);
+            value
+        }
+    };
+    let Value::Object(mut fields) = value else {
+        return Err(Error::Json(serde::de::Error::custom("expected a table of settings")));
+    };
+    // Text files were first written without a version, with the fields of the 0.1.0 release.
+    let version = match fields.remove("version") {
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| Error::Json(serde::de::Error::custom("invalid version")))?,
+        None => 0,
+    };
+    if version > VERSION {
+        return Err(Error::UnsupportedVersion(version));
+    }
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(&mut fields);
+    }
+    serde_json::from_value(Value::Object(fields)).map_err(Error::Json)
+}
+
+pub fn to_vec(args: &Args, format: Format) -> Result<Vec<u8>, Error> {
+    #[derive(Serialize)]
+    struct Versioned<'a> {
+        version: u32,
+        #[serde(flatten)]
+        args: &'a Args,
+    }
+
+    let versioned = Versioned {
+        version: VERSION,
+        args,
+    };
+    match format {
+        Format::Binary => {
+            let mut bytes = MAGIC.to_vec();
+            bytes.extend(VERSION.to_le_bytes());
+            bincode::serialize_into(&mut bytes, args).map_err(Error::Binary)?;
+            Ok(bytes)
+        }
+        Format::Json => serde_json::to_vec_pretty(&versioned).map_err(Error::Json),
+        Format::Toml => {
+            let mut value = serde_json::to_value(&versioned).map_err(Error::Json)?;
+            disabled_to_toml(&mut value);
+            toml::to_string_pretty(&value)
+                .map(String::into_bytes)
+                .map_err(|err| Error::Toml(err.to_string()))
+        }
+    }
+}
+
+// TOML has no null, and a missing pixel cache takes its default budget instead of disabling it. A
+// zero budget never bakes any line either, so it stands for the disabled cache.
+fn disabled_to_toml(value: &mut Value) {
+    if let Some(cache @ Value::Null) = value.get_mut("pixel_cache") {
+        *cache = 0.into();
+    }
+}
+
+fn disabled_from_toml(value: &mut Value) {
+    if let Some(cache) = value.get_mut("pixel_cache").filter(|cache| **cache == 0) {
+        *cache = Value::Null;
+    }
+}
+
+fn from_binary(bytes: &[u8]) -> Result<Args, Error> {
+    // Same encoding as `bincode::serialize`, but failing on trailing bytes so a layout can not be
+    // mistaken for another one.
+    let options = || {
+        bincode::DefaultOptions::new()
+            .with_fixint_encoding()
+            .reject_trailing_bytes()
+    };
+    match bytes.strip_prefix(MAGIC) {
+        Some(versioned) => {
+            let (version, args) = versioned
+                .split_first_chunk::<4>()
+                .ok_or_else(|| Error::Binary(Box::new(bincode::ErrorKind::SizeLimit)))?;
+            match u32::from_le_bytes(*version) {
+                VERSION => options().deserialize(args).map_err(Error::Binary),
+                1 => options()
+                    .deserialize::<ArgsV1>(args)
+                    .map(Args::from)
+                    .map_err(Error::Binary),
+                2 => options()
+                    .deserialize::<ArgsV2>(args)
+                    .map(Args::from)
+                    .map_err(Error::Binary),
+                3 => options()
+                    .deserialize::<ArgsV3>(args)
+                    .map(Args::from)
+                    .map_err(Error::Binary),
+                4 => options()
+                    .deserialize::<ArgsV4>(args)
+                    .map(Args::from)
+                    .map_err(Error::Binary),
+                5 => options()
+                    .deserialize::<ArgsV5>(args)
+                    .map(Args::from)
+                    .map_err(Error::Binary),
+                version => Err(Error::UnsupportedVersion(version)),
+            }
+        }
+        None => match options().deserialize::<ArgsV0>(bytes) {
+            Ok(args) => Ok(args.into()),
+            // Layout saved by development builds before the header existed.
+            Err(err) => options()
+                .deserialize::<ArgsV1>(bytes)
+                .map(Args::from)
+                .map_err(|_| Error::Binary(err)),
+        },
+    }
+}
+
+/// Layout of [`Args`] in the 0.1.0 release.
+#[derive(Deserialize)]
+struct ArgsV0 {
+    nails: NonZero<usize>,
+    nail_shape: NailShape,
+    resolution: NonZero<u32>,
+    precision: Precision,
+    darkness_mode: DarknessMode,
+    min_nail_distance: usize,
+    palette: Vec<NamedColor>,
+    tickness: f32,
+    line_config: ArgLineCount,
+}
+
+impl From<ArgsV0> for Args {
+    fn from(args: ArgsV0) -> Self {
+        Self {
+            nails: args.nails,
+            nail_shape: args.nail_shape,
+            resolution: args.resolution,
+            precision: args.precision,
+            darkness_mode: args.darkness_mode,
+            min_nail_distance: args.min_nail_distance,
+            palette: args.palette,
+            tickness: args.tickness,
+            line_config: args.line_config,
+            ..Default::default()
+        }
+    }
+}
+
+/// Layout of [`Args`] in version 1.
+#[derive(Deserialize)]
+struct ArgsV1 {
+    nails: NonZero<usize>,
+    frame_shape: FrameShape,
+    interior_nails: InteriorNails,
+    nail_shape: NailShape,
+    resolution: NonZero<u32>,
+    precision: Precision,
+    darkness_mode: DarknessMode,
+    min_nail_distance: usize,
+    link_rule: LinkRule,
+    palette: Vec<NamedColor>,
+    tickness: f32,
+    pixel_cache: Option<u32>,
+    line_config: ArgLineCount,
+}
+
+impl From<ArgsV1> for Args {
+    fn from(args: ArgsV1) -> Self {
+        Self {
+            nails: args.nails,
+            frame_shape: args.frame_shape,
+            interior_nails: args.interior_nails,
+            nail_shape: args.nail_shape,
+            resolution: args.resolution,
+            precision: args.precision,
+            darkness_mode: args.darkness_mode,
+            min_nail_distance: args.min_nail_distance,
+            link_rule: args.link_rule,
+            palette: args.palette,
+            tickness: args.tickness,
+            pixel_cache: args.pixel_cache,
+            line_config: args.line_config,
+            ..Default::default()
+        }
+    }
+}
+
+/// Layout of [`Args`] in version 2.
+#[derive(Deserialize)]
+struct ArgsV2 {
+    // Bincode lays nested structs out field after field, so the fields of version 1 come first.
+    v1: ArgsV1,
+    export: ExportArgsV2,
+}
+
+#[derive(Deserialize)]
+struct ExportArgsV2 {
+    size: RasterSize,
+    background: (u8, u8, u8),
+    opacity: f32,
+    nails: bool,
+}
+
+impl From<ArgsV2> for Args {
+    fn from(args: ArgsV2) -> Self {
+        Self {
+            export: ExportArgs {
+                size: args.export.size,
+                background: args.export.background,
+                opacity: args.export.opacity,
+                nails: args.export.nails,
+                ..Default::default()
+            },
+            ..args.v1.into()
+        }
+    }
+}
+
+/// Layout of [`Args`] in version 3.
+#[derive(Deserialize)]
+struct ArgsV3 {
+    v1: ArgsV1,
+    export: ExportArgsV3,
+}
+
+#[derive(Deserialize)]
+struct ExportArgsV3 {
+    size: RasterSize,
+    background: (u8, u8, u8),
+    opacity: f32,
+    nails: bool,
+    thread_width: Option<f32>,
+    supersampling: u32,
+}
+
+impl From<ExportArgsV3> for ExportArgs {
+    fn from(export: ExportArgsV3) -> Self {
+        Self {
+            size: export.size,
+            background: export.background,
+            opacity: export.opacity,
+            nails: export.nails,
+            thread_width: export.thread_width,
+            supersampling: export.supersampling,
+        }
+    }
+}
+
+impl From<ArgsV3> for Args {
+    fn from(args: ArgsV3) -> Self {
+        Self {
+            export: args.export.into(),
+            ..args.v1.into()
+        }
+    }
+}
+
+/// Layout of [`Args`] in version 4.
+#[derive(Deserialize)]
+struct ArgsV4 {
+    v1: ArgsV1,
+    export: ExportArgsV3,
+    stop: StopArgsV4,
+}
+
+#[derive(Deserialize)]
+struct StopArgsV4 {
+    min_weight: Option<f32>,
+    plateau: Option<(usize, f32)>,
+    length_budget: Option<f32>,
+}
+
+impl From<StopArgsV4> for StopArgs {
+    fn from(stop: StopArgsV4) -> Self {
+        Self {
+            min_weight: stop.min_weight,
+            plateau: stop.plateau,
+            length_budget: stop.length_budget,
+        }
+    }
+}
+
+impl From<ArgsV4> for Args {
+    fn from(args: ArgsV4) -> Self {
+        Self {
+            export: args.export.into(),
+            stop: args.stop.into(),
+            ..args.v1.into()
+        }
+    }
+}
+
+/// Layout of [`Args`] in version 5.
+#[derive(Deserialize)]
+struct ArgsV5 {
+    v1: ArgsV1,
+    board_diameter: Option<f32>,
+    export: ExportArgsV3,
+    stop: StopArgsV4,
+}
+
+impl From<ArgsV5> for Args {
+    fn from(args: ArgsV5) -> Self {
+        Self {
+            board_diameter: args.board_diameter,
+            export: args.export.into(),
+            stop: args.stop.into(),
+            ..args.v1.into()
+        }
+    }
+}
+
+// Version 1 added the frame shape, interior nails, link rule and pixel cache, which are filled
+// with their defaults like any other missing field.
+fn from_v0(_fields: &mut Map<String, Value>) {}
+
+// Version 2 added the export settings.
+fn from_v1(_fields: &mut Map<String, Value>) {}
+
+// Version 3 added the thread width and supersampling of the export settings.
+fn from_v2(fields: &mut Map<String, Value>) {
+    if let Some(Value::Object(export)) = fields.get_mut("export") {
+        let defaults = ExportArgs::default();
+        export.entry("thread_width").or_insert(Value::Null);
+        export
+            .entry("supersampling")
+            .or_insert(defaults.supersampling.into());
+    }
+}
+
+// Version 4 added the stopping criteria.
+fn from_v3(_fields: &mut Map<String, Value>) {}
+
+// Version 5 added the board diameter, sizes were pixels before.
+fn from_v4(_fields: &mut Map<String, Value>) {}
+
+// Version 6 added the winding machine.
+fn from_v5(_fields: &mut Map<String, Value>) {}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(std::io::Error),
+
+    #[error("Invalid configuration: {0}")]
+    Json(serde_json::Error),
+
+    #[error("Invalid configuration: {0}")]
+    Toml(String),
+
+    #[error("Invalid configuration: the file is corrupted ({0}).")]
+    Binary(bincode::Error),
+
+    #[error("The configuration was saved by a newer version (format {0}).")]
+    UnsupportedVersion(u32),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::args::{ExportArgs, HookWrap, StopArgs};
+    use string_art::RasterSize;
+
+    // Settings differing from the defaults in every field some version added.
+    fn sample() -> Args {
+        Args {
+            nails: NonZero::new(300).unwrap(),
+            frame_shape: FrameShape::Rectangle(Default::default()),
+            nail_shape: NailShape::Hook {
+                radius: 1.5,
+                wrap: HookWrap::Outward,
+            },
+            interior_nails: InteriorNails::Grid { spacing: 30.0 },
+            min_nail_distance: 7,
+            link_rule: LinkRule::Geometric {
+                min_length: 80.0,
+                min_edge_angle: 10.0,
+                distinct_sides: false,
+            },
+            palette: vec![
+                NamedColor {
+                    name: String::from("Black"),
+                    color: (0, 0, 0),
+                },
+                NamedColor {
+                    name: String::from("Red"),
+                    color: (255, 0, 0),
+                },
+            ],
+            tickness: 0.5,
+            pixel_cache: None,
+            board_diameter: Some(400.0),
+            export: ExportArgs {
+                size: RasterSize::Physical {
+                    dpi: 300.0,
+                    diameter: 400.0,
+                },
+                background: (1, 2, 3),
+                opacity: 0.5,
+                nails: true,
+                thread_width: Some(0.2),
+                supersampling: 3,
+            },
+            stop: StopArgs {
+                min_weight: Some(0.1),
+                plateau: Some((100, 0.5)),
+                length_budget: Some(1e5),
+            },
+            ..Default::default()
+        }
+    }
+
+    // What loading `sample` saved by `version` gives, the fields it lacked are defaults.
+    fn expected(version: u32) -> Args {
+        let defaults = Args::default();
+        let mut args = sample();
+        if version < 1 {
+            args.frame_shape = defaults.frame_shape;
+            args.interior_nails = defaults.interior_nails;
+            args.link_rule = defaults.link_rule;
+            args.pixel_cache = defaults.pixel_cache;
+        }
+        if version < 2 {
+            args.export = ExportArgs::default();
+        }
+        if version < 3 {
+            args.export.thread_width = None;
+            args.export.supersampling = 1;
+        }
+        if version < 4 {
+            args.stop = StopArgs::default();
+        }
+        if version < 5 {
+            args.board_diameter = None;
+        }
+        args
+    }
+
+    fn assert_same(loaded: &Args, expected: &Args) {
+        assert_eq!(
+            serde_json::to_value(loaded).unwrap(),
+            serde_json::to_value(expected).unwrap()
+        );
+    }
+
+    // Binary layout `version` wrote for `args`, which must hold what that version knew.
+    fn binary(version: u32, args: &Args) -> Vec<u8> {
+        let v1 = (
+            args.nails,
+            &args.frame_shape,
+            &args.interior_nails,
+            &args.nail_shape,
+            args.resolution,
+            &args.precision,
+            &args.darkness_mode,
+            args.min_nail_distance,
+            &args.link_rule,
+            &args.palette,
+            args.tickness,
+            args.pixel_cache,
+            &args.line_config,
+        );
+        let export = &args.export;
+        let export_v2 = (export.size, export.background, export.opacity, export.nails);
+        let export_v3 = (
+            export.size,
+            export.background,
+            export.opacity,
+            export.nails,
+            export.thread_width,
+            export.supersampling,
+        );
+        let stop = (args.stop.min_weight, args.stop.plateau, args.stop.length_budget);
+        let layout = match version {
+            0 => bincode::serialize(&(
+                args.nails,
+                &args.nail_shape,
+                args.resolution,
+                &args.precision,
+                &args.darkness_mode,
+                args.min_nail_distance,
+                &args.palette,
+                args.tickness,
+                &args.line_config,
+            )),
+            1 => bincode::serialize(&v1),
+            2 => bincode::serialize(&(v1, export_v2)),
+            3 => bincode::serialize(&(v1, export_v3)),
+            4 => bincode::serialize(&(v1, export_v3, stop)),
+            5 => bincode::serialize(&(v1, args.board_diameter, export_v3, stop)),
+            _ => unreachable!(),
+        }
+        .unwrap();
+        if version == 0 {
+            return layout;
+        }
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend(version.to_le_bytes());
+        bytes.extend(layout);
+        bytes
+    }
+
+    // Text file `version` wrote for `args`, without the fields added later.
+    fn text(version: u32, args: &Args) -> Map<String, Value> {
+        let Value::Object(mut fields) = serde_json::to_value(args).unwrap() else {
+            unreachable!()
+        };
+        let added = [
+            (1, &["frame_shape", "interior_nails", "link_rule", "pixel_cache"][..]),
+            (2, &["export"]),
+            (4, &["stop"]),
+            (5, &["board_diameter"]),
+            (6, &["machine"]),
+        ];
+        for (since, names) in added {
+            if version < since {
+                for name in names {
+                    fields.remove(*name);
+                }
+            }
+        }
+        if version == 2 {
+            let export = fields["export"].as_object_mut().unwrap();
+            export.remove("thread_width");
+            export.remove("supersampling");
+        }
+        if version > 0 {
+            fields.insert(String::from("version"), version.into());
+        }
+        fields
+    }
+
+    #[test]
+    fn loads_every_binary_version() {
+        for version in 0..VERSION {
+            let loaded = from_slice(&binary(version, &expected(version)), Format::Binary).unwrap();
+            assert_same(&loaded, &expected(version));
+        }
+    }
+
+    #[test]
+    fn loads_unversioned_development_binaries() {
+        let bytes = binary(1, &expected(1));
+        let loaded = from_slice(&bytes[MAGIC.len() + 4..], Format::Binary).unwrap();
+        assert_same(&loaded, &expected(1));
+    }
+
+    #[test]
+    fn loads_every_text_version() {
+        for version in 0..VERSION {
+            let mut fields = Value::Object(text(version, &expected(version)));
+            let json = serde_json::to_vec(&fields).unwrap();
+            assert_same(&from_slice(&json, Format::Json).unwrap(), &expected(version));
+            disabled_to_toml(&mut fields);
+            let toml = toml::to_string(&fields).unwrap();
+            assert_same(&from_slice(toml.as_bytes(), Format::Toml).unwrap(), &expected(version));
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        let args = sample();
+        for format in [Format::Binary, Format::Json, Format::Toml] {
+            let loaded = from_slice(&to_vec(&args, format).unwrap(), format).unwrap();
+            assert_same(&loaded, &args);
+        }
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        let mut fields = text(VERSION, &sample());
+        fields.insert(String::from("version"), (VERSION + 1).into());
+        let json = serde_json::to_vec(&fields).unwrap();
+        assert!(matches!(
+            from_slice(&json, Format::Json),
+            Err(Error::UnsupportedVersion(version)) if version == VERSION + 1
+        ));
+    }
+}
@@ -0,0 +1,4 @@
+pub mod args;
+pub mod checkpoint;
+pub mod computation;
+pub mod config;
@@ -1,10 +1,19 @@
 #![windows_subsystem = "windows"]
 
-use args::Args;
+use string_art_ui::{
+    args::{self, Args, FrameShape, NamedColor},
+    checkpoint,
+    computation::Computation,
+    config::{self, Format},
+};
 use egui::{IconData, ViewportBuilder};
 use rfd::FileDialog;
 use std::{
-    fs::File, io::{BufWriter, Read, Write}, mem, num::NonZero, path::Path, sync::Arc
+    fs::File, io::{BufWriter, Write}, mem, num::NonZero, path::Path, sync::Arc
+};
+use string_art::{
+    verboser::{CancellationToken, Verboser},
+    Instructions, NailPosition, Paper, Quality, RasterStyle, ThreadLength, HEATMAP_MAX_DELTA_E,
 };
 use synced::{ComputationState, Message, SyncData, Synced, SyncedVerboser};
 
@@ -19,20 +28,21 @@ fn config_path() -> Option<std::path::PathBuf> {
         }            
 }
 
+// Checkpoints of the running computation are saved next to the configuration.
+fn checkpoint_path() -> Option<std::path::PathBuf> {
+    config_path().map(|path| path.with_file_name("checkpoint").with_extension(checkpoint::EXTENSION))
+}
+
+#[derive(Default)]
 enum ArgsState {
+    #[default]
     Ready,
     Waiting,
 }
 
-impl Default for ArgsState {
-    fn default() -> Self {
-        Self::Ready
-    }
-}
-
 enum SyncArgs {
     Waiting,
-    Done(Option<Args>),
+    Done(Option<Box<Args>>),
 }
 
 impl Default for SyncArgs {
@@ -41,19 +51,45 @@ impl Default for SyncArgs {
     }
 }
 
+/// Height in points of the source image and the thread preview.
+const PREVIEW_HEIGHT: f32 = 300.0;
+
 #[derive(Default)]
 struct App {
     args: Args,
     args_state: ArgsState,
     sync_data: Synced<SyncData>,
     message: Option<Message>,
+    warning: Option<Message>,
     computation: ComputationState,
+    /// Imported nail file with the number of positions it holds.
+    imported_nails: Option<(String, usize)>,
+    cancel: Option<CancellationToken>,
+    source: Option<egui::TextureHandle>,
+    preview: Option<egui::TextureHandle>,
+    quality: Option<Quality>,
+    /// Thread used by the last result, with the names of its colors.
+    thread_length: Option<(Vec<String>, ThreadLength)>,
+    heatmap: Option<egui::TextureHandle>,
+    /// Sheets the nail template is tiled across, a single page fitting it when `None`.
+    template_paper: Option<Paper>,
+    /// Threads drawn by "Add threads", of a single color or shared between all of them.
+    added_threads: usize,
+    added_color: Option<usize>,
+    /// The running computation adds threads to a result, leaving the line count as it is.
+    resuming: bool,
+    /// Computations save checkpoints to restart them after a crash.
+    save_checkpoints: bool,
 }
 
+/// Threads "Add threads" starts at.
+const ADDED_THREADS: usize = 1000;
+
 impl App {
     fn new(args: Args) -> Self {
         Self {
             args,
+            added_threads: ADDED_THREADS,
             ..Default::default()
         }
     }
@@ -61,24 +97,194 @@ impl App {
     fn with_error(err: impl ToString) -> Self{
         Self{
             message: Some(Message::error(err)),
+            added_threads: ADDED_THREADS,
             ..Default::default()
         }
     }
 
+    // Nails placed on the frame, read from the nail file when the frame is imported.
+    fn frame_nails(&mut self) -> usize {
+        let FrameShape::Imported { file_path, .. } = &self.args.frame_shape else {
+            return self.args.nails.get();
+        };
+        let Some(file_path) = file_path else {
+            return 0;
+        };
+        match &self.imported_nails {
+            Some((path, count)) if path == file_path => *count,
+            _ => {
+                let count = NailPosition::<f32>::read(file_path).map_or(0, |positions| positions.len());
+                self.imported_nails = Some((file_path.clone(), count));
+                count
+            }
+        }
+    }
+
+    // Clears the last result, returning the verboser of the next one.
+    fn start_computation(&mut self) -> SyncedVerboser {
+        self.sync_data.lock().computation = ComputationState::Running;
+        self.computation = ComputationState::Running;
+        self.warning = None;
+        self.source = None;
+        self.preview = None;
+        self.quality = None;
+        self.thread_length = None;
+        self.heatmap = None;
+        let cancel = CancellationToken::new();
+        self.cancel = Some(cancel.clone());
+        let verboser = SyncedVerboser::new(self.sync_data.clone(), &self.args, cancel);
+        let checkpoints = checkpoint_path().filter(|path| {
+            self.save_checkpoints
+                && path.parent().is_some_and(|parent| std::fs::create_dir_all(parent).is_ok())
+        });
+        match checkpoints {
+            Some(path) => verboser.with_checkpoints(path, &self.args),
+            None => verboser,
+        }
+    }
+
     fn compute_button(&mut self, ui: &mut egui::Ui) {
         if ui.button("Compute!").clicked() {
-            self.sync_data.lock().computation = ComputationState::Running;
-            self.computation = ComputationState::Running;
+            let mut verboser = self.start_computation();
             let args = self.args.clone();
-            let mut verboser = SyncedVerboser::new(self.sync_data.clone(), &args);
-            rayon::spawn(move || match args.create_algorithm(&mut verboser) {
-                Ok(algorithm) => {
-                    verboser.lock().computation = ComputationState::Completed(algorithm)
+            rayon::spawn(move || {
+                let result = args.create_algorithm(&mut verboser);
+                complete(&args, &mut verboser, result);
+            });
+        }
+    }
+
+    fn load_instructions_button(&mut self, ui: &mut egui::Ui) {
+        if ui
+            .button("Load instructions")
+            .on_hover_text("Rebuild a result from instructions saved as JSON or CSV. The image and \
+the nails must be the ones they were computed on, the palette is taken from them.")
+            .clicked()
+        {
+            let mut verboser = self.start_computation();
+            self.args_state = ArgsState::Waiting;
+            self.sync_data.lock().args = SyncArgs::Waiting;
+            let mut args = self.args.clone();
+            rayon::spawn(move || {
+                let instructions = match FileDialog::new()
+                    .set_title("Load instructions file")
+                    .add_filter("Instructions", &["json", "csv"])
+                    .pick_file()
+                    .map(Instructions::read)
+                {
+                    Some(Ok(instructions)) => instructions,
+                    Some(Err(err)) => {
+                        let mut synced = verboser.lock();
+                        synced.message =
+                            Some(Message::error(format!("Failed to read the file: {}", err)));
+                        synced.args = SyncArgs::Done(None);
+                        synced.computation = ComputationState::Idle;
+                        return;
+                    }
+                    None => {
+                        let mut synced = verboser.lock();
+                        synced.args = SyncArgs::Done(None);
+                        synced.computation = ComputationState::Idle;
+                        return;
+                    }
+                };
+                args.palette = instructions.header.palette.iter().map(NamedColor::from).collect();
+                let result = args.replay(&instructions, &mut verboser);
+                if let Ok(computation) = &result {
+                    verboser.preview(computation.build_rgb(computation.grid()));
                 }
-                Err(err) => {
-                    let mut synced = verboser.lock();
-                    synced.message = Some(Message::error(err));
-                    synced.computation = ComputationState::Idle;
+                // The palette is in place before the result is shown with its names.
+                verboser.lock().args = SyncArgs::Done(result.is_ok().then(|| Box::new(args.clone())));
+                complete(&args, &mut verboser, result);
+            });
+        }
+    }
+
+    fn checkpoint_buttons(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.save_checkpoints, "Save checkpoints").on_hover_text(format!("Save \
+the state of the computation every {} threads, next to the configuration file, to restart it \
+after a crash.", synced::CHECKPOINT_INTERVAL));
+        if ui
+            .button("Restart checkpoint")
+            .on_hover_text("Restart a computation from a checkpoint. The image and the settings \
+must be the ones it was started with.")
+            .clicked()
+        {
+            let mut verboser = self.start_computation();
+            let args = self.args.clone();
+            rayon::spawn(move || {
+                let mut dialog = FileDialog::new()
+                    .set_title("Restart checkpoint")
+                    .add_filter("Checkpoint", &[checkpoint::EXTENSION]);
+                if let Some(dir) = checkpoint_path().as_deref().and_then(Path::parent) {
+                    dialog = dialog.set_directory(dir);
+                }
+                let checkpoint = match dialog.pick_file().map(|path| checkpoint::load(&path, &args)) {
+                    Some(Ok(checkpoint)) => checkpoint,
+                    Some(Err(err)) => {
+                        let mut synced = verboser.lock();
+                        synced.message =
+                            Some(Message::error(format!("Failed to read the checkpoint: {}", err)));
+                        synced.computation = ComputationState::Idle;
+                        return;
+                    }
+                    None => {
+                        verboser.lock().computation = ComputationState::Idle;
+                        return;
+                    }
+                };
+                let result = args.restart(&checkpoint, &mut verboser);
+                complete(&args, &mut verboser, result);
+            });
+        }
+    }
+
+    fn add_threads_button(&mut self, ui: &mut egui::Ui) {
+        ui.add(egui::DragValue::new(&mut self.added_threads).range(1..=100000));
+        egui::ComboBox::from_id_salt("added_color")
+            .selected_text(
+                self.added_color
+                    .and_then(|idx| self.args.palette.get(idx))
+                    .map_or("All colors", |color| color.name.as_str()),
+            )
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut self.added_color, None, "All colors");
+                for (idx, color) in self.args.palette.iter().enumerate() {
+                    ui.selectable_value(&mut self.added_color, Some(idx), &color.name);
+                }
+            });
+        if ui
+            .button("Add threads")
+            .on_hover_text("Draw more threads on the result, going on from where each color \
+stopped. Threads of all colors are shared between them like the automatic line count.")
+            .clicked()
+        {
+            let mut computation = match mem::replace(&mut self.computation, ComputationState::Running) {
+                ComputationState::Completed(computation) => computation,
+                _ => unsafe { core::hint::unreachable_unchecked() },
+            };
+            self.sync_data.lock().computation = ComputationState::Running;
+            self.warning = None;
+            self.quality = None;
+            self.thread_length = None;
+            self.heatmap = None;
+            self.resuming = true;
+            let cancel = CancellationToken::new();
+            self.cancel = Some(cancel.clone());
+            let color_idx = self.added_color.filter(|&idx| idx < self.args.palette.len());
+            let line_config = self.args.line_config.added(color_idx, self.added_threads);
+            let mut verboser = SyncedVerboser::new(self.sync_data.clone(), &self.args, cancel)
+                .with_threads(computation.threads() + self.added_threads);
+            let args = self.args.clone();
+            rayon::spawn(move || {
+                match computation.resume(&line_config, &args.stop, &mut verboser) {
+                    Ok(()) => complete(&args, &mut verboser, Ok(computation)),
+                    // The result is kept as it was.
+                    Err(err) => {
+                        let mut synced = verboser.lock();
+                        synced.message = Some(Message::error(err));
+                        synced.computation = ComputationState::Completed(computation);
+                    }
                 }
             });
         }
@@ -107,41 +313,49 @@ resolution values will require a darkness algorithm with a steeper gradient to m
                 // Tickness
                 ui.horizontal(|ui| {
                     ui.label("Thread Tickness:");
+                    let unit = if self.args.board_diameter.is_some() { " mm" } else { "" };
                     ui.add(
                         egui::Slider::new(&mut self.args.tickness, 0.1..=2.0)
+                            .suffix(unit)
                             .clamping(egui::SliderClamping::Never),
                     );
                     if self.args.tickness <= 0.0 {
                         self.args.tickness = 0.1;
                     }
                 });
+                self.args.board_form(ui);
             });
 
             columns[1].vertical(|ui| {
                 //Nails
-                ui.horizontal(|ui| {
-                    ui.label("Nail Count:").on_hover_text("Number of nails placed around the image.");
-                    ui.add(
-                        egui::Slider::new(
-                            &mut self.args.nails,
-                            unsafe { NonZero::new_unchecked(1) }..=unsafe {
-                                NonZero::new_unchecked(1000)
-                            },
+                if !self.args.frame_shape.is_imported() {
+                    ui.horizontal(|ui| {
+                        ui.label("Nail Count:").on_hover_text("Number of nails placed around the image.");
+                        ui.add(
+                            egui::Slider::new(
+                                &mut self.args.nails,
+                                unsafe { NonZero::new_unchecked(1) }..=unsafe {
+                                    NonZero::new_unchecked(1000)
+                                },
+                            )
+                            .clamping(egui::SliderClamping::Never),
                         )
-                        .clamping(egui::SliderClamping::Never),
-                    )
-                });
-                // Minimum Nail Distance
-                ui.horizontal(|ui| {
-                    ui.label("Minimum Nail Distance:").on_hover_text("Number of continuous nails that \
-the algorithm will ignore when computing the next line.\n\nThis prevents the algorithm from drifting excessively along \
-the edges of the image. Additionally, the algorithm does not consider a nail when tracing a thread if it is not the \
-starting or ending nail of the line, so avoiding the tracing of edges is usually a good idea.");
-                    ui.add(egui::Slider::new(
-                        &mut self.args.min_nail_distance,
-                        0..=(self.args.nails.get() / 2).saturating_sub(1),
-                    ));
-                });
+                    });
+                }
+                // Frame Shape
+                self.args.frame_shape.form(ui);
+                // Interior Nails
+                let unit = self.args.length_unit();
+                self.args.interior_nails.form(ui, unit);
+                // Link Rule
+                let frame_nails = self.frame_nails();
+                self.args.link_rule.form(
+                    ui,
+                    &mut self.args.min_nail_distance,
+                    frame_nails,
+                    !self.args.interior_nails.is_enabled(),
+                    unit,
+                );
             });
         });
 
@@ -150,6 +364,7 @@ starting or ending nail of the line, so avoiding the tracing of edges is usually
         self.args.nail_shape.form(ui);
         self.args.precision.form(ui);
         self.args.darkness_mode.form(ui);
+        self.args.pixel_cache_form(ui);
 
         ui.separator();
 
@@ -161,6 +376,15 @@ starting or ending nail of the line, so avoiding the tracing of edges is usually
 
         ui.separator();
 
+        let unit = self.args.length_unit();
+        self.args.stop.form(ui, unit);
+
+        self.args.export.form(ui);
+
+        self.args.machine.form(ui);
+
+        ui.separator();
+
         self.args.image_picker(ui);
         ui.separator();
 
@@ -168,22 +392,64 @@ starting or ending nail of the line, so avoiding the tracing of edges is usually
             match &self.computation {
                 ComputationState::Idle => {
                     self.compute_button(ui);
+                    self.load_instructions_button(ui);
+                    self.checkpoint_buttons(ui);
                 }
                 ComputationState::Running => {
                     ui.spinner();
+                    if let Some(cancel) = &self.cancel {
+                        if ui.button("Cancel").clicked() {
+                            cancel.cancel();
+                        }
+                    }
                     let mut synced = self.sync_data.lock();
                     let message = synced.message.take();
                     if let Some(message) = message {
                         self.message = Some(message);
                     }
+                    if let Some(warning) = synced.warning.take() {
+                        self.warning = Some(warning);
+                    }
+                    if let Some(source) = synced.source.take() {
+                        self.source =
+                            Some(ui.ctx().load_texture("source", source, Default::default()));
+                    }
+                    if let Some(quality) = synced.quality.take() {
+                        self.heatmap = Some(ui.ctx().load_texture(
+                            "heatmap",
+                            synced::color_image(&quality.heatmap),
+                            Default::default(),
+                        ));
+                        self.quality = Some(quality);
+                    }
+                    if let Some(preview) = synced.preview.take() {
+                        match &mut self.preview {
+                            Some(texture) => texture.set(preview, Default::default()),
+                            None => {
+                                self.preview = Some(ui.ctx().load_texture(
+                                    "preview",
+                                    preview,
+                                    Default::default(),
+                                ))
+                            }
+                        }
+                    }
                     match &mut synced.computation {
                         ComputationState::Running => {}
                         ComputationState::Idle => {
+                            self.cancel = None;
+                            self.resuming = false;
                             self.computation =
                                 mem::replace(&mut synced.computation, ComputationState::Idle);
                         }
                         ComputationState::Completed(computation) => {
-                            self.args.line_config.manual = computation.get_line_config();
+                            if self.cancel.take().is_some() && !mem::take(&mut self.resuming) {
+                                self.args.line_config.manual = computation.get_line_config();
+                            }
+                            self.thread_length = Some((
+                                self.args.palette.iter().map(|color| color.name.clone()).collect(),
+                                computation.thread_length(),
+                            ));
                             self.computation =
                                 mem::replace(&mut synced.computation, ComputationState::Idle);
                         }
@@ -191,6 +457,9 @@ starting or ending nail of the line, so avoiding the tracing of edges is usually
                 }
                 ComputationState::Completed(_) => {
                     self.compute_button(ui);
+                    self.load_instructions_button(ui);
+                    self.checkpoint_buttons(ui);
+                    self.add_threads_button(ui);
                     if ui.button("Save image").clicked() {
                         self.sync_data.lock().computation = ComputationState::Running;
                         let synced = self.sync_data.clone();
@@ -199,18 +468,49 @@ starting or ending nail of the line, so avoiding the tracing of edges is usually
                                 ComputationState::Completed(computation) => computation,
                                 _ => unsafe { core::hint::unreachable_unchecked() },
                             };
-                        let tickness = self.args.tickness;
+                        let grid = computation.grid();
+                        let pixels = self.args.in_pixels(&grid);
+                        let tickness = pixels.tickness;
+                        let style =
+                            pixels.export.style(tickness, pixels.nail_shape, self.args.board());
+                        let args = self.args.clone();
                         rayon::spawn(move || {
                             match FileDialog::new()
-                                .set_title("Save image as SVG")
+                                .set_title("Save image")
                                 .add_filter("SVG Image", &["svg"])
+                                .add_filter("PNG Image", &["png"])
+                                .add_filter("JPEG Image", &["jpg", "jpeg"])
+                                .add_filter("WebP Image", &["webp"])
                                 .save_file()
                             {
                                 Some(path) => {
-                                    let svg = computation.build_svg(tickness);
-                                    match File::create(path.clone()).and_then(|file| {
-                                        BufWriter::new(file).write_all(svg.to_string().as_bytes())
-                                    }) {
+                                    let is_raster = path
+                                        .extension()
+                                        .and_then(|ext| ext.to_str())
+                                        .is_some_and(|ext| {
+                                            args::RASTER_EXTENSIONS
+                                                .iter()
+                                                .any(|raster| raster.eq_ignore_ascii_case(ext))
+                                        });
+                                    let saved = if is_raster {
+                                        computation
+                                            .build_raster(&style)
+                                            .map_err(|err| err.to_string())
+                                            .and_then(|image| {
+                                                args::save_raster(&image, &path, style.size.dpi())
+                                                    .map_err(|err| err.to_string())
+                                            })
+                                    } else {
+                                        let svg =
+                                            args.label_svg(computation.build_svg(tickness), &grid);
+                                        File::create(path.clone())
+                                            .and_then(|file| {
+                                                BufWriter::new(file)
+                                                    .write_all(svg.to_string().as_bytes())
+                                            })
+                                            .map_err(|err| err.to_string())
+                                    };
+                                    match saved {
                                         Ok(_) => match open::that(path) {
                                             Ok(_) => synced.lock(),
                                             Err(err) => {
@@ -243,17 +543,19 @@ starting or ending nail of the line, so avoiding the tracing of edges is usually
                                 ComputationState::Completed(computation) => computation,
                                 _ => unsafe { core::hint::unreachable_unchecked() },
                             };
+                        let args = self.args.clone();
                         rayon::spawn(move || {
                             match FileDialog::new()
                                 .set_title("Save instructions file")
                                 .add_filter("Plain Text Document", &["txt"])
+                                .add_filter("JSON Document", &["json"])
+                                .add_filter("CSV Document", &["csv"])
                                 .save_file()
                             {
                                 Some(path) => {
-                                    let instructions = computation.build_instructions();
-                                    match File::create(path.clone()).and_then(|file| {
-                                        BufWriter::new(file).write_all(instructions.as_bytes())
-                                    }) {
+                                    let saved =
+                                        args::save_instructions(computation.as_ref(), &args, &path);
+                                    match saved {
                                         Ok(_) => match open::that(path) {
                                             Ok(_) => synced.lock(),
                                             Err(err) => {
@@ -277,44 +579,167 @@ starting or ending nail of the line, so avoiding the tracing of edges is usually
                             }
                         });
                     }
+                    egui::ComboBox::from_id_salt("template_paper")
+                        .selected_text(match self.template_paper {
+                            None => "Single page",
+                            Some(Paper::A4) => "A4",
+                            Some(Paper::Letter) => "Letter",
+                        })
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.template_paper, None, "Single page");
+                            ui.selectable_value(&mut self.template_paper, Some(Paper::A4), "A4");
+                            ui.selectable_value(
+                                &mut self.template_paper,
+                                Some(Paper::Letter),
+                                "Letter",
+                            );
+                        })
+                        .response
+                        .on_hover_text("Paper the nail template is printed on, large boards are tiled \
+across several sheets.");
+                    if ui.button("Save template").clicked() {
+                        let board = match &self.computation {
+                            ComputationState::Completed(computation) => {
+                                self.args.physical_board(&computation.grid())
+                            }
+                            _ => None,
+                        };
+                        match board {
+                            None => {
+                                self.message = Some(Message::error(
+                                    "The nail template needs the physical units of the board.",
+                                ))
+                            }
+                            Some(board) => {
+                                self.sync_data.lock().computation = ComputationState::Running;
+                                let synced = self.sync_data.clone();
+                                let computation = match mem::replace(
+                                    &mut self.computation,
+                                    ComputationState::Running,
+                                ) {
+                                    ComputationState::Completed(computation) => computation,
+                                    _ => unsafe { core::hint::unreachable_unchecked() },
+                                };
+                                let paper = self.template_paper;
+                                rayon::spawn(move || {
+                                    match FileDialog::new()
+                                        .set_title("Save nail template")
+                                        .add_filter("PDF Document", &["pdf"])
+                                        .add_filter("SVG Image", &["svg"])
+                                        .save_file()
+                                    {
+                                        Some(path) => {
+                                            let template = computation.template(&board);
+                                            let template = match paper {
+                                                Some(paper) => template.with_paper(paper),
+                                                None => template,
+                                            };
+                                            match args::save_template(&template, &path) {
+                                                Ok(paths) => match paths
+                                                    .first()
+                                                    .map(open::that)
+                                                    .transpose()
+                                                {
+                                                    Ok(_) => synced.lock(),
+                                                    Err(err) => {
+                                                        let mut lock = synced.lock();
+                                                        lock.message = Some(Message::error(err));
+                                                        lock
+                                                    }
+                                                },
+                                                Err(err) => {
+                                                    let mut lock = synced.lock();
+                                                    lock.message = Some(Message::error(err));
+                                                    lock
+                                                }
+                                            }
+                                            .computation = ComputationState::Completed(computation);
+                                        }
+                                        None => {
+                                            synced.lock().computation =
+                                                ComputationState::Completed(computation)
+                                        }
+                                    }
+                                });
+                            }
+                        }
+                    }
+                    if ui.button("Save machine file").clicked() {
+                        self.sync_data.lock().computation = ComputationState::Running;
+                        let synced = self.sync_data.clone();
+                        let computation =
+                            match mem::replace(&mut self.computation, ComputationState::Running) {
+                                ComputationState::Completed(computation) => computation,
+                                _ => unsafe { core::hint::unreachable_unchecked() },
+                            };
+                        let machine = self.args.machine.machine();
+                        rayon::spawn(move || {
+                            match FileDialog::new()
+                                .set_title("Save machine file")
+                                .add_filter("G-code", &["gcode", "nc", "ngc"])
+                                .add_filter("Motion Commands", &["txt"])
+                                .save_file()
+                            {
+                                Some(path) => {
+                                    let saved = computation
+                                        .motions(&machine)
+                                        .map_err(|err| err.to_string())
+                                        .and_then(|motions| {
+                                            args::save_motions(&motions, &path)
+                                                .map_err(|err| err.to_string())
+                                        });
+                                    match saved {
+                                        Ok(_) => synced.lock(),
+                                        Err(err) => {
+                                            let mut lock = synced.lock();
+                                            lock.message = Some(Message::error(err));
+                                            lock
+                                        }
+                                    }
+                                    .computation = ComputationState::Completed(computation);
+                                }
+                                None => {
+                                    synced.lock().computation =
+                                        ComputationState::Completed(computation)
+                                }
+                            }
+                        });
+                    }
                     //});
                 }
             }
             if let Some(message) = &self.message {
                 message.draw(ui);
             }
+            if let Some(warning) = &self.warning {
+                warning.draw(ui);
+            }
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                 if ui.button("Load Config").clicked() {
                     self.args_state = ArgsState::Waiting;
                     self.sync_data.lock().args = SyncArgs::Waiting;
                     let data = self.sync_data.clone();
                     rayon::spawn(move || {
-                        let mut buffer = Vec::new();
                         if let Some(path) = FileDialog::new()
                             .set_title("Load configuration file")
-                            .add_filter("String Art Configuration File", &["sac"])
+                            .add_filter("String Art Configuration File", &config::EXTENSIONS)
+                            .add_filter("Binary", &["sac"])
+                            .add_filter("JSON", &["json"])
+                            .add_filter("TOML", &["toml"])
                             .pick_file()
                         {
-                            let (mut data, args) = match File::open(path)
-                                .and_then(|mut file| file.read_to_end(&mut buffer))
-                            {
-                                Ok(_) => match bincode::deserialize::<Args>(&buffer) {
-                                    Ok(args) => (data.lock(), Some(args)),
-                                    Err(_) => {
-                                        let mut data = data.lock();
-                                        data.message = Some(Message::error(
-                                            "Failed to read the file: the file is corrupted.",
-                                        ));
-                                        (data, None)
-                                    }
-                                },
+                            let (mut data, args) = match config::load(&path) {
+                                Ok(args) => (data.lock(), Some(args)),
                                 Err(err) => {
                                     let mut data = data.lock();
-                                    data.message = Some(Message::error(err));
+                                    data.message = Some(Message::error(format!(
+                                        "Failed to read the file: {}",
+                                        err
+                                    )));
                                     (data, None)
                                 }
                             };
-                            data.args = SyncArgs::Done(args)
+                            data.args = SyncArgs::Done(args.map(Box::new))
                         }
                     })
                 }
@@ -322,15 +747,14 @@ starting or ending nail of the line, so avoiding the tracing of edges is usually
                     let args = self.args.clone();
                     let data = self.sync_data.clone();
                     rayon::spawn(move || {
-                        let json = bincode::serialize(&args).unwrap();
                         let path = FileDialog::new()
                             .set_title("Save configuration file")
-                            .add_filter("String Art Configuration File", &["sac"])
+                            .add_filter("Binary", &["sac"])
+                            .add_filter("JSON", &["json"])
+                            .add_filter("TOML", &["toml"])
                             .save_file();
                         if let Some(path) = path {
-                            if let Err(err) = File::create(path)
-                                .and_then(|file| BufWriter::new(file).write_all(&json))
-                            {
+                            if let Err(err) = config::save(&args, &path) {
                                 data.lock().message = Some(Message::error(err));
                             }
                         }
@@ -338,6 +762,81 @@ starting or ending nail of the line, so avoiding the tracing of edges is usually
                 }
             });
         });
+
+        if let Some(quality) = &self.quality {
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label(format!(
+                    "Mean ΔE: {:.2}   CIEDE2000: {:.2}   SSIM: {:.4}   PSNR: {:.2} dB",
+                    quality.mean_delta_e, quality.mean_ciede2000, quality.ssim, quality.psnr
+                ))
+                .on_hover_text(format!("Measured against the computed image in Lab space, with \
+the image export style. The heatmap goes from black through purple, red and orange to pale \
+yellow at a CIEDE2000 difference of {}.",
+                    HEATMAP_MAX_DELTA_E));
+            });
+        }
+
+        if let Some((names, length)) = &self.thread_length {
+            let scale = self
+                .args
+                .physical_diameter()
+                .map(|diameter| length.millimeters_per_pixel(diameter as f64));
+            let format = |pixels: f64| match scale {
+                Some(scale) => format!("{:.2} m", pixels * scale / 1000.0),
+                None => format!("{:.0} px", pixels),
+            };
+            ui.horizontal_wrapped(|ui| {
+                ui.label(format!("Thread: {}", format(length.total())))
+                    .on_hover_text("Length of thread, wraps around the nails included. Lengths are \
+physical with physical units or a physical image export size.");
+                for (name, &pixels) in names.iter().zip(length.per_color()) {
+                    ui.label(format!("{}: {}", name, format(pixels)));
+                }
+            });
+        }
+
+        if self.source.is_some() || self.preview.is_some() {
+            ui.separator();
+            ui.horizontal(|ui| {
+                for texture in [&self.source, &self.preview, &self.heatmap].into_iter().flatten() {
+                    ui.add(egui::Image::new(texture).max_height(PREVIEW_HEIGHT));
+                }
+            });
+        }
+    }
+}
+
+// Hands the result of a computation over to the UI, measured against its image.
+fn complete(
+    args: &Args,
+    verboser: &mut SyncedVerboser,
+    result: Result<Box<dyn Computation>, args::Error>,
+) {
+    match result {
+        Ok(algorithm) => {
+            let cancelled = verboser.is_cancelled();
+            // Nails are not part of the image, so only the threads are measured.
+            let pixels = args.in_pixels(&algorithm.grid());
+            let style = RasterStyle {
+                nails: None,
+                ..pixels.export.style(pixels.tickness, pixels.nail_shape, args.board())
+            };
+            let quality = algorithm.quality(&style);
+            let mut synced = verboser.lock();
+            synced.quality = quality;
+            if cancelled {
+                synced.message = Some(Message::warning(
+                    "Computation cancelled, the threads drawn so far are kept.",
+                ));
+            }
+            synced.computation = ComputationState::Completed(algorithm)
+        }
+        Err(err) => {
+            let mut synced = verboser.lock();
+            synced.message = Some(Message::error(err));
+            synced.computation = ComputationState::Idle;
+        }
     }
 }
 
@@ -348,7 +847,7 @@ impl eframe::App for App {
                 let mut data = self.sync_data.lock();
                 if let SyncArgs::Done(new_args) = &mut data.args {
                     if let Some(args) = new_args.take() {
-                        self.args = args;
+                        self.args = *args;
                     }
                     self.message = data.message.take();
                     self.args_state = ArgsState::Ready;
@@ -359,12 +858,12 @@ impl eframe::App for App {
     }
 
     fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
-        let json = bincode::serialize(&self.args).unwrap();
+        let bytes = config::to_vec(&self.args, Format::Binary).unwrap();
         if let Some(path) = config_path() {
-            if path.parent().is_some_and(|parent| parent.exists() || { std::fs::create_dir_all(&parent).is_ok() }){           
+            if path.parent().is_some_and(|parent| parent.exists() || { std::fs::create_dir_all(parent).is_ok() }){           
                 match File::create(path.clone()){
                     Ok(mut file) =>  {                   
-                        file.write_all(&json).unwrap();
+                        file.write_all(&bytes).unwrap();
                     },
                     Err(err) => {
                         let string = err.to_string();
@@ -382,10 +881,7 @@ fn main() {
         eframe::NativeOptions {
             viewport: ViewportBuilder {
                 icon: Some(Arc::new(IconData {
-                    rgba: include_bytes!("../assets/icon.rgba")
-                        .iter()
-                        .copied()
-                        .collect(),
+                    rgba: include_bytes!("../assets/icon.rgba").to_vec(),
                     width: 384,
                     height: 384,
                 })),
@@ -400,20 +896,15 @@ fn main() {
                 .or_else(config_path);
 
             Ok(Box::new(match path{
-                Some(path) => {
-                    let mut buffer = Vec::new();                    
-                    match File::open(path){
-                        Ok(mut file) => match file.read_to_end(&mut buffer){
-                            Ok(_) => {
-                                match bincode::deserialize(&buffer){
-                                    Ok(args) => App::new(args),
-                                    Err(_) => App::with_error("Configuration file is corrupted, reverting to default settings."),
-                                }
-                            },
-                            Err(err) => App::with_error(err),
-                        },
-                        Err(_) => App::new(Args::default()),
+                Some(path) => match config::load(&path) {
+                    Ok(args) => App::new(args),
+                    Err(config::Error::Io(err)) if err.kind() == std::io::ErrorKind::NotFound => {
+                        App::new(Args::default())
                     }
+                    Err(err) => App::with_error(format!(
+                        "{} Reverting to default settings.",
+                        err
+                    )),
                 },
                 None => App::new(Args::default()),
             }))
@@ -422,5 +913,4 @@ fn main() {
     .unwrap();
 }
 
-mod args;
 mod synced;
@@ -1,13 +1,17 @@
-use std::{ops::Deref, sync::Arc};
+use std::{ops::Deref, path::PathBuf, sync::Arc};
 
 use egui::mutex::Mutex;
 use string_art::{
-    nails,
-    verboser::{self, Verboser},
-    Algorithm,
+    verboser::{self, CancellationToken, Verboser},
+    Checkpoint, Quality,
+};
+use string_art_ui::{
+    args::{ArgLineCountState, Args, MEGABYTE},
+    checkpoint,
+    computation::{Computation, Progress},
 };
 
-use crate::{args::{ArgLineCountState, Args}, SyncArgs};
+use crate::SyncArgs;
 
 #[derive(Default)]
 pub struct Synced<T>(Arc<Mutex<T>>);
@@ -26,14 +30,29 @@ impl<T> Deref for Synced<T> {
     }
 }
 
+/// Threads between two refreshes of the preview.
+pub const PREVIEW_INTERVAL: usize = 100;
+
+/// Threads between two checkpoints.
+pub const CHECKPOINT_INTERVAL: usize = 500;
+
+pub fn color_image(image: &image::RgbImage) -> egui::ColorImage {
+    egui::ColorImage::from_rgb([image.width() as usize, image.height() as usize], image.as_raw())
+}
+
 pub struct SyncedVerboser {
     synced: Synced<SyncData>,
     threads: usize,
-    nails: usize,
+    nails: Option<usize>,
+    // Threads computed so far.
+    computed: usize,
+    cancel: CancellationToken,
+    // File the checkpoints are saved to, with the settings they are saved with.
+    checkpoints: Option<(PathBuf, Args)>,
 }
 
 impl SyncedVerboser {
-    pub fn new(synced: Synced<SyncData>, args: &Args) -> Self {
+    pub fn new(synced: Synced<SyncData>, args: &Args, cancel: CancellationToken) -> Self {
         Self {
             synced,
             threads: match args.line_config.state {
@@ -43,15 +62,42 @@ impl SyncedVerboser {
                     .sum(),
                 ArgLineCountState::Auto => args.line_config.auto.threads,
             },
-            nails: args.nails.get(),
+            nails: (!args.frame_shape.is_imported()).then(|| args.nails.get()),
+            computed: 0,
+            cancel,
+            checkpoints: None,
+        }
+    }
+
+    /// Saves a checkpoint of the computation to `path` every [`CHECKPOINT_INTERVAL`] threads.
+    pub fn with_checkpoints(self, path: PathBuf, args: &Args) -> Self {
+        Self {
+            checkpoints: Some((path, args.clone())),
+            ..self
         }
     }
     
+    /// Sets the threads the computation is expected to end with.
+    pub fn with_threads(self, threads: usize) -> Self {
+        Self { threads, ..self }
+    }
+
     pub fn verbose(&mut self, message: Message) {
         self.synced.lock().message = Some(message);
     }
 }
 
+impl Progress for SyncedVerboser {
+    fn loading_image(&mut self) {
+        self.verbose(Message::new(MessageType::LoadingImage, "Loading image..."));
+    }
+
+    fn source(&mut self, image: &image::DynamicImage) {
+        let image = image.to_rgb8();
+        self.synced.lock().source = Some(color_image(&image));
+    }
+}
+
 impl Deref for SyncedVerboser {
     type Target = Synced<SyncData>;
 
@@ -61,41 +107,93 @@ impl Deref for SyncedVerboser {
 }
 
 impl Verboser for SyncedVerboser {
+    fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    fn preview_interval(&self) -> Option<usize> {
+        Some(PREVIEW_INTERVAL)
+    }
+
+    fn preview(&mut self, image: image::RgbImage) {
+        self.synced.lock().preview = Some(color_image(&image));
+    }
+
+    fn checkpoint_interval(&self) -> Option<usize> {
+        self.checkpoints.as_ref().map(|_| CHECKPOINT_INTERVAL)
+    }
+
+    fn checkpoint(&mut self, checkpoint: Checkpoint) {
+        if let Some((path, args)) = &self.checkpoints {
+            if let Err(err) = checkpoint::save(checkpoint, args, path) {
+                self.synced.lock().warning =
+                    Some(Message::warning(format!("Failed to save the checkpoint: {}", err)));
+            }
+        }
+    }
+
     fn verbose(&mut self, message: verboser::Message) {
-        self.verbose(match message {
+        let message = match message {
             verboser::Message::CreatingNail(idx) => Message {
                 message_type: MessageType::CreatingNail,
-                message: format!(
-                    "Nailing {}/{}, {}%",
-                    idx,
-                    self.nails,
-                    (idx * 100) / self.nails
-                ),
+                message: match self.nails {
+                    Some(nails) => format!("Nailing {}/{}, {}%", idx, nails, (idx * 100) / nails),
+                    None => format!("Nailing {}", idx),
+                },
             },
             verboser::Message::Baking => Message {
                 message_type: MessageType::Baking,
                 message: String::from("Baking"),
             },
+            verboser::Message::BakingPixels { bytes, budget } => {
+                if bytes > budget {
+                    self.synced.lock().warning = Some(Message::warning(format!(
+                        "The pixel cache would take {} MB, over the {} MB budget, so lines are \
+rasterized on every step.",
+                        bytes.div_ceil(MEGABYTE),
+                        budget / MEGABYTE
+                    )));
+                }
+                Message {
+                    message_type: MessageType::Baking,
+                    message: format!("Baking pixels, {} MB", bytes.div_ceil(MEGABYTE)),
+                }
+            }
             verboser::Message::Dithering(idx, total) => Message {
                 message_type: MessageType::Dithering,
-                message: format!("Dithering {}/{}, {}%", idx, total, (idx * 100) / total),
+                message: format!("Dithering {}/{}, {}%", idx, total, (idx * 100) / total.max(1)),
             },
-            verboser::Message::Computing(idx) => Message {
+            verboser::Message::Computing(idx) => {
+                self.computed = idx;
+                Message {
+                    message_type: MessageType::Computing,
+                    message: format!(
+                        "Computing {}/{}, {}%",
+                        idx,
+                        self.threads,
+                        (idx * 100) / self.threads.max(1)
+                    ),
+                }
+            }
+            verboser::Message::Stopped(reason) => Message {
                 message_type: MessageType::Computing,
-                message: format!(
-                    "Computing {}/{}, {}%",
-                    idx,
-                    self.threads,
-                    (idx * 100) / self.threads
-                ),
+                message: format!("Stopped after {} threads: {}.", self.computed, reason),
             },
-        });
+        };
+        self.verbose(message);
     }
 }
 
 #[derive(Default)]
 pub struct SyncData {
     pub message: Option<Message>,
+    pub warning: Option<Message>,
+    /// Image being processed, sent once it is loaded.
+    pub source: Option<egui::ColorImage>,
+    /// Threads drawn so far, refreshed every [`PREVIEW_INTERVAL`] threads.
+    pub preview: Option<egui::ColorImage>,
+    /// Quality of the result, measured once it is computed.
+    pub quality: Option<Quality>,
     pub computation: ComputationState,
     pub args: SyncArgs,
 }
@@ -106,6 +204,7 @@ pub enum MessageType {
     CreatingNail,
     Dithering,
     Computing,
+    Warning,
     Error,
 }
 
@@ -123,52 +222,34 @@ impl Message {
     }
 
     pub fn draw(&self, ui: &mut egui::Ui) {
-        if let MessageType::Error = self.message_type {
-            ui.label(
+        match self.message_type {
+            MessageType::Error => ui.label(
                 egui::RichText::new(&self.message)
                     .italics()
                     .color(egui::Color32::RED),
-            );
-        } else {
-            ui.label(&self.message);
-        }
+            ),
+            MessageType::Warning => ui.label(
+                egui::RichText::new(&self.message)
+                    .italics()
+                    .color(egui::Color32::YELLOW),
+            ),
+            _ => ui.label(&self.message),
+        };
     }
 
     pub fn error(message: impl ToString) -> Self {
         Self::new(MessageType::Error, message)
     }
+
+    pub fn warning(message: impl ToString) -> Self {
+        Self::new(MessageType::Warning, message)
+    }
 }
 
+#[derive(Default)]
 pub enum ComputationState {
+    #[default]
     Idle,
     Running,
     Completed(Box<dyn Computation>),
 }
-
-impl Default for ComputationState {
-    fn default() -> Self {
-        Self::Idle
-    }
-}
-
-pub trait Computation: Send + Sync {
-    fn build_svg(&self, tickness: f32) -> svg::Document;
-
-    fn build_instructions(&self) -> String;
-
-    fn get_line_config(&self) -> string_art::LineConfig;
-}
-
-impl<N: nails::Handle<Link: ToString>> Computation for Algorithm<N> {
-    fn build_svg(&self, tickness: f32) -> svg::Document {
-        self.build_svg(tickness)
-    }
-
-    fn build_instructions(&self) -> String {
-        self.build_instructions()
-    }
-    
-    fn get_line_config(&self) -> string_art::LineConfig {
-        self.line_selector().into()
-    }
-}